@@ -0,0 +1,160 @@
+//! Parallel compilation of many documents
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::error::MdxError;
+use crate::{compile, MdxCompileOptions, MdxCompileResult};
+
+/// A document for [`compile_many`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MdxInput {
+    pub source: String,
+    /// Path used in errors and source maps (overrides
+    /// `MdxCompileOptions::filepath`)
+    pub filepath: Option<String>,
+}
+
+impl MdxInput {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            filepath: None,
+        }
+    }
+
+    pub fn with_filepath(mut self, filepath: impl Into<String>) -> Self {
+        self.filepath = Some(filepath.into());
+        self
+    }
+}
+
+impl From<&str> for MdxInput {
+    fn from(source: &str) -> Self {
+        Self::new(source)
+    }
+}
+
+impl From<String> for MdxInput {
+    fn from(source: String) -> Self {
+        Self::new(source)
+    }
+}
+
+/// Compile many MDX documents across threads
+///
+/// Results are in input order. Plugins are shared by all documents, but each
+/// document has its own `PluginContext`, so state such as collected images
+/// never mixes between documents.
+///
+/// ```
+/// use bunny_mdx::{compile_many, MdxCompileOptions, MdxInput};
+///
+/// let options = MdxCompileOptions::new().with_default_plugins();
+/// let results = compile_many(
+///     [
+///         MdxInput::new("![a](/a.png)").with_filepath("a.mdx"),
+///         MdxInput::new("![b](/b.png)").with_filepath("b.mdx"),
+///     ],
+///     &options,
+/// );
+///
+/// assert_eq!(results[0].as_ref().unwrap().images, ["/a.png"]);
+/// assert_eq!(results[1].as_ref().unwrap().images, ["/b.png"]);
+/// ```
+pub fn compile_many<I>(
+    inputs: I,
+    options: &MdxCompileOptions,
+) -> Vec<Result<MdxCompileResult, Box<MdxError>>>
+where
+    I: IntoIterator,
+    I::Item: Into<MdxInput>,
+{
+    let inputs: Vec<MdxInput> = inputs.into_iter().map(Into::into).collect();
+
+    let compile_one = |input: &MdxInput| {
+        let mut file_options = options.clone();
+        if input.filepath.is_some() {
+            file_options.filepath = input.filepath.clone();
+        }
+        compile(&input.source, file_options)
+    };
+
+    // Threads are unavailable on some targets (wasm32), where this reports 1
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(inputs.len());
+    if workers <= 1 {
+        return inputs.iter().map(compile_one).collect();
+    }
+
+    // Workers take the next document from a shared counter, so one slow
+    // document doesn't hold up a whole chunk
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<_>>> = inputs.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(index) else {
+                    break;
+                };
+                let result = compile_one(input);
+                *results[index].lock().expect("Result slot mutex poisoned") = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|slot| {
+            slot.into_inner()
+                .expect("Result slot mutex poisoned")
+                .expect("Every document is compiled")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::ImageOptimizationPlugin;
+
+    #[test]
+    fn test_results_keep_input_order() {
+        let inputs: Vec<String> = (0..64).map(|i| format!("# Doc {}", i)).collect();
+        let results = compile_many(inputs, &MdxCompileOptions::new());
+
+        assert_eq!(results.len(), 64);
+        for (i, result) in results.iter().enumerate() {
+            let code = &result.as_ref().unwrap().code;
+            assert!(code.contains(&format!("\"Doc {}\"", i)));
+        }
+    }
+
+    #[test]
+    fn test_each_document_has_its_own_plugin_state() {
+        let options = MdxCompileOptions::new().with_plugin(ImageOptimizationPlugin::new());
+        let inputs: Vec<String> = (0..32).map(|i| format!("![{0}](/{0}.png)", i)).collect();
+
+        for (i, result) in compile_many(inputs, &options).into_iter().enumerate() {
+            assert_eq!(result.unwrap().images, [format!("/{}.png", i)]);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_per_document() {
+        let results = compile_many(
+            [
+                MdxInput::new("# Fine").with_filepath("a.mdx"),
+                MdxInput::new("{foo(}").with_filepath("b.mdx"),
+            ],
+            &MdxCompileOptions::new(),
+        );
+
+        assert!(results[0].is_ok());
+        let err = results[1].as_ref().unwrap_err();
+        assert_eq!(err.file.as_deref(), Some("b.mdx"));
+    }
+}
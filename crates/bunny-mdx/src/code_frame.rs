@@ -0,0 +1,120 @@
+//! Code frames: source lines with the problem underlined
+//!
+//! ```text
+//!   |
+//! 1 | # Title
+//! 2 | Some <Callout>text
+//!   |      ^^^^^^^^^
+//! 3 | more text
+//! ```
+//!
+//! Used by [`MdxError::render`](crate::MdxError::render) for CLI output and
+//! server logs; diagnostics can be rendered the same way from their span.
+
+use crate::diagnostic::SourceSpan;
+
+/// Lines shown before and after the underlined line
+const CONTEXT_LINES: usize = 1;
+
+/// Render the lines around `span` with carets under it
+///
+/// Spans over several lines are underlined to the end of their first line.
+/// Returns an empty string when the span doesn't start on a character
+/// boundary of `source` (it belongs to another source).
+pub fn render(source: &str, span: &SourceSpan) -> String {
+    let start = span.start.offset.min(source.len());
+    if !source.is_char_boundary(start) {
+        return String::new();
+    }
+    let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |index| start + index);
+    let end = span.end.offset.clamp(start, line_end);
+
+    // Keep tabs so the carets line up with the source line
+    let padding: String = source[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = source
+        .get(start..end)
+        .map_or(0, |underlined| underlined.chars().count())
+        .max(1);
+    let underline = format!("{}{}", padding, "^".repeat(width));
+
+    // Line number of the underlined line, counted from the source itself
+    let line = source[..line_start].matches('\n').count() + 1;
+    frame(source, line, &underline)
+}
+
+/// Render `line` of `source` (1-indexed) with `underline` below it
+fn frame(source: &str, line: usize, underline: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (line + CONTEXT_LINES).min(lines.len().max(line));
+    let gutter = last.to_string().len();
+
+    let mut out = format!("{} |\n", " ".repeat(gutter));
+    for number in first..=last {
+        let text = lines.get(number - 1).copied().unwrap_or_default();
+        let row = format!("{:>gutter$} | {}", number, text);
+        out.push_str(row.trim_end());
+        out.push('\n');
+        if number == line {
+            out.push_str(&format!("{} | {}\n", " ".repeat(gutter), underline));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::SourcePoint;
+
+    fn span(source: &str, start: usize, end: usize) -> SourceSpan {
+        let point = |offset: usize| {
+            let (line, column) = crate::utils::offset_to_line_col(source, offset);
+            SourcePoint {
+                line,
+                column,
+                offset,
+            }
+        };
+        SourceSpan {
+            start: point(start),
+            end: point(end),
+        }
+    }
+
+    #[test]
+    fn test_underlines_span() {
+        let source = "# Title\n\nSome <Callout>text\nmore text\n\nlast";
+        let start = source.find("<Callout>").unwrap();
+        assert_eq!(
+            render(source, &span(source, start, start + 9)),
+            "  |\n2 |\n3 | Some <Callout>text\n  |      ^^^^^^^^^\n4 | more text\n"
+        );
+    }
+
+    #[test]
+    fn test_points_and_multiline_spans() {
+        let source = "a\n\tb {c\nd}";
+        let start = source.find('{').unwrap();
+        assert_eq!(
+            render(source, &span(source, start, start)),
+            "  |\n1 | a\n2 | \tb {c\n  | \t  ^\n3 | d}\n"
+        );
+        assert_eq!(
+            render(source, &span(source, start, source.len())),
+            "  |\n1 | a\n2 | \tb {c\n  | \t  ^^\n3 | d}\n"
+        );
+
+        // End of file
+        assert_eq!(
+            render(source, &span(source, source.len(), source.len())),
+            "  |\n2 | \tb {c\n3 | d}\n  |   ^\n"
+        );
+    }
+}
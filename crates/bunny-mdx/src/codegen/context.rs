@@ -1,7 +1,17 @@
 //! Code generation context
 
-use markdown::mdast::AlignKind;
-use std::collections::HashSet;
+use anyhow::Result;
+use markdown::mdast::{AlignKind, Definition, Node};
+use markdown::unist::Position;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::diagnostic::Diagnostic;
+use crate::error::MdxError;
+use crate::plugins::HeadingAnchor;
+use crate::stats::DocumentStats;
+use crate::url_policy::{UrlPolicy, UrlViolation};
+use crate::utils::normalize_identifier;
+use crate::RawHtml;
 
 /// Table-specific state used while generating table markup
 #[derive(Clone, Debug, Default)]
@@ -11,6 +21,15 @@ pub struct TableContext {
     pub col_index: usize,
 }
 
+/// A component resolved through the `_components` map
+#[derive(Clone, Debug)]
+pub struct ComponentReference {
+    /// Member expression used to access the component (`_components.Foo`)
+    pub access: String,
+    /// Position of the first usage in the MDX source
+    pub position: Option<Position>,
+}
+
 /// Context for tracking position-based keys and table state
 #[derive(Default)]
 pub struct CodegenContext {
@@ -26,6 +45,42 @@ pub struct CodegenContext {
     /// then "Button" will be in this set, and `<Button>` will compile
     /// to `_jsx(Button, ...)` instead of `_jsx(_components.Button, ...)`.
     pub imported_components: HashSet<String>,
+
+    /// Link/image definitions keyed by normalized identifier.
+    ///
+    /// Populated by `collect_definitions()` before conversion so that
+    /// `[text][label]` references can be resolved regardless of where
+    /// the `[label]: url` definition appears in the document.
+    pub definitions: HashMap<String, Definition>,
+
+    /// Non-fatal issues found during conversion
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Provider components referenced by JSX in the document, by name.
+    ///
+    /// Used in development mode to throw a descriptive error when one of
+    /// them is missing at render time.
+    pub component_references: BTreeMap<String, ComponentReference>,
+
+    /// How raw HTML nodes are compiled
+    pub raw_html: RawHtml,
+
+    /// Set when a node renders a `_Fragment`, so the runtime's `Fragment`
+    /// is made available
+    pub uses_fragment: bool,
+
+    /// Policy applied to `href`/`src` URLs (unchecked when `None`)
+    pub url_policy: Option<UrlPolicy>,
+
+    /// Statistics about the nodes converted so far
+    pub stats: DocumentStats,
+
+    /// Ids planned by `HeadingIdPlugin` for the document's headings, in
+    /// document order
+    pub heading_ids: VecDeque<String>,
+
+    /// Link added to headings with an id (`HeadingIdPlugin::with_anchor`)
+    pub heading_anchor: Option<HeadingAnchor>,
 }
 
 impl CodegenContext {
@@ -76,6 +131,74 @@ impl CodegenContext {
             .and_then(|ctx| ctx.alignments.get(ctx.col_index))
     }
 
+    /// Walk the AST and record every definition
+    ///
+    /// Per CommonMark, the first definition for a label wins.
+    pub fn collect_definitions(&mut self, node: &Node) {
+        if let Node::Definition(definition) = node {
+            self.definitions
+                .entry(normalize_identifier(&definition.identifier))
+                .or_insert_with(|| definition.clone());
+        }
+
+        if let Some(children) = node.children() {
+            for child in children {
+                self.collect_definitions(child);
+            }
+        }
+    }
+
+    /// Look up a definition by (unnormalized) identifier
+    pub fn definition(&self, identifier: &str) -> Option<&Definition> {
+        self.definitions.get(&normalize_identifier(identifier))
+    }
+
+    /// Record a `_components.*` reference (the first usage wins)
+    pub fn reference_component(&mut self, name: &str, access: &str, position: Option<&Position>) {
+        self.component_references
+            .entry(name.to_string())
+            .or_insert_with(|| ComponentReference {
+                access: access.to_string(),
+                position: position.cloned(),
+            });
+    }
+
+    /// Apply the URL policy to a `href`/`src` value
+    ///
+    /// Returns `None` when the attribute should be left out. With
+    /// `UrlViolation::Error`, the error wraps an [`MdxError`] with the
+    /// location of the URL.
+    pub fn sanitize_url(&self, url: &str, position: Option<&Position>) -> Result<Option<String>> {
+        let Some(policy) = &self.url_policy else {
+            return Ok(Some(url.to_string()));
+        };
+        match policy.check(url) {
+            Ok(url) => Ok(Some(url)),
+            Err(scheme) => match policy.on_violation {
+                UrlViolation::Strip => Ok(None),
+                UrlViolation::Replace => Ok(Some("#".to_string())),
+                UrlViolation::Error => {
+                    let mut err = MdxError::disallowed_url(url, &scheme, &policy.allowed_schemes);
+                    if let Some(position) = position {
+                        err = err.with_location(position.start.line, position.start.column);
+                    }
+                    Err(err.into())
+                }
+            },
+        }
+    }
+
+    /// Id of the next heading: its `{#custom-id}`, or the planned one
+    pub fn next_heading_id(&mut self, custom_id: Option<&str>) -> Option<String> {
+        // Planned ids include custom ones, so one is taken either way
+        let planned = self.heading_ids.pop_front();
+        custom_id.map(str::to_string).or(planned)
+    }
+
+    pub fn push_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
     pub fn next_key(&mut self) -> String {
         let key = format!("mdx-{}", self.key_counter);
         self.key_counter += 1;
@@ -0,0 +1,305 @@
+//! Element lookup in a compiled program
+//!
+//! `MdxPlugin::transform_program` receives the output as an `oxc_ast`
+//! program, where elements are runtime calls (`_jsx`, `_jsxs`, `_jsxDEV`)
+//! or JSX syntax depending on the output format. [`add_element_props`]
+//! handles both shapes, so plugins don't have to.
+
+use oxc_allocator::Vec as ArenaVec;
+use oxc_ast::ast::*;
+use oxc_ast::AstBuilder;
+use oxc_ast_visit::{walk_mut, VisitMut};
+use oxc_span::SPAN;
+
+use super::emit::property_key;
+
+/// Runtime functions whose first two arguments are a tag and a props object
+const RUNTIME_CALLEES: &[&str] = &["_jsx", "_jsxs", "_jsxDEV"];
+
+/// An element found in a compiled program
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ElementInfo {
+    /// Tag as written in the output: a member chain like `_components.h1`,
+    /// or the name of an intrinsic element (`div`)
+    pub tag: String,
+    /// Static text content of the element and its descendants
+    pub text: String,
+    /// Names of the props already set on the element
+    pub props: Vec<String>,
+}
+
+/// Add string props to elements of a compiled program
+///
+/// `props_for` is called for every element in document order and returns
+/// the `(name, value)` props to add. They are inserted right after the
+/// leading spreads (`{...props, id: "x", children: ...}`), so they take
+/// precedence over spread `props`. Props the element already sets are kept.
+pub fn add_element_props<'a, F>(program: &mut Program<'a>, ast: AstBuilder<'a>, props_for: F)
+where
+    F: FnMut(&ElementInfo) -> Vec<(String, String)>,
+{
+    ElementProps { ast, props_for }.visit_program(program);
+}
+
+struct ElementProps<'a, F> {
+    ast: AstBuilder<'a>,
+    props_for: F,
+}
+
+impl<'a, F> VisitMut<'a> for ElementProps<'a, F>
+where
+    F: FnMut(&ElementInfo) -> Vec<(String, String)>,
+{
+    fn visit_call_expression(&mut self, call: &mut CallExpression<'a>) {
+        if let Some(info) = call_info(call) {
+            let props = (self.props_for)(&info);
+            if let Some(Argument::ObjectExpression(object)) = call.arguments.get_mut(1) {
+                let index = leading_spreads(&object.properties);
+                let new_props = props.iter().filter(|(name, _)| !info.props.contains(name));
+                for (offset, (name, value)) in new_props.enumerate() {
+                    object
+                        .properties
+                        .insert(index + offset, object_property(self.ast, name, value));
+                }
+            }
+        }
+        walk_mut::walk_call_expression(self, call);
+    }
+
+    fn visit_jsx_element(&mut self, element: &mut JSXElement<'a>) {
+        let info = jsx_info(element);
+        let props = (self.props_for)(&info);
+        let attributes = &mut element.opening_element.attributes;
+        let index = attributes
+            .iter()
+            .take_while(|attribute| matches!(attribute, JSXAttributeItem::SpreadAttribute(_)))
+            .count();
+        let new_props = props.iter().filter(|(name, _)| !info.props.contains(name));
+        for (offset, (name, value)) in new_props.enumerate() {
+            attributes.insert(index + offset, jsx_attribute(self.ast, name, value));
+        }
+        walk_mut::walk_jsx_element(self, element);
+    }
+}
+
+/// Tag, text and prop names of a runtime call
+fn call_info(call: &CallExpression<'_>) -> Option<ElementInfo> {
+    let Expression::Identifier(callee) = &call.callee else {
+        return None;
+    };
+    if !RUNTIME_CALLEES.contains(&callee.name.as_str()) {
+        return None;
+    }
+
+    let tag = match call.arguments.first()?.as_expression()? {
+        Expression::StringLiteral(literal) => literal.value.to_string(),
+        expression => member_chain(expression)?,
+    };
+    let mut info = ElementInfo {
+        tag,
+        ..ElementInfo::default()
+    };
+    if let Some(Argument::ObjectExpression(object)) = call.arguments.get(1) {
+        for property in &object.properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                continue;
+            };
+            let Some(name) = property.key.static_name() else {
+                continue;
+            };
+            if name == "children" {
+                collect_text(&property.value, &mut info.text);
+            } else {
+                info.props.push(name.into_owned());
+            }
+        }
+    }
+    Some(info)
+}
+
+/// Tag, text and prop names of a JSX element
+fn jsx_info(element: &JSXElement<'_>) -> ElementInfo {
+    let mut info = ElementInfo {
+        tag: jsx_name(&element.opening_element.name),
+        ..ElementInfo::default()
+    };
+    for attribute in &element.opening_element.attributes {
+        if let JSXAttributeItem::Attribute(attribute) = attribute {
+            info.props.push(match &attribute.name {
+                JSXAttributeName::Identifier(name) => name.name.to_string(),
+                JSXAttributeName::NamespacedName(name) => {
+                    format!("{}:{}", name.namespace.name, name.name.name)
+                }
+            });
+        }
+    }
+    collect_jsx_text(&element.children, &mut info.text);
+    info
+}
+
+/// `a.b.c` for identifier and static member chains
+fn member_chain(expression: &Expression<'_>) -> Option<String> {
+    match expression {
+        Expression::Identifier(identifier) => Some(identifier.name.to_string()),
+        Expression::StaticMemberExpression(member) => Some(format!(
+            "{}.{}",
+            member_chain(&member.object)?,
+            member.property.name
+        )),
+        _ => None,
+    }
+}
+
+fn jsx_name(name: &JSXElementName<'_>) -> String {
+    fn object_name(object: &JSXMemberExpressionObject<'_>) -> String {
+        match object {
+            JSXMemberExpressionObject::IdentifierReference(identifier) => {
+                identifier.name.to_string()
+            }
+            JSXMemberExpressionObject::MemberExpression(member) => {
+                format!("{}.{}", object_name(&member.object), member.property.name)
+            }
+            JSXMemberExpressionObject::ThisExpression(_) => "this".to_string(),
+        }
+    }
+
+    match name {
+        JSXElementName::Identifier(identifier) => identifier.name.to_string(),
+        JSXElementName::IdentifierReference(identifier) => identifier.name.to_string(),
+        JSXElementName::NamespacedName(name) => {
+            format!("{}:{}", name.namespace.name, name.name.name)
+        }
+        JSXElementName::MemberExpression(member) => {
+            format!("{}.{}", object_name(&member.object), member.property.name)
+        }
+        JSXElementName::ThisExpression(_) => "this".to_string(),
+    }
+}
+
+/// Append the static text of an element's `children` value
+fn collect_text(expression: &Expression<'_>, text: &mut String) {
+    match expression {
+        Expression::StringLiteral(literal) => text.push_str(&literal.value),
+        Expression::ArrayExpression(array) => {
+            for element in &array.elements {
+                if let Some(expression) = element.as_expression() {
+                    collect_text(expression, text);
+                }
+            }
+        }
+        Expression::CallExpression(call) => {
+            if let Some(info) = call_info(call) {
+                text.push_str(&info.text);
+            }
+        }
+        Expression::JSXElement(element) => collect_jsx_text(&element.children, text),
+        Expression::JSXFragment(fragment) => collect_jsx_text(&fragment.children, text),
+        _ => {}
+    }
+}
+
+fn collect_jsx_text(children: &[JSXChild<'_>], text: &mut String) {
+    for child in children {
+        match child {
+            JSXChild::Text(child) => text.push_str(&child.value),
+            JSXChild::Element(element) => collect_jsx_text(&element.children, text),
+            JSXChild::Fragment(fragment) => collect_jsx_text(&fragment.children, text),
+            JSXChild::ExpressionContainer(container) => {
+                if let Some(expression) = container.expression.as_expression() {
+                    collect_text(expression, text);
+                }
+            }
+            JSXChild::Spread(_) => {}
+        }
+    }
+}
+
+fn leading_spreads(properties: &ArenaVec<'_, ObjectPropertyKind<'_>>) -> usize {
+    properties
+        .iter()
+        .take_while(|property| matches!(property, ObjectPropertyKind::SpreadProperty(_)))
+        .count()
+}
+
+/// `name: "value"`
+fn object_property<'a>(ast: AstBuilder<'a>, name: &str, value: &str) -> ObjectPropertyKind<'a> {
+    let value = ast.expression_string_literal(SPAN, ast.atom(value), None);
+    ast.object_property_kind_object_property(
+        SPAN,
+        PropertyKind::Init,
+        property_key(ast, name),
+        value,
+        false,
+        false,
+        false,
+    )
+}
+
+/// `name={"value"}`
+fn jsx_attribute<'a>(ast: AstBuilder<'a>, name: &str, value: &str) -> JSXAttributeItem<'a> {
+    let value = ast.expression_string_literal(SPAN, ast.atom(value), None);
+    ast.jsx_attribute_item_attribute(
+        SPAN,
+        ast.jsx_attribute_name_identifier(SPAN, ast.atom(name)),
+        Some(ast.jsx_attribute_value_expression_container(SPAN, JSXExpression::from(value))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc_allocator::Allocator;
+    use oxc_codegen::Codegen;
+    use oxc_parser::Parser;
+    use oxc_span::SourceType;
+
+    /// Give every `_components.h1` an id made from its text
+    fn add_ids(code: &str) -> String {
+        let allocator = Allocator::default();
+        let mut program = Parser::new(&allocator, code, SourceType::mjs().with_jsx(true))
+            .parse()
+            .program;
+        add_element_props(&mut program, AstBuilder::new(&allocator), |element| {
+            if element.tag == "_components.h1" {
+                vec![("id".to_string(), element.text.to_lowercase())]
+            } else {
+                Vec::new()
+            }
+        });
+        Codegen::new()
+            .build(&program)
+            .code
+            .split_whitespace()
+            .collect()
+    }
+
+    #[test]
+    fn test_runtime_calls() {
+        assert_eq!(
+            add_ids(
+                r#"_jsxs(_components.h1, {...props, children: ["Hi ", _jsx(_components.em, {children: "There"})]});"#
+            ),
+            r#"_jsxs(_components.h1,{...props,id:"hithere",children:["Hi",_jsx(_components.em,{children:"There"})]});"#
+        );
+        // Props the element already sets are kept
+        assert_eq!(
+            add_ids(r#"_jsx(_components.h1, {id: "x", children: "Hi"});"#),
+            r#"_jsx(_components.h1,{id:"x",children:"Hi"});"#
+        );
+        // Other calls are left alone
+        assert_eq!(
+            add_ids(r#"h1(_components.h1, {children: "Hi"});"#),
+            r#"h1(_components.h1,{children:"Hi"});"#
+        );
+    }
+
+    #[test]
+    fn test_jsx_elements() {
+        assert_eq!(
+            add_ids(
+                r#"<_components.h1 {...props} className="a">Hi <b>{"There"}</b></_components.h1>;"#
+            ),
+            r#"<_components.h1{...props}id={"hithere"}className="a">Hi<b>{"There"}</b></_components.h1>;"#
+        );
+    }
+}
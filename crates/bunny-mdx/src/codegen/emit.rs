@@ -0,0 +1,861 @@
+//! JavaScript program construction
+//!
+//! The renderer assembles its output as an `oxc_ast` [`Program`] and prints
+//! it with `oxc_codegen`, so the compiled code is syntactically valid by
+//! construction. Fixed parts of the output (the `MDXContent` prologue) are
+//! parsed from templates, author-written code (ESM, expressions, spreads) is
+//! parsed with OXC, and [`JsValue`] trees are built node by node.
+//!
+//! Elements are built as runtime calls, development runtime calls or JSX
+//! syntax depending on the [`ElementStyle`].
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use markdown::unist::Position;
+use oxc_allocator::{Allocator, Vec as ArenaVec};
+use oxc_ast::ast::*;
+use oxc_ast::{AstBuilder, NONE};
+use oxc_ast_visit::VisitMut;
+use oxc_codegen::{Codegen, CodegenOptions, IndentChar};
+use oxc_parser::{ParseOptions, Parser};
+use oxc_span::{SourceType, Span, SPAN};
+
+use super::escape::is_valid_identifier;
+use super::jsx_element::{JsxElement, JsxProp};
+use super::jsx_value::JsValue;
+
+/// How [`JsxElement`]s are written
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ElementStyle {
+    /// `_jsx(tag, props, key)`, or `_jsxs(...)` for static children
+    Runtime,
+    /// `_jsxDEV(tag, props, key, isStaticChildren, source, this)`, with the
+    /// given file name in `source`, matching `react/jsx-dev-runtime`
+    Development(String),
+    /// JSX syntax (`<_components.p {...props}>…</_components.p>`), left for
+    /// an external JSX transform
+    Jsx,
+}
+
+/// Builds `oxc_ast` nodes for the compiled output
+pub(crate) struct Emitter<'a> {
+    allocator: &'a Allocator,
+    ast: AstBuilder<'a>,
+    style: ElementStyle,
+    /// Original MDX source; node spans are only recorded when it is known
+    source: Option<&'a str>,
+    /// Local names for tag expressions that are not valid JSX element names
+    tag_aliases: HashMap<String, String>,
+}
+
+impl<'a> Emitter<'a> {
+    /// Create an emitter allocating into `allocator`
+    pub fn new(allocator: &'a Allocator, style: ElementStyle) -> Self {
+        Self {
+            allocator,
+            ast: AstBuilder::new(allocator),
+            style,
+            source: None,
+            tag_aliases: HashMap::new(),
+        }
+    }
+
+    /// Give nodes spans pointing into the original MDX `source`
+    ///
+    /// Spans are what `oxc_codegen` turns into source map mappings.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(self.allocator.alloc_str(source));
+        self
+    }
+
+    /// The AST builder used by this emitter
+    pub fn ast(&self) -> AstBuilder<'a> {
+        self.ast
+    }
+
+    /// Write `tag` as `<alias>` in JSX output
+    ///
+    /// Used for tag expressions that cannot appear in JSX element position,
+    /// like `_components["foo-bar"]` (see [`jsx_tag_name`]).
+    pub fn alias_tag(&mut self, tag: impl Into<String>, alias: impl Into<String>) {
+        self.tag_aliases.insert(tag.into(), alias.into());
+    }
+
+    /// Create an empty program around `body`
+    ///
+    /// The program's source text is the MDX source (when known), so source
+    /// maps generated from it point back into the MDX file.
+    pub fn program(
+        &self,
+        source_type: SourceType,
+        directives: ArenaVec<'a, Directive<'a>>,
+        body: ArenaVec<'a, Statement<'a>>,
+    ) -> Program<'a> {
+        self.ast.program(
+            SPAN,
+            source_type,
+            self.source.unwrap_or_default(),
+            self.ast.vec(),
+            None,
+            directives,
+            body,
+        )
+    }
+
+    /// A directive prologue entry like `"use strict"`
+    pub fn directive(&self, value: &str) -> Directive<'a> {
+        let value = self.ast.atom(value);
+        self.ast
+            .directive(SPAN, self.ast.string_literal(SPAN, value, None), value)
+    }
+
+    /// Parse statements (module code with JSX, top-level `return` allowed)
+    pub fn statements(&self, code: &str) -> Result<ArenaVec<'a, Statement<'a>>> {
+        let code = self.allocator.alloc_str(code);
+        let ret = Parser::new(self.allocator, code, SourceType::mjs().with_jsx(true))
+            .with_options(ParseOptions {
+                allow_return_outside_function: true,
+                ..ParseOptions::default()
+            })
+            .parse();
+        if !ret.errors.is_empty() {
+            return Err(invalid_code(code, &ret.errors));
+        }
+
+        let mut program = ret.program;
+        SpanRewrite::Reset.visit_program(&mut program);
+        Ok(program.body)
+    }
+
+    /// Parse a single expression, falling back to the OXC parser only for
+    /// code that is not a plain identifier or member chain
+    pub fn code_expression(&self, code: &str) -> Result<Expression<'a>> {
+        match self.member_chain(code) {
+            Some(expression) => Ok(expression),
+            None => {
+                let mut expression = self.parse_expression(code)?;
+                SpanRewrite::Reset.visit_expression(&mut expression);
+                Ok(expression)
+            }
+        }
+    }
+
+    /// A string literal
+    pub fn string(&self, value: &str) -> Expression<'a> {
+        self.ast
+            .expression_string_literal(SPAN, self.ast.atom(value), None)
+    }
+
+    /// A reference to a binding by name
+    pub fn identifier(&self, name: &str) -> Expression<'a> {
+        self.ast.expression_identifier(SPAN, self.ast.atom(name))
+    }
+
+    /// A call to a function by name: `callee(arguments...)`
+    pub fn call(&self, callee: &str, arguments: Vec<Expression<'a>>) -> Expression<'a> {
+        let arguments = self
+            .ast
+            .vec_from_iter(arguments.into_iter().map(Argument::from));
+        self.ast
+            .expression_call(SPAN, self.identifier(callee), NONE, arguments, false)
+    }
+
+    /// An object property `name: value`, quoting `name` when needed
+    pub fn property(&self, name: &str, value: Expression<'a>) -> ObjectPropertyKind<'a> {
+        self.ast.object_property_kind_object_property(
+            SPAN,
+            PropertyKind::Init,
+            property_key(self.ast, name),
+            value,
+            false,
+            false,
+            false,
+        )
+    }
+
+    /// `const name = init;`
+    pub fn const_declaration(&self, name: &str, init: Expression<'a>) -> Statement<'a> {
+        let id = self.ast.binding_pattern(
+            self.ast
+                .binding_pattern_kind_binding_identifier(SPAN, self.ast.atom(name)),
+            NONE,
+            false,
+        );
+        let declarator = self.ast.variable_declarator(
+            SPAN,
+            VariableDeclarationKind::Const,
+            id,
+            Some(init),
+            false,
+        );
+        Statement::VariableDeclaration(self.ast.alloc_variable_declaration(
+            SPAN,
+            VariableDeclarationKind::Const,
+            self.ast.vec1(declarator),
+            false,
+        ))
+    }
+
+    /// `import {imported as local, ...} from "source";`
+    pub fn import(&self, names: &[(&str, &str)], source: &str) -> Statement<'a> {
+        let specifiers = self
+            .ast
+            .vec_from_iter(names.iter().map(|(imported, local)| {
+                self.ast.import_declaration_specifier_import_specifier(
+                    SPAN,
+                    self.ast
+                        .module_export_name_identifier_name(SPAN, self.ast.atom(imported)),
+                    self.ast.binding_identifier(SPAN, self.ast.atom(local)),
+                    ImportOrExportKind::Value,
+                )
+            }));
+        Statement::from(self.ast.module_declaration_import_declaration(
+            SPAN,
+            Some(specifiers),
+            self.ast.string_literal(SPAN, self.ast.atom(source), None),
+            None,
+            NONE,
+            ImportOrExportKind::Value,
+        ))
+    }
+
+    /// Build the expression for a [`JsValue`]
+    pub fn expression(&self, value: &JsValue) -> Result<Expression<'a>> {
+        Ok(match value {
+            JsValue::Raw(code) => self.code_expression(code)?,
+            JsValue::Text(text) => self.string(text),
+            JsValue::Array(items) => {
+                let mut elements = self.ast.vec_with_capacity(items.len());
+                for item in items {
+                    elements.push(ArrayExpressionElement::from(self.expression(item)?));
+                }
+                self.ast.expression_array(SPAN, elements)
+            }
+            JsValue::Bool(value) => self.ast.expression_boolean_literal(SPAN, *value),
+            JsValue::Object(properties) => {
+                let mut object = self.ast.vec_with_capacity(properties.len());
+                for (name, value) in properties {
+                    object.push(self.property(name, self.expression(value)?));
+                }
+                self.ast.expression_object(SPAN, object)
+            }
+            JsValue::Element(element) => self.element(element)?,
+            JsValue::Expression { code, position } => {
+                self.author_expression(code, position.as_ref())?
+            }
+            JsValue::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => self.ast.expression_conditional(
+                SPAN,
+                self.code_expression(test)?,
+                self.expression(consequent)?,
+                self.expression(alternate)?,
+            ),
+        })
+    }
+
+    /// Parse author-written code, keeping its spans in the MDX source when
+    /// the code appears there verbatim
+    fn author_expression(&self, code: &str, position: Option<&Position>) -> Result<Expression<'a>> {
+        let mut expression = self.parse_expression(code)?;
+
+        // `{code}`: the code starts right after the opening brace. Multi-line
+        // expressions in containers (lists, block quotes) lose their prefixes
+        // in mdast, so they cannot be mapped token by token.
+        let start = position.map(|position| position.start.offset + 1);
+        match (self.source, start) {
+            (Some(source), Some(start)) if source.get(start..start + code.len()) == Some(code) => {
+                SpanRewrite::Shift(start as u32).visit_expression(&mut expression);
+            }
+            _ => SpanRewrite::Reset.visit_expression(&mut expression),
+        }
+        Ok(expression)
+    }
+
+    fn parse_expression(&self, code: &str) -> Result<Expression<'a>> {
+        let code = self.allocator.alloc_str(code);
+        Parser::new(self.allocator, code, SourceType::mjs().with_jsx(true))
+            .parse_expression()
+            .map_err(|errors| invalid_code(code, &errors))
+    }
+
+    /// Identifiers and member chains (`props`, `_components.h1`) are built
+    /// directly instead of going through the parser
+    fn member_chain(&self, code: &str) -> Option<Expression<'a>> {
+        let mut parts = code.split('.');
+        let first = parts.next().filter(|part| {
+            is_valid_identifier(part) && !matches!(*part, "true" | "false" | "null")
+        })?;
+        let mut expression = self.identifier(first);
+        for part in parts {
+            if !is_valid_identifier(part) {
+                return None;
+            }
+            let property = self.ast.identifier_name(SPAN, self.ast.atom(part));
+            expression = Expression::from(
+                self.ast
+                    .member_expression_static(SPAN, expression, property, false),
+            );
+        }
+        Some(expression)
+    }
+
+    /// Span of a node generated from `position` (empty without a source)
+    fn span(&self, position: Option<&Position>) -> Span {
+        match (self.source, position) {
+            (Some(_), Some(position)) => {
+                Span::new(position.start.offset as u32, position.end.offset as u32)
+            }
+            _ => SPAN,
+        }
+    }
+
+    fn element(&self, element: &JsxElement) -> Result<Expression<'a>> {
+        match &self.style {
+            ElementStyle::Runtime => {
+                let callee = if element.has_static_children() {
+                    "_jsxs"
+                } else {
+                    "_jsx"
+                };
+                let mut arguments = vec![self.tag(element)?, self.props(element)?];
+                if let Some(key) = &element.key {
+                    arguments.push(self.string(key));
+                }
+                Ok(self.call(callee, arguments))
+            }
+            ElementStyle::Development(file_name) => {
+                let key = match &element.key {
+                    Some(key) => self.string(key),
+                    None => self.identifier("undefined"),
+                };
+                let source = match &element.position {
+                    Some(position) => {
+                        let properties = self.ast.vec_from_array([
+                            self.property("fileName", self.string(file_name)),
+                            self.property("lineNumber", self.number(position.start.line)),
+                            self.property("columnNumber", self.number(position.start.column)),
+                        ]);
+                        self.ast.expression_object(SPAN, properties)
+                    }
+                    None => self.identifier("undefined"),
+                };
+                let arguments = vec![
+                    self.tag(element)?,
+                    self.props(element)?,
+                    key,
+                    self.ast
+                        .expression_boolean_literal(SPAN, element.has_static_children()),
+                    source,
+                    self.ast.expression_this(SPAN),
+                ];
+                Ok(self.call("_jsxDEV", arguments))
+            }
+            ElementStyle::Jsx => self.jsx_element(element),
+        }
+    }
+
+    /// The element's tag expression, spanning the element's source position
+    fn tag(&self, element: &JsxElement) -> Result<Expression<'a>> {
+        let mut tag = self.code_expression(&element.tag)?;
+        SpanRewrite::Set(self.span(element.position.as_ref())).visit_expression(&mut tag);
+        Ok(tag)
+    }
+
+    /// Props object of a runtime call (children are always last)
+    fn props(&self, element: &JsxElement) -> Result<Expression<'a>> {
+        let mut properties = self.ast.vec_with_capacity(element.props.len() + 1);
+        for prop in &element.props {
+            properties.push(match prop {
+                JsxProp::Spread(expr) => self
+                    .ast
+                    .object_property_kind_spread_property(SPAN, self.code_expression(expr)?),
+                JsxProp::Attr(name, value) => self.property(name, self.expression(value)?),
+            });
+        }
+        if let Some(children) = &element.children {
+            properties.push(self.property("children", self.expression(children)?));
+        }
+        Ok(self.ast.expression_object(SPAN, properties))
+    }
+
+    fn number(&self, value: usize) -> Expression<'a> {
+        self.ast
+            .expression_numeric_literal(SPAN, value as f64, None, NumberBase::Decimal)
+    }
+
+    fn jsx_element(&self, element: &JsxElement) -> Result<Expression<'a>> {
+        let span = self.span(element.position.as_ref());
+        let children = self.jsx_children(element.children.as_ref())?;
+
+        if element.tag == "_Fragment" && element.props.is_empty() && element.key.is_none() {
+            return Ok(self.ast.expression_jsx_fragment(
+                span,
+                self.ast.jsx_opening_fragment(span),
+                children,
+                self.ast.jsx_closing_fragment(SPAN),
+            ));
+        }
+
+        let name = self
+            .tag_aliases
+            .get(&element.tag)
+            .cloned()
+            .or_else(|| jsx_tag_name(&element.tag))
+            .ok_or_else(|| anyhow!("`{}` cannot be used as a JSX element name", element.tag))?;
+
+        let mut attributes = self.ast.vec();
+        if let Some(key) = &element.key {
+            attributes.push(self.jsx_attribute("key", &JsValue::text(key.as_str()))?);
+        }
+        for prop in &element.props {
+            attributes.push(match prop {
+                JsxProp::Spread(expr) => self
+                    .ast
+                    .jsx_attribute_item_spread_attribute(SPAN, self.code_expression(expr)?),
+                JsxProp::Attr(name, value) if is_jsx_attribute_name(name) => {
+                    self.jsx_attribute(name, value)?
+                }
+                JsxProp::Attr(name, value) => {
+                    // Not expressible as a JSX attribute, spread it instead
+                    let object = self.ast.expression_object(
+                        SPAN,
+                        self.ast.vec1(self.property(name, self.expression(value)?)),
+                    );
+                    self.ast.jsx_attribute_item_spread_attribute(SPAN, object)
+                }
+            });
+        }
+
+        let opening =
+            self.ast
+                .jsx_opening_element(span, self.jsx_element_name(&name), NONE, attributes);
+        let closing = element.children.is_some().then(|| {
+            self.ast
+                .alloc_jsx_closing_element(SPAN, self.jsx_element_name(&name))
+        });
+        Ok(self
+            .ast
+            .expression_jsx_element(span, opening, children, closing))
+    }
+
+    fn jsx_element_name(&self, name: &str) -> JSXElementName<'a> {
+        let mut parts = name.split('.');
+        let first = parts.next().unwrap_or_default();
+        let Some(mut property) = parts.next() else {
+            // Lowercase and dashed names are intrinsic elements
+            return if first.starts_with(|c: char| c.is_ascii_lowercase()) || first.contains('-') {
+                self.ast
+                    .jsx_element_name_identifier(SPAN, self.ast.atom(first))
+            } else {
+                self.ast
+                    .jsx_element_name_identifier_reference(SPAN, self.ast.atom(first))
+            };
+        };
+
+        let mut object = self
+            .ast
+            .jsx_member_expression_object_identifier_reference(SPAN, self.ast.atom(first));
+        for part in parts {
+            object = self.ast.jsx_member_expression_object_member_expression(
+                SPAN,
+                object,
+                self.ast.jsx_identifier(SPAN, self.ast.atom(property)),
+            );
+            property = part;
+        }
+        self.ast.jsx_element_name_member_expression(
+            SPAN,
+            object,
+            self.ast.jsx_identifier(SPAN, self.ast.atom(property)),
+        )
+    }
+
+    fn jsx_attribute(&self, name: &str, value: &JsValue) -> Result<JSXAttributeItem<'a>> {
+        let name = match name.split_once(':') {
+            Some((namespace, name)) => self.ast.jsx_attribute_name_namespaced_name(
+                SPAN,
+                self.ast.jsx_identifier(SPAN, self.ast.atom(namespace)),
+                self.ast.jsx_identifier(SPAN, self.ast.atom(name)),
+            ),
+            None => self
+                .ast
+                .jsx_attribute_name_identifier(SPAN, self.ast.atom(name)),
+        };
+        let value = match value {
+            JsValue::Text(text) if is_plain_jsx_text(text) => self
+                .ast
+                .jsx_attribute_value_string_literal(SPAN, self.ast.atom(text), None),
+            value => self.ast.jsx_attribute_value_expression_container(
+                SPAN,
+                JSXExpression::from(self.expression(value)?),
+            ),
+        };
+        Ok(self
+            .ast
+            .jsx_attribute_item_attribute(SPAN, name, Some(value)))
+    }
+
+    fn jsx_children(&self, children: Option<&JsValue>) -> Result<ArenaVec<'a, JSXChild<'a>>> {
+        let mut out = self.ast.vec();
+        if let Some(children) = children {
+            self.push_jsx_child(children, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn push_jsx_child(&self, child: &JsValue, out: &mut ArenaVec<'a, JSXChild<'a>>) -> Result<()> {
+        match child {
+            JsValue::Text(text) if is_plain_jsx_text(text) => {
+                let text = self.ast.atom(text);
+                out.push(self.ast.jsx_child_text(SPAN, text, Some(text)));
+            }
+            JsValue::Array(items) => {
+                for item in items {
+                    self.push_jsx_child(item, out)?;
+                }
+            }
+            value => out.push(match self.expression(value)? {
+                Expression::JSXElement(element) => JSXChild::Element(element),
+                Expression::JSXFragment(fragment) => JSXChild::Fragment(fragment),
+                expression => self
+                    .ast
+                    .jsx_child_expression_container(SPAN, JSXExpression::from(expression)),
+            }),
+        }
+        Ok(())
+    }
+}
+
+/// Print `program` with two-space indentation
+///
+/// When `source_map_path` is set, a v3 source map (JSON) is generated from
+/// the node spans, with `source_map_path` as its `sources` entry.
+pub(crate) fn print(
+    program: &Program<'_>,
+    source_map_path: Option<&str>,
+) -> (String, Option<String>) {
+    let ret = Codegen::new()
+        .with_options(codegen_options(source_map_path))
+        .build(program);
+    (ret.code, ret.map.map(|map| map.to_json_string()))
+}
+
+/// Print a single value as a JavaScript expression, with runtime calls
+pub(crate) fn value_to_js(value: &JsValue) -> Result<String> {
+    let allocator = Allocator::default();
+    let expression = Emitter::new(&allocator, ElementStyle::Runtime).expression(value)?;
+    let mut codegen = Codegen::new().with_options(codegen_options(None));
+    codegen.print_expression(&expression);
+    Ok(codegen.into_source_text())
+}
+
+fn codegen_options(source_map_path: Option<&str>) -> CodegenOptions {
+    CodegenOptions {
+        indent_char: IndentChar::Space,
+        indent_width: 2,
+        source_map_path: source_map_path.map(PathBuf::from),
+        ..CodegenOptions::default()
+    }
+}
+
+/// An object property key, quoted when `name` is not an identifier name
+///
+/// Reserved words are valid property names (`{default: MDXContent}`).
+pub(crate) fn property_key<'a>(ast: AstBuilder<'a>, name: &str) -> PropertyKey<'a> {
+    let mut chars = name.chars();
+    let is_identifier_name = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if is_identifier_name {
+        ast.property_key_static_identifier(SPAN, ast.atom(name))
+    } else {
+        PropertyKey::StringLiteral(ast.alloc_string_literal(SPAN, ast.atom(name), None))
+    }
+}
+
+/// Rewrites the spans of parsed code, which are relative to the parsed snippet
+enum SpanRewrite {
+    /// Drop the spans (no source mapping)
+    Reset,
+    /// Move the spans to where the snippet starts in the MDX source
+    Shift(u32),
+    /// Give every node the same span
+    Set(Span),
+}
+
+impl<'a> VisitMut<'a> for SpanRewrite {
+    fn visit_span(&mut self, span: &mut Span) {
+        match *self {
+            Self::Reset => *span = SPAN,
+            Self::Shift(offset) => {
+                span.start += offset;
+                span.end += offset;
+            }
+            Self::Set(to) => *span = to,
+        }
+    }
+}
+
+fn invalid_code(code: &str, errors: &[impl std::fmt::Display]) -> anyhow::Error {
+    let message = errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+    anyhow!("Invalid JavaScript `{}`: {}", code.trim(), message)
+}
+
+/// The JSX spelling of a tag expression, if it has one
+///
+/// Member chains (`_components.h1`, `UI.Card`) and identifiers are written
+/// as-is, and string tags (`"div"`, `"my-element"`) lose their quotes.
+/// Anything else (`_components["foo-bar"]`, `(a || {}).B`) has to be
+/// bound to a local name first.
+pub(crate) fn jsx_tag_name(tag: &str) -> Option<String> {
+    if let Some(name) = tag.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        let mut chars = name.chars();
+        let starts_lowercase = chars.next().is_some_and(|c| c.is_ascii_lowercase());
+        let valid = starts_lowercase && chars.all(|c| c.is_ascii_alphanumeric() || c == '-');
+        return valid.then(|| name.to_string());
+    }
+
+    let is_member_chain = tag.split('.').all(|part| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+    });
+    is_member_chain.then(|| tag.to_string())
+}
+
+/// Collect every element tag that needs an alias in JSX output
+pub(crate) fn collect_unnamed_tags(value: &JsValue, tags: &mut Vec<String>) {
+    match value {
+        JsValue::Element(element) => {
+            if element.tag != "_Fragment"
+                && jsx_tag_name(&element.tag).is_none()
+                && !tags.contains(&element.tag)
+            {
+                tags.push(element.tag.clone());
+            }
+            for prop in &element.props {
+                if let JsxProp::Attr(_, value) = prop {
+                    collect_unnamed_tags(value, tags);
+                }
+            }
+            if let Some(children) = &element.children {
+                collect_unnamed_tags(children, tags);
+            }
+        }
+        JsValue::Array(items) => {
+            for item in items {
+                collect_unnamed_tags(item, tags);
+            }
+        }
+        JsValue::Object(properties) => {
+            for (_, value) in properties {
+                collect_unnamed_tags(value, tags);
+            }
+        }
+        JsValue::Conditional {
+            consequent,
+            alternate,
+            ..
+        } => {
+            collect_unnamed_tags(consequent, tags);
+            collect_unnamed_tags(alternate, tags);
+        }
+        JsValue::Raw(_) | JsValue::Text(_) | JsValue::Bool(_) | JsValue::Expression { .. } => {}
+    }
+}
+
+/// Whether `name` can be written as a JSX attribute (`aria-label`, `xlink:href`)
+fn is_jsx_attribute_name(name: &str) -> bool {
+    let is_part = |part: &str| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '-'))
+    };
+    match name.split_once(':') {
+        Some((namespace, name)) => is_part(namespace) && is_part(name),
+        None => is_part(name),
+    }
+}
+
+/// Whether text can be written as JSX text (or a JSX attribute string)
+/// without an expression container
+fn is_plain_jsx_text(text: &str) -> bool {
+    !text.is_empty()
+        && !text
+            .chars()
+            .any(|c| matches!(c, '{' | '}' | '<' | '>' | '&' | '"' | '\n' | '\r'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown::unist::Point;
+
+    fn point(line: usize, column: usize, offset: usize) -> Point {
+        Point {
+            line,
+            column,
+            offset,
+        }
+    }
+
+    fn emit(emitter: &Emitter<'_>, value: &JsValue) -> String {
+        let expression = emitter.expression(value).unwrap();
+        let mut codegen = Codegen::new().with_options(codegen_options(None));
+        codegen.print_expression(&expression);
+        codegen.into_source_text()
+    }
+
+    fn compact(code: &str) -> String {
+        code.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_element_serialization() {
+        let element = JsxElement::component("a")
+            .spread_props()
+            .prop("href", JsValue::text("/docs"))
+            .children(JsValue::text("Docs"));
+        assert_eq!(
+            element.into_value().to_js().unwrap(),
+            "_jsx(_components.a, {\n  ...props,\n  href: \"/docs\",\n  children: \"Docs\"\n})"
+        );
+    }
+
+    #[test]
+    fn test_static_children_use_jsxs() {
+        let element = JsxElement::component("ul")
+            .children(JsValue::array(vec![JsValue::text("a"), JsValue::text("b")]))
+            .key("mdx-0");
+        assert_eq!(
+            element.into_value().to_js().unwrap(),
+            "_jsxs(_components.ul, { children: [\"a\", \"b\"] }, \"mdx-0\")"
+        );
+    }
+
+    #[test]
+    fn test_quoted_prop_names() {
+        let element = JsxElement::component("input").prop("data-task-id", JsValue::text("x"));
+        assert_eq!(
+            element.into_value().to_js().unwrap(),
+            "_jsx(_components.input, { \"data-task-id\": \"x\" })"
+        );
+    }
+
+    #[test]
+    fn test_invalid_code_is_an_error() {
+        let element = JsxElement::component("p").children(JsValue::expression("foo(", None));
+        let error = element.into_value().to_js().unwrap_err();
+        assert!(error.to_string().contains("Invalid JavaScript `foo(`"));
+    }
+
+    #[test]
+    fn test_development_elements() {
+        let position = Position {
+            start: point(2, 3, 8),
+            end: point(2, 9, 14),
+        };
+        let value = JsxElement::component("p")
+            .at(Some(&position))
+            .children(JsValue::array(vec![
+                JsValue::text("a"),
+                JsxElement::component("br").into_value(),
+            ]))
+            .into_value();
+
+        let allocator = Allocator::default();
+        let emitter = Emitter::new(
+            &allocator,
+            ElementStyle::Development("docs/intro.mdx".to_string()),
+        );
+        assert_eq!(
+            compact(&emit(&emitter, &value)),
+            compact(
+                "_jsxDEV(_components.p, {children: [\"a\", _jsxDEV(_components.br, {}, undefined, false, undefined, this)]}, undefined, true, {fileName: \"docs/intro.mdx\", lineNumber: 2, columnNumber: 3}, this)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_jsx_syntax() {
+        let value = JsxElement::new("_Fragment")
+            .children(JsValue::array(vec![
+                JsxElement::component("a")
+                    .spread_props()
+                    .prop("href", JsValue::text("/docs"))
+                    .prop("data-x", JsValue::raw("1"))
+                    .children(JsValue::text("Docs {here}"))
+                    .into_value(),
+                JsxElement::new("\"my-element\"").key("mdx-0").into_value(),
+                JsValue::expression("props.name", None),
+            ]))
+            .into_value();
+
+        let allocator = Allocator::default();
+        let emitter = Emitter::new(&allocator, ElementStyle::Jsx);
+        assert_eq!(
+            emit(&emitter, &value),
+            "<><_components.a {...props} href=\"/docs\" data-x={1}>{\"Docs {here}\"}</_components.a><my-element key=\"mdx-0\" />{props.name}</>"
+        );
+    }
+
+    #[test]
+    fn test_jsx_tag_aliases() {
+        let tag = "_components[\"foo-bar\"]";
+        assert_eq!(jsx_tag_name(tag), None);
+        assert_eq!(
+            jsx_tag_name("_components.UI.Card"),
+            Some("_components.UI.Card".to_string())
+        );
+
+        let value = JsxElement::new(tag)
+            .children(JsValue::text("x"))
+            .into_value();
+        let mut tags = Vec::new();
+        collect_unnamed_tags(&value, &mut tags);
+        assert_eq!(tags, vec![tag.to_string()]);
+
+        let allocator = Allocator::default();
+        let mut emitter = Emitter::new(&allocator, ElementStyle::Jsx);
+        emitter.alias_tag(tag, "_component0");
+        assert_eq!(emit(&emitter, &value), "<_component0>x</_component0>");
+    }
+
+    #[test]
+    fn test_spans_follow_the_source() {
+        let source = "Hi {props.name}";
+        let position = Position {
+            start: point(1, 4, 3),
+            end: point(1, 16, 15),
+        };
+        let value = JsValue::expression("props.name", Some(&position));
+
+        let allocator = Allocator::default();
+        let emitter = Emitter::new(&allocator, ElementStyle::Runtime).with_source(source);
+        let Expression::StaticMemberExpression(member) = emitter.expression(&value).unwrap() else {
+            panic!("expected a member expression");
+        };
+        assert_eq!(member.span, Span::new(4, 14));
+
+        // Without the source there is nothing to map to
+        let emitter = Emitter::new(&allocator, ElementStyle::Runtime);
+        let Expression::StaticMemberExpression(member) = emitter.expression(&value).unwrap() else {
+            panic!("expected a member expression");
+        };
+        assert_eq!(member.span, SPAN);
+    }
+}
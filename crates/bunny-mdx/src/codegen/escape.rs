@@ -39,6 +39,21 @@ pub fn escape_js_string(text: &str) -> String {
     result
 }
 
+/// Escape text for HTML content and double-quoted attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
 /// Check if a string is a valid JavaScript identifier
 ///
 /// Valid identifiers:
@@ -160,6 +175,15 @@ mod escape_tests {
         assert_eq!(escape_js_string("`backtick`"), "`backtick`"); // Backticks are safe in double-quoted strings
     }
 
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x">Tom & Jerry</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&lt;/a&gt;"
+        );
+        assert_eq!(escape_html("it's fine"), "it's fine");
+    }
+
     #[test]
     fn test_empty_and_large() {
         assert_eq!(escape_js_string(""), "");
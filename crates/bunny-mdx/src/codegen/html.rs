@@ -0,0 +1,952 @@
+//! Static HTML backend
+//!
+//! Walks the same mdast as the JSX renderer and writes escaped HTML, for
+//! consumers without a JavaScript runtime (RSS feeds, email, AMP pages).
+//! JSX components are rendered by the templates in [`HtmlOptions`];
+//! `{expressions}` and ESM can't run without JavaScript and are left out.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use markdown::mdast::{AlignKind, AttributeContent, AttributeValue, List, ListItem, Node};
+use markdown::unist::Position;
+
+use super::context::CodegenContext;
+use super::escape::escape_html;
+use crate::diagnostic::Diagnostic;
+use crate::error::MdxError;
+use crate::frontmatter::extract_frontmatter;
+use crate::nodes::{
+    html_attribute_name, html_to_html, is_script, is_unsafe_attribute, parse_fence_meta,
+    reference_suffix, report_unresolved_reference, sanitize_attribute, should_use_components_map,
+    split_custom_id, VOID_ELEMENTS,
+};
+use crate::plugins::{AnchorPosition, HeadingAnchor, HeadingIds, PluginContext};
+
+/// Renders a JSX component to HTML
+///
+/// The returned HTML is written as-is, so templates must escape what they
+/// interpolate (see [`escape_html`](crate::codegen::escape_html)).
+pub type ComponentTemplate = Arc<dyn Fn(&ComponentProps<'_>) -> String + Send + Sync>;
+
+/// A JSX component as seen by its [`ComponentTemplate`]
+#[derive(Debug)]
+pub struct ComponentProps<'a> {
+    /// Component name (`Callout` for `<Callout>`)
+    pub name: &'a str,
+    /// Attributes with literal values (`None` for bare attributes like
+    /// `open`); expression attributes can't be evaluated and are left out
+    pub attributes: Vec<(String, Option<String>)>,
+    /// The rendered (escaped) HTML of the children
+    pub children: String,
+    /// Where the component is in the source
+    pub position: Option<&'a Position>,
+}
+
+impl ComponentProps<'_> {
+    /// Value of a literal attribute
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Whether the attribute is present, with or without a value
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes
+            .iter()
+            .any(|(attribute, _)| attribute == name)
+    }
+}
+
+/// What to do with a JSX component that has no template
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownComponents {
+    /// Render the component's children in its place
+    #[default]
+    Unwrap,
+    /// Leave the component and its children out
+    Drop,
+    /// Fail with an `MDX0104` error
+    Error,
+}
+
+/// Options for the static HTML backend
+///
+/// ```
+/// use bunny_mdx::codegen::{escape_html, HtmlOptions};
+/// use bunny_mdx::{compile_to_html, MdxCompileOptions};
+///
+/// let html = HtmlOptions::new().with_component("Callout", |props| {
+///     let kind = props.attribute("type").unwrap_or("note");
+///     format!("<aside class=\"{}\">{}</aside>", escape_html(kind), props.children)
+/// });
+///
+/// let result = compile_to_html(
+///     "<Callout type=\"tip\">\n  Use **HTML**\n</Callout>",
+///     MdxCompileOptions::new(),
+///     &html,
+/// )?;
+/// assert_eq!(
+///     result.html,
+///     "<aside class=\"tip\"><p>Use <strong>HTML</strong></p>\n</aside>\n"
+/// );
+/// # Ok::<(), Box<bunny_mdx::MdxError>>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct HtmlOptions {
+    components: HashMap<String, ComponentTemplate>,
+    /// Policy for components without a template
+    pub unknown_components: UnknownComponents,
+}
+
+impl fmt::Debug for HtmlOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut components: Vec<&str> = self.components.keys().map(String::as_str).collect();
+        components.sort_unstable();
+        f.debug_struct("HtmlOptions")
+            .field("components", &components)
+            .field("unknown_components", &self.unknown_components)
+            .finish()
+    }
+}
+
+impl HtmlOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render `<name>` with `template`
+    ///
+    /// A `CodeBlock` template replaces the default `<pre><code>` for code
+    /// fences, like a `CodeBlock` component does in the JSX output. It gets
+    /// the `lang` and `code` attributes, plus `title`, `highlightLines`
+    /// (`1,3,4`) and `highlightWords` from the fence metadata.
+    pub fn with_component(
+        mut self,
+        name: impl Into<String>,
+        template: impl Fn(&ComponentProps<'_>) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.components.insert(name.into(), Arc::new(template));
+        self
+    }
+
+    pub fn with_unknown_components(mut self, policy: UnknownComponents) -> Self {
+        self.unknown_components = policy;
+        self
+    }
+
+    fn component(&self, name: &str) -> Option<&ComponentTemplate> {
+        self.components.get(name)
+    }
+}
+
+/// Everything produced by an HTML pass
+#[derive(Debug, Default)]
+pub(crate) struct HtmlOutput {
+    pub html: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Render mdast to HTML
+///
+/// Only `transform_ast` plugins run: the other hooks work on the JavaScript
+/// output, which the HTML backend doesn't produce.
+pub(crate) fn render_html(
+    root: &Node,
+    options: &crate::mdx::MdxOptions,
+    html: &HtmlOptions,
+) -> Result<HtmlOutput> {
+    let mut cleaned_root = if options.frontmatter.is_some() {
+        root.clone()
+    } else {
+        extract_frontmatter(root)?.0
+    };
+
+    let mut plugin_context = PluginContext::new(options.filepath.clone());
+    for plugin in &options.plugins {
+        tracing::debug!(plugin = plugin.name(), "Running AST transformation plugin");
+        plugin_context.set_plugin(Some(plugin.name()));
+        plugin
+            .transform_ast(&mut cleaned_root, &mut plugin_context)
+            .with_context(|| {
+                format!(
+                    "Plugin '{}' failed during AST transformation",
+                    plugin.name()
+                )
+            })?;
+    }
+    plugin_context.set_plugin(None);
+
+    let Node::Root(root) = &cleaned_root else {
+        return Err(anyhow!("Expected Root node, got {:?}", cleaned_root));
+    };
+
+    let mut ctx = CodegenContext::new();
+    ctx.raw_html = options.raw_html;
+    ctx.url_policy = options.url_policy.clone();
+    ctx.heading_ids = HeadingIds::take(&mut plugin_context);
+    ctx.heading_anchor = HeadingAnchor::take(&mut plugin_context);
+    ctx.collect_definitions(&cleaned_root);
+    for child in &root.children {
+        if let Node::MdxjsEsm(esm) = child {
+            for statement in crate::esm::split_statements(&esm.value) {
+                if statement.starts_with("import ") {
+                    ctx.imported_components
+                        .extend(crate::esm::extract_imported_names(&statement));
+                }
+            }
+        }
+    }
+
+    let mut writer = HtmlWriter {
+        ctx,
+        options: html,
+        out: String::new(),
+    };
+    writer.nodes(&root.children)?;
+
+    let mut diagnostics = writer.ctx.diagnostics;
+    diagnostics.extend(plugin_context.take_diagnostics());
+    diagnostics
+        .sort_by_key(|diagnostic| diagnostic.span.map_or(usize::MAX, |span| span.start.offset));
+
+    Ok(HtmlOutput {
+        html: writer.out,
+        diagnostics,
+    })
+}
+
+/// Writes nodes to `out`; block nodes end with a newline
+struct HtmlWriter<'a> {
+    ctx: CodegenContext,
+    options: &'a HtmlOptions,
+    out: String,
+}
+
+impl HtmlWriter<'_> {
+    fn nodes(&mut self, nodes: &[Node]) -> Result<()> {
+        nodes.iter().try_for_each(|node| self.node(node))
+    }
+
+    /// Render `nodes` to a separate string (for component children)
+    fn capture(&mut self, nodes: &[Node]) -> Result<String> {
+        let outer = std::mem::take(&mut self.out);
+        let result = self.nodes(nodes);
+        let inner = std::mem::replace(&mut self.out, outer);
+        result.map(|_| inner)
+    }
+
+    fn node(&mut self, node: &Node) -> Result<()> {
+        match node {
+            Node::MdxJsxFlowElement(element) => {
+                self.jsx_element(
+                    element.name.as_deref(),
+                    &element.attributes,
+                    &element.children,
+                    element.position.as_ref(),
+                )?;
+                self.out.push('\n');
+            }
+            Node::MdxJsxTextElement(element) => self.jsx_element(
+                element.name.as_deref(),
+                &element.attributes,
+                &element.children,
+                element.position.as_ref(),
+            )?,
+            Node::MdxFlowExpression(expression) => {
+                self.expression(&expression.value, expression.position.as_ref())
+            }
+            Node::MdxTextExpression(expression) => {
+                self.expression(&expression.value, expression.position.as_ref())
+            }
+
+            Node::Heading(heading) => {
+                let tag = format!("h{}", heading.depth);
+                let (content, custom_id) = split_custom_id(&heading.children);
+                let id = self.ctx.next_heading_id(custom_id);
+                let attributes: Vec<(&str, &str)> =
+                    id.as_deref().map(|id| ("id", id)).into_iter().collect();
+                match (id.as_deref(), self.ctx.heading_anchor.clone()) {
+                    (Some(id), Some(anchor)) => {
+                        self.open_tag(&tag, &attributes);
+                        self.heading_anchor(&anchor, id, &content)?;
+                        self.out.push_str(&format!("</{}>", tag));
+                    }
+                    _ => self.element(&tag, &attributes, &content)?,
+                }
+                self.out.push('\n');
+            }
+            Node::Paragraph(paragraph) => {
+                self.element("p", &[], &paragraph.children)?;
+                self.out.push('\n');
+            }
+            Node::Blockquote(quote) => {
+                self.out.push_str("<blockquote>\n");
+                self.nodes(&quote.children)?;
+                self.out.push_str("</blockquote>\n");
+            }
+            Node::ThematicBreak(_) => self.out.push_str("<hr />\n"),
+            Node::Break(_) => self.out.push_str("<br />\n"),
+            Node::Code(code) => self.code(code)?,
+            Node::List(list) => self.list(list)?,
+            Node::ListItem(item) => self.list_item(item, true)?,
+
+            Node::Text(text) => self.out.push_str(&escape_html(&text.value)),
+            Node::InlineCode(code) => self.text_element("code", &[], &code.value),
+            Node::Emphasis(emphasis) => self.element("em", &[], &emphasis.children)?,
+            Node::Strong(strong) => self.element("strong", &[], &strong.children)?,
+            Node::Delete(delete) => self.element("del", &[], &delete.children)?,
+            Node::Link(link) => self.link(
+                &link.url,
+                link.title.as_deref(),
+                &link.children,
+                link.position.as_ref(),
+            )?,
+            Node::Image(image) => self.image(
+                &image.url,
+                &image.alt,
+                image.title.as_deref(),
+                image.position.as_ref(),
+            )?,
+            Node::LinkReference(link_ref) => {
+                match self.ctx.definition(&link_ref.identifier).cloned() {
+                    Some(definition) => self.link(
+                        &definition.url,
+                        definition.title.as_deref(),
+                        &link_ref.children,
+                        link_ref.position.as_ref(),
+                    )?,
+                    None => {
+                        report_unresolved_reference(
+                            &link_ref.identifier,
+                            link_ref.position.as_ref(),
+                            &mut self.ctx,
+                        );
+                        self.out.push('[');
+                        self.nodes(&link_ref.children)?;
+                        self.out.push(']');
+                        self.out.push_str(&escape_html(&reference_suffix(
+                            &link_ref.reference_kind,
+                            link_ref.label.as_deref(),
+                        )));
+                    }
+                }
+            }
+            Node::ImageReference(image_ref) => {
+                match self.ctx.definition(&image_ref.identifier).cloned() {
+                    Some(definition) => self.image(
+                        &definition.url,
+                        &image_ref.alt,
+                        definition.title.as_deref(),
+                        image_ref.position.as_ref(),
+                    )?,
+                    None => {
+                        report_unresolved_reference(
+                            &image_ref.identifier,
+                            image_ref.position.as_ref(),
+                            &mut self.ctx,
+                        );
+                        let text = format!(
+                            "![{}]{}",
+                            image_ref.alt,
+                            reference_suffix(&image_ref.reference_kind, image_ref.label.as_deref())
+                        );
+                        self.out.push_str(&escape_html(&text));
+                    }
+                }
+            }
+
+            Node::Table(table) => {
+                self.ctx.enter_table(Some(table.align.clone()));
+                self.out.push_str("<table>\n");
+                if let Some((head, body)) = table.children.split_first() {
+                    self.out.push_str("<thead>\n");
+                    self.node(head)?;
+                    self.out.push_str("</thead>\n");
+                    if !body.is_empty() {
+                        self.out.push_str("<tbody>\n");
+                        self.nodes(body)?;
+                        self.out.push_str("</tbody>\n");
+                    }
+                }
+                self.out.push_str("</table>\n");
+                self.ctx.exit_table();
+            }
+            Node::TableRow(row) => {
+                self.ctx.start_table_row();
+                self.out.push_str("<tr>\n");
+                self.nodes(&row.children)?;
+                self.out.push_str("</tr>\n");
+                self.ctx.end_table_row();
+            }
+            Node::TableCell(cell) => {
+                let tag = if self.ctx.is_header_row() { "th" } else { "td" };
+                let style = match self.ctx.current_cell_alignment() {
+                    Some(AlignKind::Left) => Some("text-align: left"),
+                    Some(AlignKind::Right) => Some("text-align: right"),
+                    Some(AlignKind::Center) => Some("text-align: center"),
+                    Some(AlignKind::None) | None => None,
+                };
+                let attributes: Vec<(&str, &str)> =
+                    style.map(|style| ("style", style)).into_iter().collect();
+                self.element(tag, &attributes, &cell.children)?;
+                self.out.push('\n');
+                self.ctx.next_table_cell();
+            }
+
+            Node::Math(math) => {
+                self.text_element("span", &[("class", "math math-display")], &math.value);
+                self.out.push('\n');
+            }
+            Node::InlineMath(math) => {
+                self.text_element("span", &[("class", "math math-inline")], &math.value)
+            }
+
+            Node::FootnoteReference(footnote_ref) => {
+                let id = escape_html(&footnote_ref.identifier);
+                let label = footnote_ref
+                    .label
+                    .as_deref()
+                    .unwrap_or(&footnote_ref.identifier);
+                self.out.push_str(&format!(
+                    "<sup><a href=\"#fn-{0}\" id=\"fnref-{0}\">{1}</a></sup>",
+                    id,
+                    escape_html(label)
+                ));
+            }
+            Node::FootnoteDefinition(footnote_def) => {
+                let id = escape_html(&footnote_def.identifier);
+                let label = escape_html(
+                    footnote_def
+                        .label
+                        .as_deref()
+                        .unwrap_or(&footnote_def.identifier),
+                );
+                let backref = format!("<a href=\"#fnref-{}\">\u{21a9}</a>", id);
+                let content = self.capture(&footnote_def.children)?;
+
+                // The label and back reference go inside the first and last
+                // paragraphs, as in the JSX output
+                self.out.push_str(&format!("<div id=\"fn-{}\">", id));
+                match content
+                    .strip_prefix("<p>")
+                    .and_then(|content| content.strip_suffix("</p>\n"))
+                {
+                    Some(inner) => self
+                        .out
+                        .push_str(&format!("<p>{}. {} {}</p>\n", label, inner, backref)),
+                    None => self.out.push_str(&format!(
+                        "<p>{}.</p>\n{}<p>{}</p>\n",
+                        label, content, backref
+                    )),
+                }
+                self.out.push_str("</div>\n");
+            }
+
+            Node::Html(html) => {
+                let html = html_to_html(html, &self.ctx)?;
+                self.out.push_str(&html);
+            }
+
+            // ESM runs only in JavaScript, and definitions are resolved
+            // through the context
+            Node::MdxjsEsm(_) | Node::Definition(_) | Node::Yaml(_) | Node::Toml(_) => {}
+
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// `<tag attributes>children</tag>`
+    fn element(&mut self, tag: &str, attributes: &[(&str, &str)], children: &[Node]) -> Result<()> {
+        self.open_tag(tag, attributes);
+        self.nodes(children)?;
+        self.out.push_str(&format!("</{}>", tag));
+        Ok(())
+    }
+
+    /// `<tag attributes>text</tag>`
+    fn text_element(&mut self, tag: &str, attributes: &[(&str, &str)], text: &str) {
+        self.open_tag(tag, attributes);
+        self.out.push_str(&escape_html(text));
+        self.out.push_str(&format!("</{}>", tag));
+    }
+
+    fn open_tag(&mut self, tag: &str, attributes: &[(&str, &str)]) {
+        self.out.push('<');
+        self.out.push_str(tag);
+        for (name, value) in attributes {
+            self.out
+                .push_str(&format!(" {}=\"{}\"", name, escape_html(value)));
+        }
+        self.out.push('>');
+    }
+
+    /// Heading content with the `HeadingIdPlugin` anchor link
+    fn heading_anchor(&mut self, anchor: &HeadingAnchor, id: &str, content: &[Node]) -> Result<()> {
+        let href = format!("#{}", id);
+        let mut attributes = vec![("href", href.as_str())];
+        if let Some(aria_label) = &anchor.aria_label {
+            attributes.push(("aria-label", aria_label));
+        }
+        if let Some(class_name) = &anchor.class_name {
+            attributes.push(("class", class_name));
+        }
+        match anchor.position {
+            AnchorPosition::Wrap => self.element("a", &attributes, content)?,
+            AnchorPosition::Prepend => {
+                self.text_element("a", &attributes, &anchor.content);
+                self.nodes(content)?;
+            }
+            AnchorPosition::Append => {
+                self.nodes(content)?;
+                self.text_element("a", &attributes, &anchor.content);
+            }
+        }
+        Ok(())
+    }
+
+    fn link(
+        &mut self,
+        url: &str,
+        title: Option<&str>,
+        children: &[Node],
+        position: Option<&Position>,
+    ) -> Result<()> {
+        let href = self.ctx.sanitize_url(url, position)?;
+        let mut attributes = Vec::new();
+        if let Some(href) = &href {
+            attributes.push(("href", href.as_str()));
+        }
+        if let Some(title) = title {
+            attributes.push(("title", title));
+        }
+        self.element("a", &attributes, children)
+    }
+
+    fn image(
+        &mut self,
+        url: &str,
+        alt: &str,
+        title: Option<&str>,
+        position: Option<&Position>,
+    ) -> Result<()> {
+        let src = self.ctx.sanitize_url(url, position)?;
+        let mut attributes = Vec::new();
+        if let Some(src) = &src {
+            attributes.push(("src", src.as_str()));
+        }
+        attributes.push(("alt", alt));
+        if let Some(title) = title {
+            attributes.push(("title", title));
+        }
+        self.open_tag("img", &attributes);
+        self.out.insert_str(self.out.len() - 1, " /");
+        Ok(())
+    }
+
+    fn code(&mut self, code: &markdown::mdast::Code) -> Result<()> {
+        let lang = code.lang.as_deref().unwrap_or_default();
+        if let Some(template) = self.options.component("CodeBlock") {
+            let meta = parse_fence_meta(code.meta.as_deref().unwrap_or_default());
+            let mut attributes = vec![
+                ("lang".to_string(), Some(lang.to_string())),
+                ("code".to_string(), Some(code.value.clone())),
+            ];
+            if let Some(title) = meta.title {
+                attributes.push(("title".to_string(), Some(title)));
+            }
+            if !meta.line_highlights.is_empty() {
+                let lines: Vec<String> = meta
+                    .line_highlights
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect();
+                attributes.push(("highlightLines".to_string(), Some(lines.join(","))));
+            }
+            if !meta.word_highlights.is_empty() {
+                attributes.push((
+                    "highlightWords".to_string(),
+                    Some(meta.word_highlights.join(",")),
+                ));
+            }
+            let html = template(&ComponentProps {
+                name: "CodeBlock",
+                attributes,
+                children: String::new(),
+                position: code.position.as_ref(),
+            });
+            self.out.push_str(&html);
+            self.out.push('\n');
+            return Ok(());
+        }
+
+        let class = format!("language-{}", lang);
+        let attributes: Vec<(&str, &str)> = if lang.is_empty() {
+            Vec::new()
+        } else {
+            vec![("class", class.as_str())]
+        };
+        self.out.push_str("<pre>");
+        self.text_element("code", &attributes, &code.value);
+        self.out.push_str("</pre>\n");
+        Ok(())
+    }
+
+    fn list(&mut self, list: &List) -> Result<()> {
+        let tag = if list.ordered { "ol" } else { "ul" };
+        let start = list
+            .start
+            .filter(|start| *start != 1)
+            .map(|start| start.to_string());
+        let attributes: Vec<(&str, &str)> = start
+            .as_deref()
+            .map(|start| ("start", start))
+            .into_iter()
+            .collect();
+        self.open_tag(tag, &attributes);
+        self.out.push('\n');
+        for child in &list.children {
+            match child {
+                Node::ListItem(item) => self.list_item(item, list.spread)?,
+                other => self.node(other)?,
+            }
+        }
+        self.out.push_str(&format!("</{}>\n", tag));
+        Ok(())
+    }
+
+    /// List items of tight lists hold their paragraphs' content directly
+    fn list_item(&mut self, item: &ListItem, list_spread: bool) -> Result<()> {
+        let spread = list_spread || item.spread;
+        self.out.push_str("<li>");
+        if let Some(checked) = item.checked {
+            let checked = if checked { " checked" } else { "" };
+            self.out
+                .push_str(&format!("<input type=\"checkbox\"{} disabled /> ", checked));
+        }
+        if spread {
+            self.out.push('\n');
+        }
+        for child in &item.children {
+            match child {
+                Node::Paragraph(paragraph) if !spread => self.nodes(&paragraph.children)?,
+                child => {
+                    if !spread && !self.out.ends_with('\n') {
+                        self.out.push('\n');
+                    }
+                    self.node(child)?;
+                }
+            }
+        }
+        self.out.push_str("</li>\n");
+        Ok(())
+    }
+
+    /// Expressions need JavaScript, so they are left out (comments silently)
+    fn expression(&mut self, value: &str, position: Option<&Position>) {
+        if crate::expression::is_comment_only(value) {
+            return;
+        }
+        if crate::expression::heading_id(value).is_some() {
+            crate::nodes::report_misplaced_heading_id(position, &mut self.ctx);
+            return;
+        }
+        self.ctx.push_diagnostic(
+            Diagnostic::info(
+                "expression-not-rendered",
+                format!(
+                    "Expression `{{{}}}` is not rendered in HTML output",
+                    value.trim()
+                ),
+            )
+            .with_position(position),
+        );
+    }
+
+    fn jsx_element(
+        &mut self,
+        name: Option<&str>,
+        attributes: &[AttributeContent],
+        children: &[Node],
+        position: Option<&Position>,
+    ) -> Result<()> {
+        // Fragments (`<>...</>`) only have children
+        let Some(name) = name else {
+            return self.nodes(children);
+        };
+
+        let is_component =
+            should_use_components_map(name) || self.ctx.imported_components.contains(name);
+        if is_component {
+            return self.component(name, attributes, children, position);
+        }
+
+        // Scripts and event handlers would run in the reader's mail client
+        // or feed reader, so HTML output drops them like raw HTML
+        if is_script(name) {
+            return Ok(());
+        }
+
+        let mut html_attributes = Vec::new();
+        for attribute in attributes {
+            match attribute {
+                AttributeContent::Property(property) => {
+                    if is_unsafe_attribute(&property.name) {
+                        continue;
+                    }
+                    let value = match &property.value {
+                        Some(AttributeValue::Literal(value)) => {
+                            match sanitize_attribute(&property.name, value, &self.ctx, position)? {
+                                Some(value) => Some(value),
+                                None => continue,
+                            }
+                        }
+                        Some(AttributeValue::Expression(expression)) => {
+                            self.expression(&expression.value, position);
+                            continue;
+                        }
+                        None => None,
+                    };
+                    html_attributes.push((html_attribute_name(&property.name).to_string(), value));
+                }
+                AttributeContent::Expression(expression) => {
+                    self.expression(&expression.value, position)
+                }
+            }
+        }
+
+        self.out.push('<');
+        self.out.push_str(name);
+        for (attribute, value) in &html_attributes {
+            self.out.push(' ');
+            self.out.push_str(attribute);
+            if let Some(value) = value {
+                self.out.push_str(&format!("=\"{}\"", escape_html(value)));
+            }
+        }
+        if VOID_ELEMENTS.contains(&name.to_ascii_lowercase().as_str()) {
+            self.out.push_str(" />");
+            return Ok(());
+        }
+        self.out.push('>');
+        self.nodes(children)?;
+        self.out.push_str(&format!("</{}>", name));
+        Ok(())
+    }
+
+    fn component(
+        &mut self,
+        name: &str,
+        attributes: &[AttributeContent],
+        children: &[Node],
+        position: Option<&Position>,
+    ) -> Result<()> {
+        let Some(template) = self.options.component(name).cloned() else {
+            return self.unknown_component(name, children, position);
+        };
+
+        let mut literal_attributes = Vec::new();
+        for attribute in attributes {
+            match attribute {
+                AttributeContent::Property(property) => match &property.value {
+                    Some(AttributeValue::Literal(value)) => {
+                        literal_attributes.push((property.name.clone(), Some(value.clone())))
+                    }
+                    Some(AttributeValue::Expression(expression)) => {
+                        self.expression(&expression.value, position)
+                    }
+                    None => literal_attributes.push((property.name.clone(), None)),
+                },
+                AttributeContent::Expression(expression) => {
+                    self.expression(&expression.value, position)
+                }
+            }
+        }
+
+        let children = self.capture(children)?;
+        let html = template(&ComponentProps {
+            name,
+            attributes: literal_attributes,
+            children,
+            position,
+        });
+        self.out.push_str(&html);
+        Ok(())
+    }
+
+    fn unknown_component(
+        &mut self,
+        name: &str,
+        children: &[Node],
+        position: Option<&Position>,
+    ) -> Result<()> {
+        let policy = self.options.unknown_components;
+        if policy == UnknownComponents::Error {
+            let mut err = MdxError::unknown_component(name);
+            if let Some(position) = position {
+                err = err.with_location(position.start.line, position.start.column);
+            }
+            return Err(err.into());
+        }
+
+        self.ctx.push_diagnostic(
+            Diagnostic::warning(
+                "unknown-component",
+                format!("No HTML template for component `<{}>`", name),
+            )
+            .with_position(position),
+        );
+        match policy {
+            UnknownComponents::Unwrap => self.nodes(children),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compile_to_html, MdxCompileOptions, MdxError};
+
+    fn html(mdx: &str, options: &HtmlOptions) -> String {
+        compile_to_html(mdx, MdxCompileOptions::new().with_all_features(), options)
+            .unwrap()
+            .html
+    }
+
+    #[test]
+    fn test_markdown() {
+        let mdx = "# Tom & *Jerry*\n\nA [link](/a \"T\") and ![img](/i.png) `x<y`.  \nNext\n\n> quote\n\n---";
+        assert_eq!(
+            html(mdx, &HtmlOptions::new()),
+            "<h1>Tom &amp; <em>Jerry</em></h1>\n\
+             <p>A <a href=\"/a\" title=\"T\">link</a> and <img src=\"/i.png\" alt=\"img\" /> <code>x&lt;y</code>.<br />\nNext</p>\n\
+             <blockquote>\n<p>quote</p>\n</blockquote>\n\
+             <hr />\n"
+        );
+    }
+
+    #[test]
+    fn test_lists_and_tasks() {
+        let mdx = "- [x] done\n- [ ] todo\n  - nested\n\n3. three\n\n   more";
+        assert_eq!(
+            html(mdx, &HtmlOptions::new()),
+            "<ul>\n\
+             <li><input type=\"checkbox\" checked disabled /> done</li>\n\
+             <li><input type=\"checkbox\" disabled /> todo\n<ul>\n<li>nested</li>\n</ul>\n</li>\n\
+             </ul>\n\
+             <ol start=\"3\">\n<li>\n<p>three</p>\n<p>more</p>\n</li>\n</ol>\n"
+        );
+    }
+
+    #[test]
+    fn test_tables_and_footnotes() {
+        let mdx = "| a | b |\n|:-:|---|\n| 1 | 2 |\n\nText[^1]\n\n[^1]: Note";
+        assert_eq!(
+            html(mdx, &HtmlOptions::new()),
+            "<table>\n<thead>\n<tr>\n<th style=\"text-align: center\">a</th>\n<th>b</th>\n</tr>\n</thead>\n\
+             <tbody>\n<tr>\n<td style=\"text-align: center\">1</td>\n<td>2</td>\n</tr>\n</tbody>\n</table>\n\
+             <p>Text<sup><a href=\"#fn-1\" id=\"fnref-1\">1</a></sup></p>\n\
+             <div id=\"fn-1\"><p>1. Note <a href=\"#fnref-1\">\u{21a9}</a></p>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let mdx = "```ts title=\"a.ts\" {1,3-4}\nlet a = \"<b>\";\n```";
+        assert_eq!(
+            html(mdx, &HtmlOptions::new()),
+            "<pre><code class=\"language-ts\">let a = &quot;&lt;b&gt;&quot;;</code></pre>\n"
+        );
+
+        let options = HtmlOptions::new().with_component("CodeBlock", |props| {
+            format!(
+                "<figure data-title=\"{}\" data-lines=\"{}\">{}</figure>",
+                props.attribute("title").unwrap_or_default(),
+                props.attribute("highlightLines").unwrap_or_default(),
+                escape_html(props.attribute("code").unwrap_or_default())
+            )
+        });
+        assert_eq!(
+            html(mdx, &options),
+            "<figure data-title=\"a.ts\" data-lines=\"1,3,4\">let a = &quot;&lt;b&gt;&quot;;</figure>\n"
+        );
+    }
+
+    #[test]
+    fn test_jsx_elements_and_expressions() {
+        let mdx = "<div className=\"box\" hidden onClick={go} title={t}>\n  Hi {props.name}{/* note */}\n</div>\n\n<>a<br/></>";
+        let result = compile_to_html(mdx, MdxCompileOptions::new(), &HtmlOptions::new()).unwrap();
+        assert_eq!(
+            result.html,
+            "<div class=\"box\" hidden><p>Hi </p>\n</div>\n<p>a<br /></p>\n"
+        );
+        let codes: Vec<&str> = result.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            ["expression-not-rendered", "expression-not-rendered"]
+        );
+    }
+
+    #[test]
+    fn test_jsx_elements_without_scripts() {
+        let mdx = concat!(
+            "<script>alert(1)</script>\n\n",
+            "Text <script>alert(1)</script> <img src=\"/a.png\" ONERROR=\"alert(1)\" />\n\n",
+            "<iframe srcDoc=\"<script>alert(1)</script>\" />\n\n",
+            "<form action=\"javascript:alert(1)\"><button formAction=\"/ok\">Go</button></form>\n\n",
+            "<video poster=\"javascript:x\" /> <img srcSet=\"/a.png 1x, javascript:x 2x\" />"
+        );
+        let options = MdxCompileOptions::new().with_url_policy(crate::UrlPolicy::new());
+        let result = compile_to_html(mdx, options, &HtmlOptions::new()).unwrap();
+        assert_eq!(
+            result.html,
+            "<p></p>\n<p>Text  <img src=\"/a.png\" /></p>\n\
+             <iframe></iframe>\n\
+             <p><form action=\"#\"><button formAction=\"/ok\">Go</button></form></p>\n\
+             <video poster=\"#\"></video>\n<img srcset=\"/a.png 1x, # 2x\" />\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_components() {
+        let mdx = "<Chart data={points}>\n  Caption\n</Chart>\n\nEnd";
+
+        let result = compile_to_html(mdx, MdxCompileOptions::new(), &HtmlOptions::new()).unwrap();
+        assert_eq!(result.html, "<p>Caption</p>\n\n<p>End</p>\n");
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.code == "unknown-component"));
+
+        let drop = HtmlOptions::new().with_unknown_components(UnknownComponents::Drop);
+        assert_eq!(html(mdx, &drop), "\n<p>End</p>\n");
+
+        let error = HtmlOptions::new().with_unknown_components(UnknownComponents::Error);
+        let err: Box<MdxError> =
+            compile_to_html(mdx, MdxCompileOptions::new(), &error).unwrap_err();
+        assert_eq!(err.code, Some(crate::ErrorCode::UnknownComponent));
+        assert_eq!((err.line, err.column), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn test_url_policy() {
+        let options = MdxCompileOptions::new().with_url_policy(crate::UrlPolicy::new());
+        let mdx = "[a](javascript:alert(1)) <a href=\"javascript:x\">b</a>";
+        let result = compile_to_html(mdx, options, &HtmlOptions::new()).unwrap();
+        assert_eq!(
+            result.html,
+            "<p><a href=\"#\">a</a> <a href=\"#\">b</a></p>\n"
+        );
+
+        let options = MdxCompileOptions::new()
+            .with_url_policy(crate::UrlPolicy::new().on_violation(crate::UrlViolation::Strip));
+        let result = compile_to_html(mdx, options, &HtmlOptions::new()).unwrap();
+        assert_eq!(result.html, "<p><a>a</a> <a>b</a></p>\n");
+    }
+}
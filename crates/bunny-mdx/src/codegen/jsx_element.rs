@@ -0,0 +1,96 @@
+//! JSX element description
+//!
+//! Node handlers describe their output as [`JsxElement`]s instead of
+//! formatting `_jsx(...)` strings themselves. The emitter turns them into
+//! runtime calls or JSX syntax when building the output program.
+
+use markdown::unist::Position;
+
+use super::jsx_value::JsValue;
+
+/// A single JSX runtime call: `_jsx(tag, {props, children}, key)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxElement {
+    /// Component expression (`_components.p`, `"div"`, `Button`, ...)
+    pub tag: String,
+    /// Props in output order (children are always written last)
+    pub props: Vec<JsxProp>,
+    /// Children of the element, if any
+    pub children: Option<JsValue>,
+    /// React key (third argument of the runtime call)
+    pub key: Option<String>,
+    /// Source position of the mdast node this element was generated from
+    pub position: Option<Position>,
+}
+
+/// A prop entry in a JSX props object
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxProp {
+    /// Spread expression: `...expr`
+    Spread(String),
+    /// Named prop: `name: value`
+    Attr(String, JsValue),
+}
+
+impl JsxElement {
+    /// Create an element for an arbitrary tag expression
+    pub fn new(tag: impl Into<String>) -> Self {
+        Self {
+            tag: tag.into(),
+            props: Vec::new(),
+            children: None,
+            key: None,
+            position: None,
+        }
+    }
+
+    /// Create an element that resolves through the `_components` map
+    pub fn component(name: &str) -> Self {
+        Self::new(format!("_components.{}", name))
+    }
+
+    /// Spread the MDXContent `props` onto this element
+    pub fn spread_props(self) -> Self {
+        self.spread("props")
+    }
+
+    /// Add a spread prop (`...expr`)
+    pub fn spread(mut self, expr: impl Into<String>) -> Self {
+        self.props.push(JsxProp::Spread(expr.into()));
+        self
+    }
+
+    /// Add a named prop
+    pub fn prop(mut self, name: impl Into<String>, value: JsValue) -> Self {
+        self.props.push(JsxProp::Attr(name.into(), value));
+        self
+    }
+
+    /// Set the children of this element
+    pub fn children(mut self, children: JsValue) -> Self {
+        self.children = Some(children);
+        self
+    }
+
+    /// Set the React key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Record the source position this element was generated from
+    pub fn at(mut self, position: Option<&Position>) -> Self {
+        self.position = position.cloned();
+        self
+    }
+
+    /// Whether the children are static (an array), which selects `jsxs`
+    pub fn has_static_children(&self) -> bool {
+        matches!(self.children, Some(JsValue::Array(_)))
+    }
+
+    /// Wrap this element into a `JsValue`
+    pub fn into_value(self) -> JsValue {
+        JsValue::Element(Box::new(self))
+    }
+}
@@ -1,6 +1,9 @@
 //! JavaScript value types with proper escaping
 
-use super::escape::escape_js_string;
+use anyhow::Result;
+use markdown::unist::Position;
+
+use super::jsx_element::JsxElement;
 
 /// Represents a JavaScript value with proper escaping semantics
 ///
@@ -18,6 +21,29 @@ pub enum JsValue {
 
     /// Array of mixed values (for children arrays)
     Array(Vec<JsValue>),
+
+    /// Boolean literal, like the `true` of an attribute without a value
+    Bool(bool),
+
+    /// Object literal with properties in order
+    /// Examples: `{__html: "<b>"}`, style objects
+    Object(Vec<(String, JsValue)>),
+
+    /// A JSX runtime call (`_jsx(...)`), see [`JsxElement`]
+    Element(Box<JsxElement>),
+
+    /// Author-written JavaScript from the MDX source (e.g. `{props.name}`)
+    Expression {
+        code: String,
+        position: Option<Position>,
+    },
+
+    /// Ternary expression: `(test ? consequent : alternate)`
+    Conditional {
+        test: String,
+        consequent: Box<JsValue>,
+        alternate: Box<JsValue>,
+    },
 }
 
 impl JsValue {
@@ -36,17 +62,44 @@ impl JsValue {
         Self::Array(values)
     }
 
-    /// Convert to JavaScript code
-    pub fn to_js(&self) -> String {
-        match self {
-            JsValue::Raw(s) => s.clone(),
-            JsValue::Text(s) => format!("\"{}\"", escape_js_string(s)),
-            JsValue::Array(items) => {
-                let elements: Vec<String> = items.iter().map(|v| v.to_js()).collect();
-                format!("[{}]", elements.join(", "))
-            }
+    /// Create a boolean value
+    pub fn bool(value: bool) -> Self {
+        Self::Bool(value)
+    }
+
+    /// Create an object value from `(key, value)` properties
+    pub fn object(properties: Vec<(String, JsValue)>) -> Self {
+        Self::Object(properties)
+    }
+
+    /// Create an expression value from author-written code
+    pub fn expression(code: impl Into<String>, position: Option<&Position>) -> Self {
+        Self::Expression {
+            code: code.into(),
+            position: position.cloned(),
         }
     }
+
+    /// Create a conditional value
+    pub fn conditional(test: impl Into<String>, consequent: JsValue, alternate: JsValue) -> Self {
+        Self::Conditional {
+            test: test.into(),
+            consequent: Box::new(consequent),
+            alternate: Box::new(alternate),
+        }
+    }
+
+    /// Whether this value is plain text
+    pub fn is_text(&self) -> bool {
+        matches!(self, JsValue::Text(_))
+    }
+
+    /// Convert to JavaScript code
+    ///
+    /// Fails when a raw or author-written part is not valid JavaScript.
+    pub fn to_js(&self) -> Result<String> {
+        super::emit::value_to_js(self)
+    }
 }
 
 #[cfg(test)]
@@ -55,15 +108,24 @@ mod jsvalue_tests {
 
     #[test]
     fn test_raw_values() {
-        assert_eq!(JsValue::raw("_jsx(Foo, {})").to_js(), "_jsx(Foo, {})");
-        assert_eq!(JsValue::raw("props.name").to_js(), "props.name");
+        assert_eq!(
+            JsValue::raw("_jsx(Foo, {})").to_js().unwrap(),
+            "_jsx(Foo, {})"
+        );
+        assert_eq!(JsValue::raw("props.name").to_js().unwrap(), "props.name");
     }
 
     #[test]
     fn test_text_values() {
-        assert_eq!(JsValue::text("hello").to_js(), "\"hello\"");
-        assert_eq!(JsValue::text("say \"hi\"").to_js(), "\"say \\\"hi\\\"\"");
-        assert_eq!(JsValue::text("line\nbreak").to_js(), "\"line\\nbreak\"");
+        assert_eq!(JsValue::text("hello").to_js().unwrap(), "\"hello\"");
+        assert_eq!(
+            JsValue::text("say \"hi\"").to_js().unwrap(),
+            "\"say \\\"hi\\\"\""
+        );
+        assert_eq!(
+            JsValue::text("line\nbreak").to_js().unwrap(),
+            "\"line\\nbreak\""
+        );
     }
 
     #[test]
@@ -73,7 +135,23 @@ mod jsvalue_tests {
             JsValue::raw("props.name"),
             JsValue::text("world"),
         ]);
-        assert_eq!(arr.to_js(), "[\"hello\", props.name, \"world\"]");
+        assert_eq!(
+            arr.to_js().unwrap(),
+            "[\n  \"hello\",\n  props.name,\n  \"world\"\n]"
+        );
+    }
+
+    #[test]
+    fn test_object_values() {
+        let object = JsValue::object(vec![
+            ("__html".to_string(), JsValue::text("<b>\"hi\"</b>")),
+            ("--gap".to_string(), JsValue::text("1px")),
+            ("hidden".to_string(), JsValue::bool(true)),
+        ]);
+        assert_eq!(
+            object.to_js().unwrap(),
+            "({\n  __html: \"<b>\\\"hi\\\"</b>\",\n  \"--gap\": \"1px\",\n  hidden: true\n})"
+        );
     }
 
     #[test]
@@ -81,6 +159,6 @@ mod jsvalue_tests {
         // Text value already containing quotes should not double-escape
         let text = "already\\nescaped";
         let value = JsValue::text(text);
-        assert_eq!(value.to_js(), "\"already\\\\nescaped\"");
+        assert_eq!(value.to_js().unwrap(), "\"already\\\\nescaped\"");
     }
 }
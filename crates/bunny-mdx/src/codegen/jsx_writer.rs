@@ -1,7 +0,0 @@
-//! JSX string building utilities
-
-// This module will contain high-level utilities for building JSX strings
-// Currently kept minimal as most logic is in renderer and nodes modules
-
-// Placeholder for future JSX writing utilities
-// e.g., building component calls, formatting attributes, etc.
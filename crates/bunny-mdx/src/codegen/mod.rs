@@ -1,15 +1,24 @@
 //! JSX code generation
 //!
-//! Converts markdown AST nodes to JSX code strings with proper escaping
-//! and React runtime integration.
+//! Converts markdown AST nodes to an `oxc_ast` program with proper escaping
+//! and React runtime integration, printed with `oxc_codegen`. The `html`
+//! backend renders the same tree to static HTML instead.
 
 mod context;
+mod elements;
+mod emit;
 mod escape;
+mod html;
+mod jsx_element;
 mod jsx_value;
-mod jsx_writer;
 mod renderer;
 
-pub use context::{CodegenContext, TableContext};
-pub use escape::{escape_js_string, is_valid_identifier};
+pub use context::{CodegenContext, ComponentReference, TableContext};
+pub use elements::{add_element_props, ElementInfo};
+pub use escape::{escape_html, escape_js_string, is_valid_identifier};
+pub(crate) use html::render_html;
+pub use html::{ComponentProps, ComponentTemplate, HtmlOptions, UnknownComponents};
+pub use jsx_element::{JsxElement, JsxProp};
 pub use jsx_value::JsValue;
+pub(crate) use renderer::render;
 pub use renderer::{mdast_to_jsx, mdast_to_jsx_with_options};
@@ -2,9 +2,44 @@
 
 use anyhow::{anyhow, Context, Result};
 use markdown::mdast::Node;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{Declaration, ExportDefaultDeclarationKind, Statement, UnaryOperator};
+use oxc_span::{SourceType, SPAN};
 
 use super::context::CodegenContext;
+use super::emit::{collect_unnamed_tags, print, ElementStyle, Emitter};
+use super::escape::is_valid_identifier;
+use super::jsx_element::JsxElement;
+use super::jsx_value::JsValue;
+use crate::diagnostic::Diagnostic;
 use crate::frontmatter::extract_frontmatter;
+use crate::plugins::{HeadingAnchor, HeadingIds, PluginContext};
+use crate::stats::DocumentStats;
+
+/// `fileName` passed to `jsxDEV` when the MDX file has no path
+const DEFAULT_DEV_FILE_NAME: &str = "<source.mdx>";
+
+/// Source map `sources` entry when the MDX file has no path
+const DEFAULT_SOURCE_NAME: &str = "input.mdx";
+
+/// Helper emitted in development mode for missing provider components
+const MISSING_REFERENCE_HELPER: &str = r#"function _missingMdxReference(id, place) {
+  throw new Error("MDX component `" + id + "` was not provided (referenced at " + place + "). Import it in the MDX file or pass it via the `components` prop.");
+}
+"#;
+
+/// Everything produced by a single codegen pass
+#[derive(Debug)]
+pub(crate) struct RenderOutput {
+    pub code: String,
+    pub map: Option<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub stats: DocumentStats,
+    /// State plugins kept for this document
+    pub plugin_context: PluginContext,
+    /// The tree after `transform_ast` plugins ran
+    pub root: Node,
+}
 
 /// Convert MDX mdast to JSX string with React 19 and MDX v3 compatibility
 ///
@@ -29,9 +64,16 @@ pub fn mdast_to_jsx(root: &Node) -> Result<String> {
 ///
 /// 1. Extract frontmatter from AST (or use pre-extracted from options)
 /// 2. Run all `plugin.transform_ast()` in registration order
-/// 3. Convert AST to JSX
-/// 4. Run all `plugin.transform_jsx()` in registration order
+/// 3. Convert AST to an `oxc_ast` program
+/// 4. Run all `plugin.transform_program()` in registration order
+/// 5. Print the program with `oxc_codegen`
+/// 6. Run all `plugin.transform_jsx()` in registration order
 pub fn mdast_to_jsx_with_options(root: &Node, options: &crate::mdx::MdxOptions) -> Result<String> {
+    render(root, options).map(|output| output.code)
+}
+
+/// Convert mdast to JSX, also returning diagnostics collected along the way
+pub(crate) fn render(root: &Node, options: &crate::mdx::MdxOptions) -> Result<RenderOutput> {
     // Use pre-extracted frontmatter if provided, otherwise extract from AST
     let (mut cleaned_root, frontmatter) = if options.frontmatter.is_some() {
         // Frontmatter already extracted, just clone root and use provided frontmatter
@@ -42,77 +84,82 @@ pub fn mdast_to_jsx_with_options(root: &Node, options: &crate::mdx::MdxOptions)
     };
 
     // Run AST transformation plugins
+    let mut plugin_context = PluginContext::new(options.filepath.clone());
     for plugin in &options.plugins {
         tracing::debug!(plugin = plugin.name(), "Running AST transformation plugin");
-        plugin.transform_ast(&mut cleaned_root).with_context(|| {
-            format!(
-                "Plugin '{}' failed during AST transformation",
-                plugin.name()
-            )
-        })?;
+        plugin_context.set_plugin(Some(plugin.name()));
+        plugin
+            .transform_ast(&mut cleaned_root, &mut plugin_context)
+            .with_context(|| {
+                format!(
+                    "Plugin '{}' failed during AST transformation",
+                    plugin.name()
+                )
+            })?;
     }
 
     let mut imports = Vec::new();
     let mut named_exports = Vec::new();
     let mut reexports = Vec::new();
     let mut jsx_elements = Vec::new();
+    let mut layout = None;
     let mut ctx = CodegenContext::new();
+    ctx.raw_html = options.raw_html;
+    ctx.url_policy = options.url_policy.clone();
+    ctx.heading_ids = HeadingIds::take(&mut plugin_context);
+    ctx.heading_anchor = HeadingAnchor::take(&mut plugin_context);
 
-    // NOTE: For bunny-next remote MDX, we don't import useMDXComponents here
-    // because MDXRemote handles all component resolution.
-    // These imports would cause Server Component boundary issues.
+    // Definitions can appear anywhere (even after their references)
+    ctx.collect_definitions(&cleaned_root);
 
-    // imports.push(
-    //     "import {useMDXComponents as _provideComponents} from '@bunny/mdx-runtime';".to_string(),
-    // );
-    // imports.push(
-    //     "import {useTaskListContext as _useTaskListContext} from '@bunny/mdx-runtime';".to_string(),
-    // );
+    // NOTE: For bunny-next remote MDX, we don't import useMDXComponents by
+    // default because MDXRemote handles all component resolution, and the
+    // import would cause Server Component boundary issues. Apps that use a
+    // context provider opt in with `provider_import_source`.
 
     if let Node::Root(root_node) = &cleaned_root {
         for child in &root_node.children {
             match child {
                 Node::MdxjsEsm(esm) => {
-                    // Categorize ESM statements
-                    let code = esm.value.trim();
-
-                    if crate::esm::is_reexport(code) {
-                        // Re-exports: export {...} from './x'
-                        reexports.push(code.to_string());
-                    } else if crate::esm::has_named_exports(code) {
-                        // Named exports: export const meta = ...
-                        named_exports.push(code.to_string());
-                    } else if !code.starts_with("export default") {
-                        // Regular imports: import {...} from './x'
-                        // Note: markdown-rs may combine multiple imports into one ESM node
-                        imports.push(code.to_string());
-
-                        // Extract imported component names from each line
-                        // (markdown-rs combines consecutive imports into one node)
-                        for line in code.lines() {
-                            let line = line.trim();
-                            if line.starts_with("import ") {
-                                let imported_names = crate::esm::extract_imported_names(line);
+                    // markdown-rs may combine several statements into one ESM node
+                    for statement in crate::esm::split_statements(&esm.value) {
+                        let code = statement.as_str();
+
+                        // Categorize ESM statements
+                        if crate::esm::is_reexport(code) {
+                            // Re-exports: export {...} from './x'
+                            reexports.push(statement);
+                        } else if crate::esm::has_named_exports(code) {
+                            // Named exports: export const meta = ...
+                            named_exports.push(statement);
+                        } else if code.starts_with("export default") {
+                            // `export default Layout` wraps the content in a layout
+                            layout = crate::esm::get_default_export_expression(code)
+                                .map(|expression| (expression, esm.position.clone()));
+                        } else {
+                            // Regular imports: import {...} from './x'
+                            if code.starts_with("import ") {
+                                let imported_names = crate::esm::extract_imported_names(code);
 
                                 // Debug logging to diagnose MDX import issues
                                 tracing::info!(
-                                    import_statement = line,
+                                    import_statement = code,
                                     extracted_names = ?imported_names,
                                     "Extracted component names from MDX import"
                                 );
 
                                 ctx.imported_components.extend(imported_names);
                             }
+                            imports.push(statement);
                         }
                     }
-                    // Skip export default statements - we generate our own
                 }
                 _ => {
                     // Convert markdown/MDX nodes to JSX - use full path to avoid circular dependency
                     if let Some(jsx_value) =
                         super::super::nodes::node_to_jsx(child, &mut ctx, false)?
                     {
-                        jsx_elements.push(jsx_value.to_js()); // Convert JsValue to String
+                        jsx_elements.push(jsx_value);
                     }
                 }
             }
@@ -132,145 +179,393 @@ pub fn mdast_to_jsx_with_options(root: &Node, options: &crate::mdx::MdxOptions)
 
     // Generate MDXContent component with React 19 JSX runtime
     let (content, needs_fragment) = if jsx_elements.is_empty() {
-        (String::from("null"), false)
+        (JsValue::raw("null"), false)
     } else if jsx_elements.len() == 1 {
-        (jsx_elements[0].clone(), false)
+        (jsx_elements.remove(0), false)
     } else {
         // Use jsxs for static multi-child Fragments
         // jsxs tells React: "these children are static, skip key warnings"
         (
-            format!(
-                "_jsxs(_Fragment, {{children: [{}]}})",
-                jsx_elements.join(", ")
-            ),
+            JsxElement::new("_Fragment")
+                .children(JsValue::array(jsx_elements))
+                .into_value(),
             true,
         )
     };
+    let needs_fragment = needs_fragment || ctx.uses_fragment;
 
-    // Build MDXContent function body (shared between formats)
-    let mdx_content_body = format!(
-        r#"function MDXContent({{components: _cProp = {{}}, ...props}}) {{
-  const _components = Object.assign({{
-    h1: "h1", h2: "h2", h3: "h3", h4: "h4", h5: "h5", h6: "h6",
-    p: "p", a: "a", strong: "strong", em: "em", code: "code", pre: "pre",
-    blockquote: "blockquote", ul: "ul", ol: "ol", li: "li",
-    table: "table", thead: "thead", tbody: "tbody", tr: "tr", th: "th", td: "td",
-    hr: "hr", br: "br", img: "img", del: "del", div: "div", span: "span", sup: "sup", input: "input"
-  }}, _cProp);
-  const _taskListCtx = null; // Task list context disabled for now
-  const _handleTaskToggle = (e) => {{
-    const taskId = e.target.getAttribute('data-task-id');
-    if (taskId && _taskListCtx) {{
-      _taskListCtx.toggleTask(taskId, e.target.checked);
-    }}
-  }};
-  return {};
-}}"#,
-        content
-    );
+    // `export default Layout` wraps the content; otherwise a provided
+    // `wrapper` component does (the layout takes precedence, as in MDX v3)
+    let mut layout_expression = None;
+    let wrapped_content = match layout {
+        Some((expression, position)) => {
+            let tag = if is_valid_identifier(&expression) {
+                expression
+            } else {
+                layout_expression = Some(expression);
+                "MDXLayout".to_string()
+            };
+            JsxElement::new(tag)
+                .spread_props()
+                .children(JsValue::raw("_content"))
+                .at(position.as_ref())
+                .into_value()
+        }
+        None => JsValue::conditional(
+            "_components.wrapper",
+            JsxElement::new("_components.wrapper")
+                .spread_props()
+                .children(JsValue::raw("_content"))
+                .into_value(),
+            JsValue::raw("_content"),
+        ),
+    };
 
-    // Build final output based on format
-    let mut output = String::new();
+    // File name reported to React in development mode
+    let dev_file_name = options.development.then(|| {
+        options
+            .filepath
+            .clone()
+            .unwrap_or_else(|| DEFAULT_DEV_FILE_NAME.to_string())
+    });
 
-    match options.output_format {
-        crate::OutputFormat::Program => {
-            // Program format: ES module with import/export
-            // Add JSX runtime imports based on what we need
-            let jsx_runtime = if needs_fragment {
-                format!(
-                    "import {{jsx as _jsx, jsxs as _jsxs, Fragment as _Fragment}} from '{}';",
-                    options.jsx_runtime
-                )
-            } else {
-                format!(
-                    "import {{jsx as _jsx, jsxs as _jsxs}} from '{}';",
-                    options.jsx_runtime
-                )
+    let style = match (&options.output_format, &dev_file_name) {
+        (crate::OutputFormat::Jsx, _) => ElementStyle::Jsx,
+        (_, Some(file_name)) => ElementStyle::Development(file_name.clone()),
+        (_, None) => ElementStyle::Runtime,
+    };
+    let allocator = Allocator::default();
+    let mut emitter = Emitter::new(&allocator, style);
+    if let Some(source) = &options.source {
+        emitter = emitter.with_source(source);
+    }
+    let ast = emitter.ast();
+
+    // Tags like `_components["foo-bar"]` can't be JSX element names
+    let mut unnamed_tags = Vec::new();
+    if options.output_format == crate::OutputFormat::Jsx {
+        collect_unnamed_tags(&content, &mut unnamed_tags);
+        collect_unnamed_tags(&wrapped_content, &mut unnamed_tags);
+    }
+    let mut aliases = Vec::new();
+    for (index, tag) in unnamed_tags.into_iter().enumerate() {
+        let alias = format!("_component{}", index);
+        emitter.alias_tag(tag.clone(), alias.clone());
+        aliases.push((alias, tag));
+    }
+
+    // MDXContent function (shared between formats): the prologue from the
+    // template, then the statements that depend on the document
+    let mut mdx_content = emitter.statements(&mdx_content_template(
+        options.provider_import_source.is_some(),
+    ))?;
+    let Some(Statement::FunctionDeclaration(mut mdx_content)) = mdx_content.pop() else {
+        return Err(anyhow!(
+            "MDXContent template must be a function declaration"
+        ));
+    };
+    let mdx_content_body = &mut mdx_content
+        .body
+        .as_mut()
+        .ok_or_else(|| anyhow!("MDXContent template must have a body"))?
+        .statements;
+    if let Some(file_name) = &dev_file_name {
+        // Fail loudly (with the MDX location) instead of rendering `undefined`
+        for (name, reference) in &ctx.component_references {
+            let place = match &reference.position {
+                Some(position) => format!(
+                    "{}:{}:{}",
+                    file_name, position.start.line, position.start.column
+                ),
+                None => file_name.clone(),
             };
-            imports.insert(0, jsx_runtime);
+            let test = ast.expression_unary(
+                SPAN,
+                UnaryOperator::LogicalNot,
+                emitter.code_expression(&reference.access)?,
+            );
+            let report = emitter.call(
+                "_missingMdxReference",
+                vec![emitter.string(name), emitter.string(&place)],
+            );
+            mdx_content_body.push(ast.statement_if(
+                SPAN,
+                test,
+                ast.statement_expression(SPAN, report),
+                None,
+            ));
+        }
+    }
+    for (alias, tag) in &aliases {
+        mdx_content_body.push(emitter.const_declaration(alias, emitter.code_expression(tag)?));
+    }
+    mdx_content_body.push(emitter.const_declaration("_content", emitter.expression(&content)?));
+    mdx_content_body.push(ast.statement_return(SPAN, Some(emitter.expression(&wrapped_content)?)));
+    let needs_missing_reference = options.development && !ctx.component_references.is_empty();
 
-            // Add imports
-            if !imports.is_empty() {
-                output.push_str(&imports.join("\n"));
-                output.push_str("\n\n");
+    // Build final output based on format
+    let mut directives = ast.vec();
+    let mut body = ast.vec();
+    let source_type = match options.output_format {
+        crate::OutputFormat::Program | crate::OutputFormat::Jsx => {
+            // Program format: ES module with import/export
+            // Add JSX runtime imports based on what we need (JSX syntax is
+            // compiled by the consumer's own JSX transform)
+            if options.output_format == crate::OutputFormat::Program {
+                let mut names = if options.development {
+                    vec![("jsxDEV", "_jsxDEV")]
+                } else {
+                    vec![("jsx", "_jsx"), ("jsxs", "_jsxs")]
+                };
+                if needs_fragment {
+                    names.push(("Fragment", "_Fragment"));
+                }
+                let runtime = if options.development {
+                    dev_runtime_source(&options.jsx_runtime)
+                } else {
+                    options.jsx_runtime.clone()
+                };
+                body.push(emitter.import(&names, &runtime));
+            }
+            if let Some(source) = &options.provider_import_source {
+                body.push(emitter.import(&[("useMDXComponents", "_provideComponents")], source));
+            }
+
+            // Add imports, then named exports before default export
+            for statement in imports.iter().chain(&named_exports) {
+                body.extend(emitter.statements(statement)?);
             }
 
-            // Add named exports before default export
-            if !named_exports.is_empty() {
-                output.push_str(&named_exports.join("\n"));
-                output.push_str("\n\n");
+            if let Some(expression) = &layout_expression {
+                body.push(
+                    emitter.const_declaration("MDXLayout", emitter.code_expression(expression)?),
+                );
             }
 
             // Add MDXContent default export
-            output.push_str("export default ");
-            output.push_str(&mdx_content_body);
+            body.push(Statement::from(
+                ast.module_declaration_export_default_declaration(
+                    SPAN,
+                    ExportDefaultDeclarationKind::FunctionDeclaration(mdx_content),
+                ),
+            ));
+            if needs_missing_reference {
+                body.extend(emitter.statements(MISSING_REFERENCE_HELPER)?);
+            }
 
             // Add re-exports after default export
-            if !reexports.is_empty() {
-                output.push_str("\n\n");
-                output.push_str(&reexports.join("\n"));
+            for statement in &reexports {
+                body.extend(emitter.statements(statement)?);
             }
+
+            SourceType::mjs().with_jsx(options.output_format == crate::OutputFormat::Jsx)
         }
         crate::OutputFormat::FunctionBody => {
             // Function-body format: for runtime eval with new Function()
-            output.push_str("\"use strict\";\n");
+            directives.push(emitter.directive("use strict"));
 
             // Provide JSX runtime from arguments[0]
-            if needs_fragment {
-                output.push_str(
-                    "const {jsx: _jsx, jsxs: _jsxs, Fragment: _Fragment} = arguments[0];\n",
-                );
+            let mut runtime_bindings = if options.development {
+                vec!["jsxDEV: _jsxDEV"]
             } else {
-                output.push_str("const {jsx: _jsx, jsxs: _jsxs} = arguments[0];\n");
+                vec!["jsx: _jsx", "jsxs: _jsxs"]
+            };
+            if needs_fragment {
+                runtime_bindings.push("Fragment: _Fragment");
+            }
+            if options.provider_import_source.is_some() {
+                runtime_bindings.push("useMDXComponents: _provideComponents");
             }
+            body.extend(emitter.statements(&format!(
+                "const {{{}}} = arguments[0];",
+                runtime_bindings.join(", ")
+            ))?);
 
-            // Extract named export names for return object
+            // Add named exports as plain declarations, collecting the
+            // `(exported, local)` names for the return object
             let mut export_names = Vec::new();
-
-            // Add named exports as const declarations (remove "export" keyword)
             for export in &named_exports {
-                // Remove "export " prefix
-                let const_decl = export.replace("export ", "");
-                output.push_str(&const_decl);
-                output.push_str("\n");
-
-                // Extract variable name for return object
-                // Pattern: "const NAME = ..." or "const NAME ="
-                if let Some(const_start) = const_decl.find("const ") {
-                    let after_const = &const_decl[const_start + 6..];
-                    if let Some(name_end) = after_const.find(|c: char| c == ' ' || c == '=') {
-                        let name = after_const[..name_end].trim();
-                        if !name.is_empty() {
-                            export_names.push(name.to_string());
+                for statement in emitter.statements(export)? {
+                    let Statement::ExportNamedDeclaration(export) = statement else {
+                        body.push(statement);
+                        continue;
+                    };
+                    let export = export.unbox();
+                    for specifier in &export.specifiers {
+                        export_names.push((
+                            specifier.exported.name().to_string(),
+                            specifier.local.name().to_string(),
+                        ));
+                    }
+                    if let Some(declaration) = export.declaration {
+                        for name in declared_names(&declaration) {
+                            export_names.push((name.clone(), name));
                         }
+                        body.push(Statement::from(declaration));
                     }
                 }
             }
 
+            if let Some(expression) = &layout_expression {
+                body.push(
+                    emitter.const_declaration("MDXLayout", emitter.code_expression(expression)?),
+                );
+            }
+
             // Add MDXContent function (without export default)
-            output.push_str(&mdx_content_body);
-            output.push_str("\n");
+            body.push(Statement::FunctionDeclaration(mdx_content));
+            if needs_missing_reference {
+                body.extend(emitter.statements(MISSING_REFERENCE_HELPER)?);
+            }
 
             // Build return object with all exports
-            output.push_str("return {default: MDXContent");
-            // Add named exports to return object
-            for name in &export_names {
-                output.push_str(&format!(", {}: {}", name, name));
+            let mut properties =
+                ast.vec1(emitter.property("default", emitter.identifier("MDXContent")));
+            for (exported, local) in &export_names {
+                properties.push(emitter.property(exported, emitter.identifier(local)));
             }
-            output.push_str("};\n");
+            body.push(ast.statement_return(SPAN, Some(ast.expression_object(SPAN, properties))));
+
+            SourceType::cjs()
         }
+    };
+
+    let mut program = emitter.program(source_type, directives, body);
+
+    // Run program transformation plugins
+    for plugin in &options.plugins {
+        tracing::debug!(
+            plugin = plugin.name(),
+            "Running program transformation plugin"
+        );
+        plugin_context.set_plugin(Some(plugin.name()));
+        plugin
+            .transform_program(&mut program, ast, &mut plugin_context)
+            .with_context(|| {
+                format!(
+                    "Plugin '{}' failed during program transformation",
+                    plugin.name()
+                )
+            })?;
     }
 
+    // Mappings come from node spans, so they cover `transform_program` edits
+    // but not `transform_jsx` ones (see `MdxPlugin::transform_jsx`)
+    let source_map_path = options
+        .source_map
+        .then(|| options.filepath.as_deref().unwrap_or(DEFAULT_SOURCE_NAME));
+    let (mut output, map) = print(&program, source_map_path);
+
     // Run JSX transformation plugins
     for plugin in &options.plugins {
         tracing::debug!(plugin = plugin.name(), "Running JSX transformation plugin");
-        plugin.transform_jsx(&mut output).with_context(|| {
-            format!(
-                "Plugin '{}' failed during JSX transformation",
-                plugin.name()
-            )
-        })?;
+        plugin_context.set_plugin(Some(plugin.name()));
+        plugin
+            .transform_jsx(&mut output, &mut plugin_context)
+            .with_context(|| {
+                format!(
+                    "Plugin '{}' failed during JSX transformation",
+                    plugin.name()
+                )
+            })?;
     }
 
-    Ok(output)
+    plugin_context.set_plugin(None);
+
+    // Compiler and plugin diagnostics, in source order
+    let mut diagnostics = ctx.diagnostics;
+    diagnostics.extend(plugin_context.take_diagnostics());
+    diagnostics
+        .sort_by_key(|diagnostic| diagnostic.span.map_or(usize::MAX, |span| span.start.offset));
+
+    Ok(RenderOutput {
+        code: output,
+        map,
+        diagnostics,
+        stats: ctx.stats,
+        plugin_context,
+        root: cleaned_root,
+    })
+}
+
+/// Source of the MDXContent function before the document-specific statements
+///
+/// Provider components (`provider`) sit between the defaults and the
+/// `components` prop.
+fn mdx_content_template(provider: bool) -> String {
+    format!(
+        r#"function MDXContent({{components: _cProp = {{}}, ...props}}) {{
+  const _components = Object.assign({{
+    h1: "h1", h2: "h2", h3: "h3", h4: "h4", h5: "h5", h6: "h6",
+    p: "p", a: "a", strong: "strong", em: "em", code: "code", pre: "pre",
+    blockquote: "blockquote", ul: "ul", ol: "ol", li: "li",
+    table: "table", thead: "thead", tbody: "tbody", tr: "tr", th: "th", td: "td",
+    hr: "hr", br: "br", img: "img", del: "del", div: "div", span: "span", sup: "sup", input: "input"
+  }}, {}_cProp);
+  const _taskListCtx = null;
+  const _handleTaskToggle = (e) => {{
+    const taskId = e.target.getAttribute('data-task-id');
+    if (taskId && _taskListCtx) {{
+      _taskListCtx.toggleTask(taskId, e.target.checked);
+    }}
+  }};
+}}"#,
+        if provider {
+            "_provideComponents(), "
+        } else {
+            ""
+        }
+    )
+}
+
+/// Names bound by an exported declaration
+fn declared_names(declaration: &Declaration<'_>) -> Vec<String> {
+    match declaration {
+        Declaration::VariableDeclaration(variable) => variable
+            .declarations
+            .iter()
+            .flat_map(|declarator| declarator.id.get_binding_identifiers())
+            .map(|identifier| identifier.name.to_string())
+            .collect(),
+        Declaration::FunctionDeclaration(function) => function
+            .id
+            .iter()
+            .map(|identifier| identifier.name.to_string())
+            .collect(),
+        Declaration::ClassDeclaration(class) => class
+            .id
+            .iter()
+            .map(|identifier| identifier.name.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Development runtime matching a production JSX runtime import source
+///
+/// `react/jsx-runtime` becomes `react/jsx-dev-runtime`; anything else is
+/// assumed to already export `jsxDEV` and is used as-is.
+fn dev_runtime_source(jsx_runtime: &str) -> String {
+    match jsx_runtime.strip_suffix("/jsx-runtime") {
+        Some(import_source) => format!("{}/jsx-dev-runtime", import_source),
+        None => jsx_runtime.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dev_runtime_source() {
+        assert_eq!(
+            dev_runtime_source("react/jsx-runtime"),
+            "react/jsx-dev-runtime"
+        );
+        assert_eq!(
+            dev_runtime_source("preact/jsx-runtime"),
+            "preact/jsx-dev-runtime"
+        );
+        assert_eq!(dev_runtime_source("my-runtime"), "my-runtime");
+    }
 }
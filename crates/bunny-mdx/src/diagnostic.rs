@@ -0,0 +1,136 @@
+//! Non-fatal compilation diagnostics
+//!
+//! Diagnostics describe problems that do not stop compilation (for example a
+//! link reference without a matching definition). They are collected while
+//! converting the AST, or reported by plugins through `PluginContext`, and
+//! returned on `MdxCompileResult::diagnostics`. With
+//! `MdxCompileOptions::warnings_as_errors` they fail compilation instead.
+
+use markdown::unist::{Point, Position};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Something is definitely wrong, but compilation could still continue
+    Error,
+    /// Something is probably wrong
+    Warning,
+    /// Informational note
+    Info,
+}
+
+/// A point in the MDX source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcePoint {
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Column number (1-indexed)
+    pub column: usize,
+    /// Byte offset (0-indexed)
+    pub offset: usize,
+}
+
+/// A range in the MDX source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    pub start: SourcePoint,
+    pub end: SourcePoint,
+}
+
+impl From<&Point> for SourcePoint {
+    fn from(point: &Point) -> Self {
+        Self {
+            line: point.line,
+            column: point.column,
+            offset: point.offset,
+        }
+    }
+}
+
+impl From<&Position> for SourceSpan {
+    fn from(position: &Position) -> Self {
+        Self {
+            start: SourcePoint::from(&position.start),
+            end: SourcePoint::from(&position.end),
+        }
+    }
+}
+
+/// A non-fatal issue found during compilation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// How serious the issue is
+    pub severity: Severity,
+    /// Stable, machine-readable identifier (e.g. `unresolved-reference`)
+    pub code: String,
+    /// Human-readable description
+    pub message: String,
+    /// Where in the source the issue occurred
+    pub span: Option<SourceSpan>,
+    /// Name of the plugin that reported the issue (`None` for the compiler)
+    pub plugin: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a new diagnostic without location information
+    pub fn new(severity: Severity, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code: code.into(),
+            message: message.into(),
+            span: None,
+            plugin: None,
+        }
+    }
+
+    /// Create an error diagnostic
+    pub fn error(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    /// Create a warning diagnostic
+    pub fn warning(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, message)
+    }
+
+    /// Create an informational diagnostic
+    pub fn info(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Info, code, message)
+    }
+
+    /// Attach the source span from an mdast position (if present)
+    pub fn with_position(mut self, position: Option<&Position>) -> Self {
+        self.span = position.map(SourceSpan::from);
+        self
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+        if let Some(span) = &self.span {
+            write!(
+                f,
+                " (at line {}, column {})",
+                span.start.line, span.start.column
+            )?;
+        }
+        if let Some(plugin) = &self.plugin {
+            write!(f, " [{}]", plugin)?;
+        }
+        Ok(())
+    }
+}
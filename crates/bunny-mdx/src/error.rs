@@ -6,9 +6,133 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::diagnostic::{Diagnostic, SourcePoint, SourceSpan};
+use crate::frontmatter::FieldError;
+
+/// Stable identifier for a kind of error
+///
+/// Codes never change meaning, so tools can match on them (or link to docs)
+/// instead of parsing messages. Parse errors are `MDX00xx`, policy errors
+/// `MDX01xx` and internal errors `MDX09xx`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// A JSX tag is never closed (`<div>` without `</div>`)
+    #[serde(rename = "MDX0001")]
+    UnclosedTag,
+    /// A closing tag doesn't match the open tag (`<a></b>`)
+    #[serde(rename = "MDX0002")]
+    MismatchedClosingTag,
+    /// A closing tag has attributes or a misplaced slash (`</a b>`, `</a/>`)
+    #[serde(rename = "MDX0003")]
+    InvalidClosingTag,
+    /// The document ends inside a tag or expression (`<a`, `{1 +`)
+    #[serde(rename = "MDX0004")]
+    UnexpectedEndOfFile,
+    /// A character that can't appear at that point of a JSX tag
+    #[serde(rename = "MDX0005")]
+    UnexpectedCharacter,
+    /// A JSX tag or expression continues on a line outside its block quote or
+    /// list item
+    #[serde(rename = "MDX0006")]
+    LazyLine,
+    /// An `{expression}` is not valid JavaScript
+    #[serde(rename = "MDX0007")]
+    InvalidExpression,
+    /// An `import`/`export` is not valid JavaScript
+    #[serde(rename = "MDX0008")]
+    InvalidEsm,
+    /// An export MDX can't compile
+    #[serde(rename = "MDX0009")]
+    InvalidExport,
+    /// YAML or TOML frontmatter that doesn't parse
+    #[serde(rename = "MDX0010")]
+    InvalidFrontmatter,
+    /// Any other syntax error
+    #[serde(rename = "MDX0011")]
+    Syntax,
+    /// mdast JSON that isn't a valid tree
+    #[serde(rename = "MDX0012")]
+    InvalidMdast,
+    /// A construct rejected by safe mode
+    #[serde(rename = "MDX0101")]
+    UnsafeConstruct,
+    /// A URL rejected by the URL policy
+    #[serde(rename = "MDX0102")]
+    DisallowedUrl,
+    /// Diagnostics promoted by `warnings_as_errors`
+    #[serde(rename = "MDX0103")]
+    PromotedDiagnostics,
+    /// A JSX component without an HTML template, with
+    /// `UnknownComponents::Error`
+    #[serde(rename = "MDX0104")]
+    UnknownComponent,
+    /// Frontmatter that doesn't match `MdxCompileOptions::frontmatter_schema`
+    #[serde(rename = "MDX0105")]
+    FrontmatterSchema,
+    /// The parsed document could not be converted (a bug or a failing plugin)
+    #[serde(rename = "MDX0901")]
+    Conversion,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnclosedTag => "MDX0001",
+            ErrorCode::MismatchedClosingTag => "MDX0002",
+            ErrorCode::InvalidClosingTag => "MDX0003",
+            ErrorCode::UnexpectedEndOfFile => "MDX0004",
+            ErrorCode::UnexpectedCharacter => "MDX0005",
+            ErrorCode::LazyLine => "MDX0006",
+            ErrorCode::InvalidExpression => "MDX0007",
+            ErrorCode::InvalidEsm => "MDX0008",
+            ErrorCode::InvalidExport => "MDX0009",
+            ErrorCode::InvalidFrontmatter => "MDX0010",
+            ErrorCode::Syntax => "MDX0011",
+            ErrorCode::InvalidMdast => "MDX0012",
+            ErrorCode::UnsafeConstruct => "MDX0101",
+            ErrorCode::DisallowedUrl => "MDX0102",
+            ErrorCode::PromotedDiagnostics => "MDX0103",
+            ErrorCode::UnknownComponent => "MDX0104",
+            ErrorCode::FrontmatterSchema => "MDX0105",
+            ErrorCode::Conversion => "MDX0901",
+        }
+    }
+
+    /// Code for a markdown-rs message, from its `source` and `rule_id`
+    fn from_markdown_message(message: &markdown::message::Message) -> Self {
+        match (message.source.as_str(), message.rule_id.as_str()) {
+            ("bunny-mdx", "expression") => ErrorCode::InvalidExpression,
+            ("bunny-mdx", "esm") => ErrorCode::InvalidEsm,
+            (_, "end-tag-mismatch") if message.reason.starts_with("Unexpected closing tag") => {
+                ErrorCode::MismatchedClosingTag
+            }
+            (_, "end-tag-mismatch") => ErrorCode::UnclosedTag,
+            (
+                _,
+                "unexpected-attribute"
+                | "unexpected-closing-slash"
+                | "unexpected-self-closing-slash",
+            ) => ErrorCode::InvalidClosingTag,
+            (_, "unexpected-eof") => ErrorCode::UnexpectedEndOfFile,
+            (_, "unexpected-character") => ErrorCode::UnexpectedCharacter,
+            (_, "unexpected-lazy") => ErrorCode::LazyLine,
+            _ => ErrorCode::Syntax,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// MDX compilation error with enhanced context
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MdxError {
+    /// Stable error code (see [`ErrorCode`])
+    pub code: Option<ErrorCode>,
     /// The error message
     pub message: String,
     /// Optional file path where the error occurred
@@ -17,6 +141,8 @@ pub struct MdxError {
     pub line: Option<usize>,
     /// Column number (1-indexed)
     pub column: Option<usize>,
+    /// Location in the source, with byte offsets
+    pub span: Option<SourceSpan>,
     /// The source code context (lines around the error)
     pub context: Option<String>,
     /// Helpful suggestion to fix the error
@@ -27,10 +153,12 @@ impl MdxError {
     /// Create a new MDX error
     pub fn new(message: impl Into<String>) -> Self {
         Self {
+            code: None,
             message: message.into(),
             file: None,
             line: None,
             column: None,
+            span: None,
             context: None,
             suggestion: None,
         }
@@ -49,6 +177,20 @@ impl MdxError {
         self
     }
 
+    /// Add the source span (also sets the line and column)
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.line = Some(span.start.line);
+        self.column = Some(span.start.column);
+        self.span = Some(span);
+        self
+    }
+
+    /// Add an error code
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
     /// Add source code context
     pub fn with_context(mut self, context: impl Into<String>) -> Self {
         self.context = Some(context.into());
@@ -66,6 +208,7 @@ impl MdxError {
         Self::new(message)
             .with_location(line, column)
             .with_context(context)
+            .with_code(ErrorCode::InvalidEsm)
             .with_suggestion(
                 "Check your import/export syntax. Ensure all statements are valid ES modules.",
             )
@@ -74,20 +217,130 @@ impl MdxError {
     /// Create an invalid export error
     pub fn invalid_export(code: &str) -> Self {
         Self::new(format!("Invalid export statement: {}", code))
+            .with_code(ErrorCode::InvalidExport)
             .with_suggestion("Only named exports, re-exports, and default exports are allowed in MDX. Remove or fix the export statement.")
     }
 
+    /// Create an error for a construct rejected in safe mode
+    pub fn unsafe_construct(construct: impl Into<String>) -> Self {
+        Self::new(format!("{} is not allowed in safe mode", construct.into()))
+            .with_code(ErrorCode::UnsafeConstruct)
+            .with_suggestion(
+                "Safe mode compiles untrusted content. Remove the construct, or allow it in `SafeMode`.",
+            )
+    }
+
+    /// Create an error for a URL whose scheme is not allowed
+    pub fn disallowed_url(url: &str, scheme: &str, allowed_schemes: &[String]) -> Self {
+        Self::new(format!(
+            "URL `{}` uses a disallowed scheme `{}:`",
+            url, scheme
+        ))
+        .with_code(ErrorCode::DisallowedUrl)
+        .with_suggestion(format!(
+            "Use a relative URL or one of the allowed schemes: {}.",
+            allowed_schemes.join(", ")
+        ))
+    }
+
+    /// Create an error for frontmatter fields that don't match the schema
+    pub fn frontmatter_mismatch(errors: &[FieldError]) -> Self {
+        let message = match errors {
+            [error] if error.path.is_empty() => {
+                format!("Frontmatter doesn't match the schema: {}", error.message)
+            }
+            [error] => format!(
+                "Frontmatter field `{}` doesn't match the schema: {}",
+                error.path, error.message
+            ),
+            _ => {
+                let mut message = format!("{} frontmatter errors:", errors.len());
+                for error in errors {
+                    message.push_str(&format!("\n  {}", error));
+                }
+                message
+            }
+        };
+        Self::new(message)
+            .with_code(ErrorCode::FrontmatterSchema)
+            .with_suggestion(
+                "Fix the frontmatter, or change `MdxCompileOptions::frontmatter_schema`.",
+            )
+    }
+
+    /// Create an error for diagnostics promoted by `warnings_as_errors`
+    ///
+    /// The location is that of the first diagnostic with a span.
+    pub fn promoted_diagnostics(diagnostics: &[Diagnostic]) -> Self {
+        let message = match diagnostics {
+            [diagnostic] => format!("Diagnostic treated as an error: {}", diagnostic),
+            _ => {
+                let mut message = format!("{} diagnostics treated as errors:", diagnostics.len());
+                for diagnostic in diagnostics {
+                    message.push_str(&format!("\n  {}", diagnostic));
+                }
+                message
+            }
+        };
+        let err = Self::new(message)
+            .with_code(ErrorCode::PromotedDiagnostics)
+            .with_suggestion(
+                "Fix the reported issues, or turn off `warnings_as_errors` to get them as diagnostics.",
+            );
+        match diagnostics.iter().find_map(|diagnostic| diagnostic.span) {
+            Some(span) => err.with_span(span),
+            None => err,
+        }
+    }
+
+    /// Create an error for a component the HTML backend can't render
+    pub fn unknown_component(name: &str) -> Self {
+        Self::new(format!("No HTML template for component `<{}>`", name))
+            .with_code(ErrorCode::UnknownComponent)
+            .with_suggestion(format!(
+                "Register a template with `HtmlOptions::with_component(\"{}\", ...)`, or use another `UnknownComponents` policy.",
+                name
+            ))
+    }
+
     /// Create a parsing error
     pub fn parse_error(message: String) -> Self {
         Self::new(format!("Failed to parse MDX: {}", message))
+            .with_code(ErrorCode::Syntax)
             .with_suggestion("Check your MDX syntax. Ensure all JSX tags are properly closed and expressions are valid.")
     }
 
+    /// Create a parsing error from a markdown-rs message, keeping its
+    /// location in `source`
+    pub fn from_markdown_message(message: &markdown::message::Message, source: &str) -> Self {
+        let code = ErrorCode::from_markdown_message(message);
+        let mut err = Self::parse_error(message.reason.clone()).with_code(code);
+        let span = match message.place.as_deref() {
+            Some(markdown::message::Place::Point(point)) => {
+                let point = SourcePoint::from(point);
+                Some(SourceSpan {
+                    start: point,
+                    end: point,
+                })
+            }
+            Some(markdown::message::Place::Position(position)) => Some(SourceSpan::from(position)),
+            None => None,
+        };
+        if let Some(span) = span {
+            err =
+                err.with_span(span)
+                    .with_context(Self::extract_context(source, span.start.line, 1));
+        }
+        err
+    }
+
     /// Create a conversion error
     pub fn conversion_error(message: String) -> Self {
-        Self::new(format!("Failed to convert MDX to JSX: {}", message)).with_suggestion(
-            "This is likely an internal error. Check that your MDX content is valid.",
-        )
+        Self::new(format!("Failed to convert MDX to JSX: {}", message))
+            .with_code(ErrorCode::Conversion)
+            .with_suggestion(
+                "This is likely an internal error. Check that your MDX content is valid.",
+            )
     }
 
     /// Extract context lines from source code
@@ -95,6 +348,8 @@ impl MdxError {
         let lines: Vec<&str> = source.lines().collect();
         let start = line.saturating_sub(context_lines + 1);
         let end = (line + context_lines).min(lines.len());
+        // Lines past the end of `source` have no context
+        let start = start.min(end);
 
         let mut context = String::new();
         for (i, line_text) in lines[start..end].iter().enumerate() {
@@ -106,10 +361,76 @@ impl MdxError {
     }
 }
 
+impl MdxError {
+    /// Render the error with a code frame of `source`, for CLI output and
+    /// server logs
+    ///
+    /// ```text
+    /// error[MDX0001]: Failed to parse MDX: Expected a closing tag for `<Callout>` (3:1)
+    ///  --> post.mdx:3:1
+    ///   |
+    /// 2 |
+    /// 3 | <Callout>
+    ///   | ^
+    ///   = help: Check your MDX syntax. …
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let mut out = match self.code {
+            Some(code) => format!("error[{}]: {}\n", code, self.message),
+            None => format!("error: {}\n", self.message),
+        };
+
+        let location = match (self.line, self.column) {
+            (Some(line), Some(column)) => Some(format!("{}:{}", line, column)),
+            _ => None,
+        };
+        match (&self.file, location) {
+            (Some(file), Some(location)) => out.push_str(&format!(" --> {}:{}\n", file, location)),
+            (Some(file), None) => out.push_str(&format!(" --> {}\n", file)),
+            (None, Some(location)) => out.push_str(&format!(" --> {}\n", location)),
+            (None, None) => {}
+        }
+
+        let span = self.span.or_else(|| {
+            // Errors located by line and column only get a one-character span
+            let (line, column) = (self.line?, self.column?);
+            let line_start: usize = source
+                .split_inclusive('\n')
+                .take(line.saturating_sub(1))
+                .map(str::len)
+                .sum();
+            let offset = source[line_start.min(source.len())..]
+                .char_indices()
+                .nth(column.saturating_sub(1))
+                .map_or(source.len(), |(index, _)| line_start + index);
+            let point = SourcePoint {
+                line,
+                column,
+                offset,
+            };
+            Some(SourceSpan {
+                start: point,
+                end: point,
+            })
+        });
+        if let Some(span) = span {
+            out.push_str(&crate::code_frame::render(source, &span));
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            out.push_str(&format!("  = help: {}\n", suggestion));
+        }
+        out
+    }
+}
+
 impl fmt::Display for MdxError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Error header
-        write!(f, "MDX Error: {}", self.message)?;
+        match self.code {
+            Some(code) => write!(f, "MDX Error [{}]: {}", code, self.message)?,
+            None => write!(f, "MDX Error: {}", self.message)?,
+        }
 
         // File location
         if let Some(ref file) = self.file {
@@ -191,6 +512,8 @@ mod tests {
         assert!(context.contains(">"));
         assert!(context.contains("3 | line 3"));
         assert!(context.contains("4 | line 4"));
+
+        assert_eq!(MdxError::extract_context("", 5, 1), "");
     }
 
     #[test]
@@ -213,4 +536,60 @@ mod tests {
         assert!(err.message.contains("Invalid export statement"));
         assert!(err.suggestion.is_some());
     }
+
+    #[test]
+    fn test_render_with_code_frame() {
+        let source = "# Title\n\nSome <b>text";
+        let start = source.find("<b>").unwrap();
+        let point = |offset| SourcePoint {
+            line: 3,
+            column: offset - 8,
+            offset,
+        };
+        let err = MdxError::new("Expected a closing tag for `<b>`")
+            .with_code(ErrorCode::UnclosedTag)
+            .with_file("post.mdx")
+            .with_span(SourceSpan {
+                start: point(start),
+                end: point(start + 3),
+            })
+            .with_suggestion("Close the tag");
+
+        assert_eq!((err.line, err.column), (Some(3), Some(6)));
+        assert!(err
+            .to_string()
+            .starts_with("MDX Error [MDX0001]: Expected a closing tag"));
+        assert_eq!(
+            err.render(source),
+            "error[MDX0001]: Expected a closing tag for `<b>`\n \
+             --> post.mdx:3:6\n  \
+             |\n\
+             2 |\n\
+             3 | Some <b>text\n  \
+             |      ^^^\n  \
+             = help: Close the tag\n"
+        );
+
+        // Errors with only a line and column point at one character
+        let err = MdxError::new("Oops").with_location(1, 3);
+        assert_eq!(
+            err.render("# Title"),
+            "error: Oops\n --> 1:3\n  |\n1 | # Title\n  |   ^\n"
+        );
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        assert_eq!(ErrorCode::UnclosedTag.to_string(), "MDX0001");
+        assert_eq!(
+            serde_json::to_string(&ErrorCode::InvalidExpression).unwrap(),
+            "\"MDX0007\""
+        );
+        let code: ErrorCode = serde_json::from_str("\"MDX0102\"").unwrap();
+        assert_eq!(code, ErrorCode::DisallowedUrl);
+        assert_eq!(
+            MdxError::parse_error("x".to_string()).code,
+            Some(ErrorCode::Syntax)
+        );
+    }
 }
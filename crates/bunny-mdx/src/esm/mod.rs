@@ -6,5 +6,8 @@
 mod parser;
 mod validator;
 
-pub use parser::{extract_imported_names, get_default_export_name, has_named_exports, is_reexport};
+pub use parser::{
+    extract_imported_names, get_default_export_expression, get_default_export_name,
+    has_named_exports, is_reexport, split_statements,
+};
 pub use validator::validate_esm_syntax;
@@ -1,5 +1,56 @@
 //! ESM statement parsing and classification
 
+use oxc_allocator::Allocator;
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType};
+
+/// Splits an ESM block into its top-level statements
+///
+/// markdown-rs combines consecutive ESM lines into a single node, so a block
+/// may hold `import Layout from './layout'` followed by `export default Layout`.
+/// Each statement has to be classified on its own. If the block does not
+/// parse, it is returned whole (validation reports the error separately).
+pub fn split_statements(code: &str) -> Vec<String> {
+    let allocator = Allocator::default();
+    let ret = Parser::new(&allocator, code, SourceType::mjs()).parse();
+
+    if !ret.errors.is_empty() || ret.program.body.is_empty() {
+        return vec![code.trim().to_string()];
+    }
+
+    ret.program
+        .body
+        .iter()
+        .map(|statement| {
+            let span = statement.span();
+            code[span.start as usize..span.end as usize]
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Extracts the expression of an `export default` statement
+///
+/// # Examples
+/// - `export default Layout` → Some("Layout")
+/// - `export default function Layout({children}) {...}` → Some("function Layout({children}) {...}")
+/// - `export const x = 1` → None
+pub fn get_default_export_expression(code: &str) -> Option<String> {
+    let expression = code
+        .trim()
+        .strip_prefix("export default ")?
+        .trim()
+        .trim_end_matches(';')
+        .trim_end();
+
+    if expression.is_empty() {
+        None
+    } else {
+        Some(expression.to_string())
+    }
+}
+
 /// Checks if an ESM block contains named exports
 ///
 /// # Examples
@@ -182,6 +233,33 @@ mod tests {
         assert_eq!(get_default_export_name("export const x = 1"), None);
     }
 
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("import Layout from './layout'\nexport default Layout;"),
+            vec!["import Layout from './layout'", "export default Layout;"]
+        );
+        assert_eq!(
+            split_statements("export const meta = {\n  title: 'x'\n}"),
+            vec!["export const meta = {\n  title: 'x'\n}"]
+        );
+        // Unparseable blocks are kept whole
+        assert_eq!(split_statements("import { from"), vec!["import { from"]);
+    }
+
+    #[test]
+    fn test_get_default_export_expression() {
+        assert_eq!(
+            get_default_export_expression("export default Layout;"),
+            Some("Layout".to_string())
+        );
+        assert_eq!(
+            get_default_export_expression("export default function Layout(props) {}"),
+            Some("function Layout(props) {}".to_string())
+        );
+        assert_eq!(get_default_export_expression("export const x = 1"), None);
+    }
+
     #[test]
     fn test_extract_imported_names() {
         // Default import
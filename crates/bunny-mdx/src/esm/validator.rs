@@ -1,6 +1,5 @@
 //! ESM syntax validation using OXC parser
 
-use crate::utils::offset_to_line_col;
 use markdown::MdxSignal;
 use oxc_allocator::Allocator;
 use oxc_parser::{Parser, ParserReturn};
@@ -28,34 +27,19 @@ pub fn validate_esm_syntax(code: &str) -> MdxSignal {
         let error = &errors[0];
         let message = format!("Invalid ESM syntax: {}", error.message);
 
-        // Extract line and column from error
-        let (line, column, context) = if let Some(labels) = &error.labels {
-            if let Some(label) = labels.first() {
-                // Calculate line/column from byte offset
-                let offset = label.offset();
-                let (line, col) = offset_to_line_col(code, offset);
+        // markdown-rs wants the offset relative to the ESM block and turns
+        // it into a place in the document
+        let offset = error
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.first())
+            .map_or(0, |label| label.offset());
 
-                // Extract context (the line containing the error)
-                let context = code
-                    .lines()
-                    .nth(line.saturating_sub(1))
-                    .unwrap_or("")
-                    .to_string();
-
-                (line, col, context)
-            } else {
-                (1, 1, String::new())
-            }
-        } else {
-            (1, 1, String::new())
-        };
-
-        // MdxSignal::Error(message, line, reason, place)
         MdxSignal::Error(
             message,
-            line,
-            Box::new(format!("at line {}, column {}", line, column)),
-            Box::new(context),
+            offset,
+            Box::new("bunny-mdx".to_string()),
+            Box::new("esm".to_string()),
         )
     }
 }
@@ -79,6 +63,10 @@ mod tests {
     #[test]
     fn test_invalid_syntax() {
         let code = "import { from";
-        assert!(matches!(validate_esm_syntax(code), MdxSignal::Error(..)));
+        let MdxSignal::Error(_, offset, source, rule_id) = validate_esm_syntax(code) else {
+            panic!("expected an error");
+        };
+        assert!(offset <= code.len());
+        assert_eq!((source.as_str(), rule_id.as_str()), ("bunny-mdx", "esm"));
     }
 }
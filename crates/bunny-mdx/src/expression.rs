@@ -0,0 +1,216 @@
+//! MDX expression validation using OXC parser
+//!
+//! Expressions in prose (`{1 + 1}`), attribute values (`a={b}`) and spread
+//! attributes (`{...props}`) end up in the compiled output as-is, so they are
+//! checked while the markdown is parsed. A typo like `{foo(}` then fails
+//! compilation with a line and column instead of breaking at runtime.
+
+use markdown::{MdxExpressionKind, MdxSignal};
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{Expression, ObjectPropertyKind, Statement};
+use oxc_parser::{Parser, ParserReturn};
+use oxc_span::{GetSpan, SourceType};
+
+/// Namespace of messages reported to markdown-rs
+const MESSAGE_SOURCE: &str = "bunny-mdx";
+
+/// Validates an MDX expression
+///
+/// This function is called by the markdown parser for every expression found
+/// in MDX, with the code between the braces. Prose expressions may be empty
+/// or contain only comments (`{/* note */}`); attribute values may not.
+pub fn validate_expression_syntax(code: &str, kind: &MdxExpressionKind) -> MdxSignal {
+    match kind {
+        MdxExpressionKind::Expression if is_comment_only(code) => MdxSignal::Ok,
+        // `## Title {#custom-id}` (see `heading_id`)
+        MdxExpressionKind::Expression if heading_id(code).is_some() => MdxSignal::Ok,
+        MdxExpressionKind::Expression => parse_wrapped(code, "(", "\n)", false),
+        MdxExpressionKind::AttributeValueExpression if is_comment_only(code) => error(
+            "Unexpected empty expression, expected a value between braces (such as `a={b}`)"
+                .to_string(),
+            0,
+        ),
+        MdxExpressionKind::AttributeValueExpression => parse_wrapped(code, "(", "\n)", false),
+        MdxExpressionKind::AttributeExpression => parse_wrapped(code, "({", "\n})", true),
+    }
+}
+
+/// Whether an expression has no code, only whitespace and comments
+pub(crate) fn is_comment_only(code: &str) -> bool {
+    let allocator = Allocator::default();
+    let ParserReturn {
+        program, errors, ..
+    } = Parser::new(&allocator, code, SourceType::mjs()).parse();
+    errors.is_empty() && program.body.is_empty() && program.directives.is_empty()
+}
+
+/// The id of a `{#custom-id}` heading suffix, which is not JavaScript
+///
+/// Ids are letters, digits, `-` and `_`.
+pub(crate) fn heading_id(code: &str) -> Option<&str> {
+    let id = code.trim().strip_prefix('#')?;
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(id)
+}
+
+/// Parse `code` between `prefix` and `suffix` as a single expression
+///
+/// The suffix starts on a new line so a trailing line comment can't swallow
+/// it. With `spread`, the expression must be an object with a single spread
+/// (`({...props})`).
+fn parse_wrapped(code: &str, prefix: &str, suffix: &str, spread: bool) -> MdxSignal {
+    let allocator = Allocator::default();
+    let wrapped = format!("{}{}{}", prefix, code, suffix);
+    let source_type = SourceType::mjs().with_jsx(true);
+    let ParserReturn {
+        program, errors, ..
+    } = Parser::new(&allocator, &wrapped, source_type).parse();
+
+    // Offset into `code` of an offset into `wrapped`
+    let relative = |offset: usize| offset.saturating_sub(prefix.len()).min(code.len());
+
+    if let Some(diagnostic) = errors.first() {
+        let start = diagnostic
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.first())
+            .map_or(0, |label| label.offset());
+        let message = format!(
+            "Could not parse expression with OXC: {}",
+            diagnostic.message
+        );
+
+        // The expression may continue after a `}` markdown-rs stopped at (as
+        // in `{"}"}` or `{a(() => {})}`): let it retry with more text
+        if start >= prefix.len() + code.len() || diagnostic.message.starts_with("Unterminated") {
+            return MdxSignal::Eof(
+                message,
+                Box::new(MESSAGE_SOURCE.to_string()),
+                Box::new("expression".to_string()),
+            );
+        }
+        return error(message, relative(start));
+    }
+
+    let expression = match program.body.as_slice() {
+        [Statement::ExpressionStatement(statement)] => &statement.expression,
+        [_, extra, ..] => {
+            return error(
+                "Unexpected content after expression".to_string(),
+                relative(extra.span().start as usize),
+            )
+        }
+        _ => return error("Expected an expression".to_string(), 0),
+    };
+
+    if spread {
+        let Expression::ObjectExpression(object) = expression.without_parentheses() else {
+            return error("Expected a spread (such as `{...x}`)".to_string(), 0);
+        };
+        match object.properties.as_slice() {
+            [ObjectPropertyKind::SpreadProperty(_)] => {}
+            [] => return error("Expected a spread (such as `{...x}`)".to_string(), 0),
+            [ObjectPropertyKind::SpreadProperty(_), extra, ..] | [extra, ..] => {
+                return error(
+                "Unexpected prop in spread (such as `{x}`): only a single spread is supported (such as `{...x}`)"
+                    .to_string(),
+                    relative(extra.span().start as usize),
+                )
+            }
+        }
+    }
+
+    MdxSignal::Ok
+}
+
+/// A syntax error at `offset` bytes into the expression
+fn error(message: String, offset: usize) -> MdxSignal {
+    MdxSignal::Error(
+        message,
+        offset,
+        Box::new(MESSAGE_SOURCE.to_string()),
+        Box::new("expression".to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_ok(code: &str, kind: MdxExpressionKind) -> bool {
+        matches!(validate_expression_syntax(code, &kind), MdxSignal::Ok)
+    }
+
+    #[test]
+    fn test_valid_expressions() {
+        assert!(is_ok("1 + 1", MdxExpressionKind::Expression));
+        assert!(is_ok(
+            " props.items.map((item) => <li>{item}</li>) ",
+            MdxExpressionKind::Expression
+        ));
+        assert!(is_ok(
+            "a // trailing comment",
+            MdxExpressionKind::Expression
+        ));
+        assert!(is_ok("{a: 1}", MdxExpressionKind::AttributeValueExpression));
+        assert!(is_ok("...props", MdxExpressionKind::AttributeExpression));
+    }
+
+    #[test]
+    fn test_comment_only_expressions() {
+        assert!(is_ok("", MdxExpressionKind::Expression));
+        assert!(is_ok(" /* note */ ", MdxExpressionKind::Expression));
+        assert!(is_ok("// note", MdxExpressionKind::Expression));
+        assert!(!is_ok(
+            "/* note */",
+            MdxExpressionKind::AttributeValueExpression
+        ));
+
+        assert!(is_comment_only("/* a */ // b"));
+        assert!(!is_comment_only("/* a */ b"));
+    }
+
+    #[test]
+    fn test_heading_id_expressions() {
+        assert!(is_ok("#custom-id", MdxExpressionKind::Expression));
+        assert_eq!(heading_id(" #über_1 "), Some("über_1"));
+        assert_eq!(heading_id("#"), None);
+        assert_eq!(heading_id("#a b"), None);
+        assert!(!is_ok("#a b", MdxExpressionKind::Expression));
+        assert!(!is_ok("#id", MdxExpressionKind::AttributeValueExpression));
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        let MdxSignal::Error(message, offset, ..) =
+            validate_expression_syntax("a b", &MdxExpressionKind::Expression)
+        else {
+            panic!("expected an error");
+        };
+        assert!(message.starts_with("Could not parse expression with OXC"));
+        assert_eq!(offset, 2);
+
+        assert!(matches!(
+            validate_expression_syntax("a); b(", &MdxExpressionKind::Expression),
+            MdxSignal::Error(_, 4, ..)
+        ));
+        assert!(!is_ok("...a, b", MdxExpressionKind::AttributeExpression));
+        assert!(!is_ok("a", MdxExpressionKind::AttributeExpression));
+    }
+
+    #[test]
+    fn test_unfinished_expressions_ask_for_more_text() {
+        // `{"}"}`: markdown-rs first stops at the `}` inside the string
+        assert!(matches!(
+            validate_expression_syntax("\"", &MdxExpressionKind::Expression),
+            MdxSignal::Eof(..)
+        ));
+        assert!(matches!(
+            validate_expression_syntax("foo(", &MdxExpressionKind::Expression),
+            MdxSignal::Eof(..)
+        ));
+    }
+}
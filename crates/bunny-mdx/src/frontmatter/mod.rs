@@ -1,10 +1,14 @@
 //! Frontmatter parsing and types
 //!
 //! Handles extraction and parsing of YAML and TOML frontmatter blocks
-//! from MDX documents during compilation.
+//! from MDX documents during compilation, and their validation against a
+//! schema.
 
 mod parser;
+mod schema;
 mod types;
 
 pub use parser::extract_frontmatter;
+pub(crate) use schema::check_frontmatter;
+pub use schema::{FieldError, FrontmatterSchema};
 pub use types::{FrontmatterData, FrontmatterFormat};
@@ -72,13 +72,9 @@ pub fn extract_frontmatter(root: &Node) -> Result<(Node, Option<FrontmatterData>
                 let data: toml::Value =
                     toml::from_str(&toml_node.value).context("Failed to parse TOML frontmatter")?;
 
-                // Convert TOML value to JSON value
-                let json_data =
-                    serde_json::to_value(&data).context("Failed to convert TOML to JSON")?;
-
                 frontmatter = Some(FrontmatterData::new(
                     FrontmatterFormat::Toml,
-                    json_data,
+                    toml_to_json(data),
                     toml_node.value.clone(),
                 ));
             }
@@ -97,3 +93,21 @@ pub fn extract_frontmatter(root: &Node) -> Result<(Node, Option<FrontmatterData>
 
     Ok((cleaned_root, frontmatter))
 }
+
+/// Convert a TOML value to JSON, with dates and times as RFC 3339 strings
+fn toml_to_json(value: toml::Value) -> JsonValue {
+    match value {
+        toml::Value::String(string) => JsonValue::String(string),
+        toml::Value::Integer(integer) => integer.into(),
+        toml::Value::Float(float) => float.into(),
+        toml::Value::Boolean(boolean) => boolean.into(),
+        toml::Value::Datetime(datetime) => JsonValue::String(datetime.to_string()),
+        toml::Value::Array(array) => array.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => JsonValue::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
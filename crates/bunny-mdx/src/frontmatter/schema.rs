@@ -0,0 +1,844 @@
+//! Frontmatter validation (`MdxCompileOptions::frontmatter_schema`)
+//!
+//! Frontmatter is checked against a JSON Schema or a Rust type before
+//! codegen. Errors name the field (`tags[0]`, `author.name`) and point to its
+//! line in the frontmatter block.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use markdown::unist::Position;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+
+use super::FrontmatterData;
+use crate::error::MdxError;
+
+/// What frontmatter must look like
+///
+/// Documents without frontmatter are validated as an empty object, so
+/// required fields are enforced for them too.
+///
+/// ```
+/// use bunny_mdx::{compile, FrontmatterSchema, MdxCompileOptions};
+/// use serde_json::json;
+///
+/// let schema = FrontmatterSchema::json(json!({
+///     "type": "object",
+///     "required": ["title", "date"],
+///     "properties": {
+///         "title": {"type": "string"},
+///         "date": {"type": "string", "format": "date"},
+///         "tags": {"type": "array", "items": {"type": "string"}}
+///     }
+/// }))?;
+/// let options = MdxCompileOptions::new().with_frontmatter_schema(schema);
+///
+/// let mdx = "---\ntitle: Hello\ndate: 2024-01-15\ntags:\n  - rust\n  - 42\n---\n\n# Hello";
+/// let err = compile(mdx, options).unwrap_err();
+/// assert_eq!(
+///     err.message,
+///     "Frontmatter field `tags[1]` doesn't match the schema: expected string, found integer"
+/// );
+/// assert_eq!(err.line, Some(6));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct FrontmatterSchema(SchemaKind);
+
+#[derive(Clone)]
+enum SchemaKind {
+    Json(Arc<JsonSchema>),
+    Type {
+        name: &'static str,
+        check: fn(&JsonValue) -> Option<FieldError>,
+    },
+}
+
+/// A frontmatter field that doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Path of the field, like `tags[0]` or `author.name` (empty for the
+    /// frontmatter itself)
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "`{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+impl fmt::Debug for FrontmatterSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            SchemaKind::Json(json) => f.debug_tuple("Json").field(&json.schema).finish(),
+            SchemaKind::Type { name, .. } => f.debug_tuple("Type").field(name).finish(),
+        }
+    }
+}
+
+impl FrontmatterSchema {
+    /// Validate against a JSON Schema
+    ///
+    /// These keywords are checked: `type`, `enum`, `const`, `properties`,
+    /// `required`, `additionalProperties`, `items`, `minItems`, `maxItems`,
+    /// `minLength`, `maxLength`, `pattern`, `minimum`, `maximum`, `anyOf` and
+    /// `format` (`date`, `date-time`, `email` and `uri`). Other keywords are
+    /// ignored.
+    ///
+    /// Fails when a `pattern` isn't a regular expression the `regex` crate
+    /// supports (it has no lookarounds or backreferences).
+    pub fn json(schema: JsonValue) -> Result<Self> {
+        let mut patterns = HashMap::new();
+        compile_patterns(&schema, "", &mut patterns)?;
+        Ok(Self(SchemaKind::Json(Arc::new(JsonSchema {
+            schema,
+            patterns,
+        }))))
+    }
+
+    /// Validate by deserializing into `T` (use `#[serde(deny_unknown_fields)]`
+    /// to reject unknown fields)
+    ///
+    /// Only the first error is reported, as serde stops there.
+    pub fn of<T: DeserializeOwned>() -> Self {
+        Self(SchemaKind::Type {
+            name: std::any::type_name::<T>(),
+            check: deserialize_as::<T>,
+        })
+    }
+
+    /// Identifies the schema for [`crate::MdxCompileOptions::cache_key`]
+    pub(crate) fn cache_key(&self) -> String {
+        match &self.0 {
+            SchemaKind::Json(json) => format!("json {}", json.schema),
+            SchemaKind::Type { name, .. } => format!("type {}", name),
+        }
+    }
+
+    /// Fields of `data` that don't match the schema
+    pub fn validate(&self, data: &JsonValue) -> Vec<FieldError> {
+        match &self.0 {
+            SchemaKind::Json(json) => {
+                let mut errors = Vec::new();
+                check(&json.schema, &json.patterns, data, "", &mut errors);
+                errors
+            }
+            SchemaKind::Type { check, .. } => check(data).into_iter().collect(),
+        }
+    }
+}
+
+/// Validate `frontmatter` (the block at `position`) against `schema`
+pub(crate) fn check_frontmatter(
+    schema: &FrontmatterSchema,
+    frontmatter: Option<&FrontmatterData>,
+    position: Option<&Position>,
+    source: Option<&str>,
+) -> Result<(), Box<MdxError>> {
+    let empty = JsonValue::Object(Default::default());
+    let data = frontmatter.map_or(&empty, |frontmatter| &frontmatter.data);
+    let errors = schema.validate(data);
+    let Some(first) = errors.first() else {
+        return Ok(());
+    };
+
+    let mut err = MdxError::frontmatter_mismatch(&errors);
+    if let (Some(frontmatter), Some(position)) = (frontmatter, position) {
+        // The block starts with its `---`/`+++` fence
+        let (line, column) = match field_line(&frontmatter.raw, &first.path) {
+            Some((line, indent)) => (position.start.line + 1 + line, indent + 1),
+            None => (position.start.line, position.start.column),
+        };
+        err = err.with_location(line, column);
+        if let Some(source) = source {
+            err = err.with_context(MdxError::extract_context(source, line, 1));
+        }
+    }
+    Err(Box::new(err))
+}
+
+fn deserialize_as<T: DeserializeOwned>(data: &JsonValue) -> Option<FieldError> {
+    // Deserialize from pretty JSON, whose error lines lead back to the field
+    let json = serde_json::to_string_pretty(data).ok()?;
+    let err = serde_json::from_str::<T>(&json).err()?;
+    let mut message = err.to_string();
+    if let Some((rest, _)) = message.rsplit_once(" at line ") {
+        message = rest.to_string();
+    }
+    let mut path = None;
+    locate_line(data, &mut 1, String::new(), err.line(), &mut path);
+    Some(FieldError {
+        path: path.unwrap_or_default(),
+        message,
+    })
+}
+
+/// Path of the deepest value on `target` in the pretty JSON of `value`
+///
+/// `line` is the line `value` starts on, and is moved to its last line.
+fn locate_line(
+    value: &JsonValue,
+    line: &mut usize,
+    path: String,
+    target: usize,
+    found: &mut Option<String>,
+) {
+    let start = *line;
+    match value {
+        JsonValue::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                *line += 1;
+                locate_line(item, line, format!("{}[{}]", path, index), target, found);
+            }
+            // Closing bracket
+            *line += 1;
+        }
+        JsonValue::Object(object) if !object.is_empty() => {
+            for (key, field) in object {
+                *line += 1;
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                locate_line(field, line, field_path, target, found);
+            }
+            *line += 1;
+        }
+        _ => {}
+    }
+    if found.is_none() && (start..=*line).contains(&target) {
+        *found = Some(path);
+    }
+}
+
+/// A JSON Schema with its `pattern`s compiled
+struct JsonSchema {
+    schema: JsonValue,
+    /// Compiled patterns, by source
+    patterns: HashMap<String, Regex>,
+}
+
+/// Compile the `pattern`s of `schema` (at `path` in the whole schema) and of
+/// its subschemas
+fn compile_patterns(
+    schema: &JsonValue,
+    path: &str,
+    patterns: &mut HashMap<String, Regex>,
+) -> Result<()> {
+    let JsonValue::Object(schema) = schema else {
+        return Ok(());
+    };
+    let at = |keyword: &str| {
+        if path.is_empty() {
+            keyword.to_string()
+        } else {
+            format!("{}.{}", path, keyword)
+        }
+    };
+
+    if let Some(pattern) = schema.get("pattern").and_then(JsonValue::as_str) {
+        if !patterns.contains_key(pattern) {
+            let regex = Regex::new(pattern).map_err(|err| {
+                anyhow!(
+                    "Invalid pattern `{}` at `{}` in the frontmatter schema: {}",
+                    pattern,
+                    at("pattern"),
+                    err
+                )
+            })?;
+            patterns.insert(pattern.to_string(), regex);
+        }
+    }
+    if let Some(JsonValue::Object(properties)) = schema.get("properties") {
+        for (key, property) in properties {
+            compile_patterns(property, &at(&format!("properties.{}", key)), patterns)?;
+        }
+    }
+    for keyword in ["items", "additionalProperties"] {
+        if let Some(subschema) = schema.get(keyword) {
+            compile_patterns(subschema, &at(keyword), patterns)?;
+        }
+    }
+    if let Some(JsonValue::Array(options)) = schema.get("anyOf") {
+        for (index, option) in options.iter().enumerate() {
+            compile_patterns(option, &format!("{}[{}]", at("anyOf"), index), patterns)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check `value` against `schema`, adding errors at `path`
+fn check(
+    schema: &JsonValue,
+    patterns: &HashMap<String, Regex>,
+    value: &JsonValue,
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    let schema = match schema {
+        JsonValue::Object(schema) => schema,
+        JsonValue::Bool(false) => return push(errors, path, "no value is allowed".to_string()),
+        _ => return,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            JsonValue::String(name) => vec![name.as_str()],
+            JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return push(
+                errors,
+                path,
+                format!(
+                    "expected {}, found {}",
+                    types.join(" or "),
+                    type_name(value)
+                ),
+            );
+        }
+    }
+    if let Some(JsonValue::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(JsonValue::to_string).collect();
+            push(
+                errors,
+                path,
+                format!("expected one of {}", allowed.join(", ")),
+            );
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            push(errors, path, format!("expected {}", expected));
+        }
+    }
+    if let Some(JsonValue::Array(options)) = schema.get("anyOf") {
+        let matches_any = options.iter().any(|option| {
+            let mut option_errors = Vec::new();
+            check(option, patterns, value, path, &mut option_errors);
+            option_errors.is_empty()
+        });
+        if !matches_any {
+            push(
+                errors,
+                path,
+                "doesn't match any of the allowed schemas".to_string(),
+            );
+        }
+    }
+
+    match value {
+        JsonValue::String(string) => {
+            let length = string.chars().count();
+            if let Some(min) = schema.get("minLength").and_then(JsonValue::as_u64) {
+                if (length as u64) < min {
+                    push(
+                        errors,
+                        path,
+                        format!("expected a length of at least {}", min),
+                    );
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(JsonValue::as_u64) {
+                if length as u64 > max {
+                    push(
+                        errors,
+                        path,
+                        format!("expected a length of at most {}", max),
+                    );
+                }
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(JsonValue::as_str) {
+                // Compiled by `FrontmatterSchema::json`
+                if patterns
+                    .get(pattern)
+                    .is_some_and(|regex| !regex.is_match(string))
+                {
+                    push(
+                        errors,
+                        path,
+                        format!("`{}` doesn't match `{}`", string, pattern),
+                    );
+                }
+            }
+            if let Some(format) = schema.get("format").and_then(JsonValue::as_str) {
+                if !matches_format(string, format) {
+                    push(
+                        errors,
+                        path,
+                        format!("`{}` is not a valid {}", string, format),
+                    );
+                }
+            }
+        }
+        JsonValue::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(JsonValue::as_f64) {
+                if number < min {
+                    push(errors, path, format!("expected at least {}", min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(JsonValue::as_f64) {
+                if number > max {
+                    push(errors, path, format!("expected at most {}", max));
+                }
+            }
+        }
+        JsonValue::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(JsonValue::as_u64) {
+                if (items.len() as u64) < min {
+                    push(errors, path, format!("expected at least {} item(s)", min));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(JsonValue::as_u64) {
+                if items.len() as u64 > max {
+                    push(errors, path, format!("expected at most {} item(s)", max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    let item_path = format!("{}[{}]", path, index);
+                    check(item_schema, patterns, item, &item_path, errors);
+                }
+            }
+        }
+        JsonValue::Object(object) => {
+            if let Some(JsonValue::Array(required)) = schema.get("required") {
+                for field in required.iter().filter_map(JsonValue::as_str) {
+                    if !object.contains_key(field) {
+                        push(errors, path, format!("missing field `{}`", field));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(JsonValue::as_object);
+            for (key, field) in object {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(field_schema) => check(field_schema, patterns, field, &field_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(JsonValue::Bool(false)) => {
+                            push(errors, path, format!("unknown field `{}`", key))
+                        }
+                        Some(extra_schema) => {
+                            check(extra_schema, patterns, field, &field_path, errors)
+                        }
+                        None => {}
+                    },
+                }
+            }
+        }
+        JsonValue::Bool(_) | JsonValue::Null => {}
+    }
+}
+
+fn push(errors: &mut Vec<FieldError>, path: &str, message: String) {
+    errors.push(FieldError {
+        path: path.to_string(),
+        message,
+    });
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "number" => value.is_number(),
+        name => type_name(value) == name,
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(number) if number.is_f64() => "number",
+        JsonValue::Number(_) => "integer",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn matches_format(value: &str, format: &str) -> bool {
+    static DATE_TIME: OnceLock<Regex> = OnceLock::new();
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    static URI: OnceLock<Regex> = OnceLock::new();
+    match format {
+        "date" => is_date(value),
+        "date-time" => {
+            let regex = DATE_TIME.get_or_init(|| {
+                Regex::new(r"^(\d{4}-\d{2}-\d{2})[Tt ]([01]\d|2[0-3]):[0-5]\d:([0-5]\d|60)(\.\d+)?([Zz]|[+-]([01]\d|2[0-3]):[0-5]\d)$")
+                    .expect("valid regex")
+            });
+            regex
+                .captures(value)
+                .is_some_and(|captures| is_date(&captures[1]))
+        }
+        "email" => EMAIL
+            .get_or_init(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").expect("valid regex"))
+            .is_match(value),
+        "uri" => URI
+            .get_or_init(|| Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:\S*$").expect("valid regex"))
+            .is_match(value),
+        // Unknown formats are annotations only
+        _ => true,
+    }
+}
+
+/// `YYYY-MM-DD`, with a day that exists in that month
+fn is_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// Line (0-based, in `raw`) and indentation of the field at `path`
+///
+/// Keys are found by scanning YAML (`key:`, nested by indentation and `- `
+/// items) and TOML (`key =`, `[table]`) lines. When part of the path can't be
+/// found (inline arrays and objects), the line of the closest parent is
+/// returned. `None` is the frontmatter itself.
+fn field_line(raw: &str, path: &str) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = raw.lines().collect();
+    let mut found: Option<(usize, usize)> = None;
+    let mut scope = Scope::default();
+
+    for segment in path_segments(path) {
+        let start = match found {
+            // The first key of an item is on the item's line
+            Some((line, _)) if scope.same_line => line,
+            Some((line, _)) => line + 1,
+            None => 0,
+        };
+        let next = match segment {
+            Segment::Key(key) => find_key(&lines[start..], key, scope),
+            Segment::Index(index) => find_item(&lines[start..], index, scope.indent),
+        };
+        let Some((offset, indent)) = next else {
+            break;
+        };
+        let line = start + offset;
+        found = Some((line, indent));
+        scope = match segment {
+            Segment::Index(_) => Scope {
+                indent: Some(indent),
+                same_line: true,
+                table: false,
+            },
+            // Keys of a TOML table follow its header, unindented
+            Segment::Key(_) if lines[line].trim_start().starts_with('[') => Scope {
+                indent: None,
+                same_line: false,
+                table: true,
+            },
+            Segment::Key(_) => Scope {
+                indent: Some(indent),
+                same_line: false,
+                table: false,
+            },
+        };
+    }
+    found
+}
+
+/// Where the children of a field are
+#[derive(Clone, Copy, Default)]
+struct Scope {
+    /// Children are indented deeper than this
+    indent: Option<usize>,
+    /// The first line is the parent's own (a `- key: value` item)
+    same_line: bool,
+    /// The parent is a TOML table, which ends at the next header
+    table: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// `tags[0].name` → `tags`, `0`, `name`
+fn path_segments(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let mut pieces = part.split('[');
+        if let Some(key) = pieces.next().filter(|key| !key.is_empty()) {
+            segments.push(Segment::Key(key));
+        }
+        for index in pieces.filter_map(|piece| piece.trim_end_matches(']').parse().ok()) {
+            segments.push(Segment::Index(index));
+        }
+    }
+    segments
+}
+
+/// Lines with content, with their index and indentation
+fn content_lines<'a>(lines: &'a [&'a str]) -> impl Iterator<Item = (usize, usize, &'a str)> {
+    lines.iter().enumerate().filter_map(|(index, line)| {
+        let trimmed = line.trim_start();
+        (!trimmed.is_empty() && !trimmed.starts_with('#'))
+            .then(|| (index, line.len() - trimmed.len(), trimmed))
+    })
+}
+
+fn find_key(lines: &[&str], key: &str, scope: Scope) -> Option<(usize, usize)> {
+    for (index, indent, trimmed) in content_lines(lines) {
+        let own_line = index == 0 && scope.same_line;
+        if scope.indent.is_some_and(|parent| indent <= parent) && !own_line {
+            // Past the parent's block
+            return None;
+        }
+        if scope.table && trimmed.starts_with('[') {
+            return None;
+        }
+        // Keys of items come after their `- ` markers
+        let mut rest = trimmed;
+        let mut key_indent = indent;
+        while let Some(after) = rest.strip_prefix("- ") {
+            let after_trimmed = after.trim_start();
+            key_indent += rest.len() - after_trimmed.len();
+            rest = after_trimmed;
+        }
+        if defines_key(rest, key) {
+            return Some((index, key_indent));
+        }
+    }
+    None
+}
+
+/// `key:` (YAML), `key =` (TOML) or a `[table.key]` header
+fn defines_key(rest: &str, key: &str) -> bool {
+    if let Some(header) = rest.strip_prefix('[') {
+        let header = header.trim_start_matches('[');
+        let header = header.split(']').next().unwrap_or_default();
+        return header.rsplit('.').next().map(str::trim) == Some(key);
+    }
+    let after = [
+        key.to_string(),
+        format!("\"{}\"", key),
+        format!("'{}'", key),
+    ]
+    .iter()
+    .find_map(|quoted| rest.strip_prefix(quoted.as_str()).map(str::to_string));
+    after.is_some_and(|after| {
+        let after = after.trim_start();
+        after.starts_with(':') || after.starts_with('=')
+    })
+}
+
+/// Line of the `index`th `- ` item of a YAML sequence
+fn find_item(lines: &[&str], index: usize, parent_indent: Option<usize>) -> Option<(usize, usize)> {
+    let mut item_indent = None;
+    let mut count = 0;
+    for (line, indent, trimmed) in content_lines(lines) {
+        let is_item = trimmed == "-" || trimmed.starts_with("- ");
+        // Sequences may sit at their key's indentation
+        let parent = parent_indent.unwrap_or_default();
+        if indent < parent || (indent == parent && !is_item) {
+            return None;
+        }
+        if !is_item || item_indent.is_some_and(|item_indent| indent != item_indent) {
+            continue;
+        }
+        item_indent = Some(indent);
+        if count == index {
+            return Some((line, indent));
+        }
+        count += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    fn content_schema() -> FrontmatterSchema {
+        FrontmatterSchema::json(json!({
+            "type": "object",
+            "required": ["title", "date"],
+            "additionalProperties": false,
+            "properties": {
+                "title": {"type": "string", "minLength": 1},
+                "date": {"type": "string", "format": "date"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "author": {
+                    "type": "object",
+                    "properties": {"email": {"type": "string", "format": "email"}}
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let data = json!({
+            "title": "",
+            "date": "2023-02-29",
+            "tags": ["a", 1],
+            "author": {"email": "nope"},
+            "draft": true
+        });
+        let errors: Vec<String> = content_schema()
+            .validate(&data)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "`author.email`: `nope` is not a valid email",
+                "`date`: `2023-02-29` is not a valid date",
+                "unknown field `draft`",
+                "`tags[1]`: expected string, found integer",
+                "`title`: expected a length of at least 1",
+            ]
+        );
+
+        let errors = content_schema().validate(&json!({"date": "2024-02-29"}));
+        assert_eq!(errors[0].message, "missing field `title`");
+        assert_eq!(errors.len(), 1);
+
+        assert!(matches_format("2024-01-15T10:30:00Z", "date-time"));
+        assert!(!matches_format("2024-01-15 25:00:00Z", "date-time"));
+        assert!(matches_format("https://example.com", "uri"));
+    }
+
+    #[test]
+    fn test_patterns() {
+        let schema = FrontmatterSchema::json(json!({
+            "properties": {
+                "slug": {"type": "string", "pattern": "^[a-z-]+$"},
+                "tags": {"items": {"pattern": "^[a-z]+$"}}
+            }
+        }))
+        .unwrap();
+        let errors = schema.validate(&json!({"slug": "Hello", "tags": ["ok", "No"]}));
+        assert_eq!(
+            errors,
+            [
+                FieldError {
+                    path: "slug".to_string(),
+                    message: "`Hello` doesn't match `^[a-z-]+$`".to_string(),
+                },
+                FieldError {
+                    path: "tags[1]".to_string(),
+                    message: "`No` doesn't match `^[a-z]+$`".to_string(),
+                },
+            ]
+        );
+
+        // Lookarounds are ECMA regex syntax the `regex` crate doesn't support
+        let err = FrontmatterSchema::json(json!({
+            "properties": {"tags": {"items": {"pattern": "^(?!draft)"}}}
+        }))
+        .unwrap_err();
+        assert!(err.to_string().starts_with(
+            "Invalid pattern `^(?!draft)` at `properties.tags.items.pattern` in the frontmatter schema"
+        ));
+    }
+
+    #[test]
+    fn test_rust_type() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Post {
+            title: String,
+            tags: Vec<String>,
+            author: Author,
+        }
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Author {
+            name: String,
+        }
+
+        let schema = FrontmatterSchema::of::<Post>();
+        let valid = json!({"title": "Hi", "tags": ["a"], "author": {"name": "Ann"}});
+        assert!(schema.validate(&valid).is_empty());
+
+        let errors =
+            schema.validate(&json!({"title": "Hi", "tags": ["a", 1], "author": {"name": "Ann"}}));
+        assert_eq!(errors[0].path, "tags[1]");
+        assert!(errors[0].message.starts_with("invalid type: integer `1`"));
+
+        let errors = schema.validate(&json!({"title": "Hi", "tags": [], "author": {}}));
+        assert_eq!(
+            errors,
+            [FieldError {
+                path: "author".to_string(),
+                message: "missing field `name`".to_string()
+            }]
+        );
+        let errors = schema.validate(&json!({}));
+        assert_eq!(errors[0].path, "");
+        assert!(format!("{:?}", schema).contains("Post"));
+    }
+
+    #[test]
+    fn test_field_lines() {
+        let yaml = "title: Hi\nauthor:\n  name: Ann\n  email: a@b.c\ntags:\n- one\n- two\nitems:\n  - name: a\n  - name: b\n    size: 2\nlist: [1, 2]";
+        for (path, line) in [
+            ("title", Some(0)),
+            ("author", Some(1)),
+            ("author.email", Some(3)),
+            ("tags[1]", Some(6)),
+            ("items[1].size", Some(10)),
+            ("items[0].name", Some(8)),
+            ("list[1]", Some(11)),
+            ("missing", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                field_line(yaml, path).map(|(line, _)| line),
+                line,
+                "{}",
+                path
+            );
+        }
+
+        let toml = "title = \"Hi\"\ntags = [\"a\", 1]\n\n[author]\nname = \"Ann\"";
+        assert_eq!(field_line(toml, "tags[1]"), Some((1, 0)));
+        assert_eq!(field_line(toml, "author.name"), Some((4, 0)));
+    }
+}
@@ -1,12 +1,13 @@
 //! Frontmatter data structures
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
 /// Frontmatter data extracted from MDX documents
 ///
 /// Frontmatter can be in YAML or TOML format and is parsed during
 /// MDX compilation for build-time access. This avoids runtime parsing overhead.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FrontmatterData {
     /// The format of the original frontmatter
     pub format: FrontmatterFormat,
@@ -17,7 +18,8 @@ pub struct FrontmatterData {
 }
 
 /// Format of the frontmatter block
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FrontmatterFormat {
     /// YAML format (---)
     Yaml,
@@ -7,12 +7,21 @@
 //! and returns all the extracted information (frontmatter, images, exports, etc.)
 //! in simple data structures.
 
+mod batch;
+pub mod code_frame;
 pub mod codegen;
+pub mod diagnostic;
 pub mod error;
 pub mod esm;
+pub mod expression;
 pub mod frontmatter;
+pub mod mdast;
 pub mod nodes;
 pub mod plugins;
+pub mod safe_mode;
+pub mod stats;
+pub mod text;
+pub mod url_policy;
 pub mod utils;
 
 // Legacy mdx module for gradual migration
@@ -22,17 +31,32 @@ pub mod mdx {
     pub use crate::codegen::{mdast_to_jsx, mdast_to_jsx_with_options};
     pub use crate::frontmatter::{extract_frontmatter, FrontmatterData, FrontmatterFormat};
     pub use crate::plugins::MdxPlugin;
+    use std::sync::Arc;
 
     // Re-export plugins module
     pub use crate::plugins;
 
     /// Configuration options for MDX processing
     pub struct MdxOptions {
-        pub plugins: Vec<Box<dyn MdxPlugin>>,
+        pub plugins: Vec<Arc<dyn MdxPlugin>>,
         pub jsx_runtime: String,
         pub output_format: crate::OutputFormat,
         /// Pre-extracted frontmatter (passed from compile() to avoid double extraction)
         pub frontmatter: Option<crate::FrontmatterData>,
+        /// Generate a source map alongside the code
+        pub source_map: bool,
+        /// Original MDX source (embedded in the source map, used for columns)
+        pub source: Option<String>,
+        /// Path of the MDX file (used as the source map `sources` entry)
+        pub filepath: Option<String>,
+        /// Emit `jsxDEV` calls with source locations
+        pub development: bool,
+        /// Module exporting `useMDXComponents` (merged into `_components`)
+        pub provider_import_source: Option<String>,
+        /// How raw HTML nodes are compiled
+        pub raw_html: crate::RawHtml,
+        /// Policy for `href`/`src` URLs
+        pub url_policy: Option<crate::UrlPolicy>,
     }
 
     impl Default for MdxOptions {
@@ -42,6 +66,13 @@ pub mod mdx {
                 jsx_runtime: "react/jsx-runtime".to_string(),
                 output_format: crate::OutputFormat::default(),
                 frontmatter: None,
+                source_map: false,
+                source: None,
+                filepath: None,
+                development: false,
+                provider_import_source: None,
+                raw_html: crate::RawHtml::default(),
+                url_policy: None,
             }
         }
     }
@@ -51,23 +82,40 @@ pub mod mdx {
             Self::default()
         }
 
-        pub fn with_plugin(mut self, plugin: Box<dyn MdxPlugin>) -> Self {
-            self.plugins.push(plugin);
+        pub fn with_plugin(mut self, plugin: impl MdxPlugin + 'static) -> Self {
+            self.plugins.push(Arc::new(plugin));
             self
         }
 
         pub fn with_default_plugins(self) -> Self {
-            self.with_plugin(Box::new(plugins::HeadingIdPlugin::default()))
-                .with_plugin(Box::new(plugins::ImageOptimizationPlugin::default()))
+            self.with_plugin(plugins::HeadingIdPlugin::default())
+                .with_plugin(plugins::ImageOptimizationPlugin::new())
         }
     }
 }
 
 // Re-export public types
-pub use codegen::{mdast_to_jsx, mdast_to_jsx_with_options};
-pub use error::MdxError;
-pub use frontmatter::{extract_frontmatter, FrontmatterData, FrontmatterFormat};
-pub use plugins::MdxPlugin;
+pub use batch::{compile_many, MdxInput};
+pub use codegen::{
+    mdast_to_jsx, mdast_to_jsx_with_options, ComponentProps, ComponentTemplate, HtmlOptions,
+    UnknownComponents,
+};
+pub use diagnostic::{Diagnostic, Severity};
+pub use error::{ErrorCode, MdxError};
+pub use frontmatter::{
+    extract_frontmatter, FieldError, FrontmatterData, FrontmatterFormat, FrontmatterSchema,
+};
+pub use plugins::{MdxPlugin, PluginContext, TocEntry};
+pub use safe_mode::{SafeExpressions, SafeMode};
+pub use stats::DocumentStats;
+pub use text::{extract_text, TextOptions};
+pub use url_policy::{UrlPolicy, UrlRewrite, UrlViolation};
+
+/// The `oxc_ast` version used for compiled programs (see
+/// [`MdxPlugin::transform_program`])
+pub use oxc_ast;
+
+use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 
@@ -79,17 +127,72 @@ pub enum OutputFormat {
     Program,
     /// Function body format for runtime eval with new Function()
     FunctionBody,
+    /// ES module like `Program`, but with JSX syntax left in place
+    /// (`<_components.h1 {...props}>…</_components.h1>`) for an external
+    /// JSX transform (Babel, SWC, ...)
+    Jsx,
+}
+
+/// How raw HTML nodes (`Html` in mdast) are compiled
+///
+/// MDX doesn't parse raw HTML, but plugins and hand-built trees can still
+/// produce `Html` nodes. HTML comments are always stripped.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RawHtml {
+    /// Leave raw HTML out of the output
+    Drop,
+    /// Parse the HTML fragment and compile it to JSX elements (`class` becomes
+    /// `className`, inline `style` strings become style objects). Scripts,
+    /// event handler attributes and `srcdoc` are dropped, and URLs follow the
+    /// URL policy.
+    #[default]
+    Jsx,
+    /// Render the HTML as-is through `dangerouslySetInnerHTML` on a `div`
+    /// (or a `span` for inline HTML)
+    DangerouslySetInnerHtml,
 }
 
 /// Options for MDX compilation
+///
+/// Plugins are shared (`Arc`), so options can be cloned and reused for any
+/// number of documents, including from several threads.
+#[derive(Clone)]
 pub struct MdxCompileOptions {
     pub filepath: Option<String>,
     pub gfm: bool,
     pub footnotes: bool,
     pub math: bool,
     pub jsx_runtime: String,
-    pub plugins: Vec<Box<dyn MdxPlugin>>,
+    /// Plugins in the order they run (see [`MdxPlugin`])
+    pub plugins: Vec<Arc<dyn MdxPlugin>>,
     pub output_format: OutputFormat,
+    /// Generate a v3 source map mapping the compiled code back to the MDX source
+    pub source_map: bool,
+    /// Development mode: use `jsxDEV` from the dev runtime, pass MDX source
+    /// locations to React, and throw when a provider component is missing
+    pub development: bool,
+    /// Module exporting a `useMDXComponents` hook (e.g. `@mdx-js/react`).
+    ///
+    /// When set, components from the provider are merged into `_components`
+    /// (after the defaults, before the `components` prop). In `FunctionBody`
+    /// output the hook is read from `arguments[0].useMDXComponents`.
+    pub provider_import_source: Option<String>,
+    /// How raw HTML nodes are compiled (see [`RawHtml`])
+    pub raw_html: RawHtml,
+    /// Restrictions for untrusted content: ESM, expressions and JSX
+    /// components are rejected unless allowed (see [`SafeMode`])
+    pub safe_mode: Option<SafeMode>,
+    /// Allowed URL schemes for links, images and JSX `href`/`src`
+    /// attributes (unchecked when `None`, see [`UrlPolicy`])
+    pub url_policy: Option<UrlPolicy>,
+    /// Fail compilation when there are warning (or error) diagnostics,
+    /// including those reported by plugins
+    pub warnings_as_errors: bool,
+    /// Reading speed for `DocumentStats::reading_time_minutes`
+    pub words_per_minute: u32,
+    /// Schema the frontmatter must match, checked before codegen (see
+    /// [`FrontmatterSchema`])
+    pub frontmatter_schema: Option<FrontmatterSchema>,
 }
 
 impl std::fmt::Debug for MdxCompileOptions {
@@ -101,7 +204,23 @@ impl std::fmt::Debug for MdxCompileOptions {
             .field("math", &self.math)
             .field("jsx_runtime", &self.jsx_runtime)
             .field("output_format", &self.output_format)
-            .field("plugins_count", &self.plugins.len())
+            .field("source_map", &self.source_map)
+            .field("development", &self.development)
+            .field("provider_import_source", &self.provider_import_source)
+            .field("raw_html", &self.raw_html)
+            .field("safe_mode", &self.safe_mode)
+            .field("url_policy", &self.url_policy)
+            .field("warnings_as_errors", &self.warnings_as_errors)
+            .field("words_per_minute", &self.words_per_minute)
+            .field("frontmatter_schema", &self.frontmatter_schema)
+            .field(
+                "plugins",
+                &self
+                    .plugins
+                    .iter()
+                    .map(|plugin| plugin.name())
+                    .collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
@@ -116,20 +235,15 @@ impl Default for MdxCompileOptions {
             jsx_runtime: "react/jsx-runtime".to_string(),
             plugins: Vec::new(),
             output_format: OutputFormat::default(),
-        }
-    }
-}
-
-impl Clone for MdxCompileOptions {
-    fn clone(&self) -> Self {
-        Self {
-            filepath: self.filepath.clone(),
-            gfm: self.gfm,
-            footnotes: self.footnotes,
-            math: self.math,
-            jsx_runtime: self.jsx_runtime.clone(),
-            plugins: Vec::new(), // Don't clone plugins (trait objects can't be cloned)
-            output_format: self.output_format,
+            source_map: false,
+            development: false,
+            provider_import_source: None,
+            raw_html: RawHtml::default(),
+            safe_mode: None,
+            url_policy: None,
+            warnings_as_errors: false,
+            words_per_minute: stats::DEFAULT_WORDS_PER_MINUTE,
+            frontmatter_schema: None,
         }
     }
 }
@@ -144,6 +258,51 @@ impl MdxCompileOptions {
         self
     }
 
+    pub fn with_source_map(mut self) -> Self {
+        self.source_map = true;
+        self
+    }
+
+    pub fn with_development(mut self) -> Self {
+        self.development = true;
+        self
+    }
+
+    pub fn with_provider_import_source(mut self, source: impl Into<String>) -> Self {
+        self.provider_import_source = Some(source.into());
+        self
+    }
+
+    pub fn with_raw_html(mut self, raw_html: RawHtml) -> Self {
+        self.raw_html = raw_html;
+        self
+    }
+
+    pub fn with_safe_mode(mut self, safe_mode: SafeMode) -> Self {
+        self.safe_mode = Some(safe_mode);
+        self
+    }
+
+    pub fn with_url_policy(mut self, url_policy: UrlPolicy) -> Self {
+        self.url_policy = Some(url_policy);
+        self
+    }
+
+    pub fn with_warnings_as_errors(mut self) -> Self {
+        self.warnings_as_errors = true;
+        self
+    }
+
+    pub fn with_words_per_minute(mut self, words_per_minute: u32) -> Self {
+        self.words_per_minute = words_per_minute;
+        self
+    }
+
+    pub fn with_frontmatter_schema(mut self, schema: FrontmatterSchema) -> Self {
+        self.frontmatter_schema = Some(schema);
+        self
+    }
+
     pub fn with_all_features(mut self) -> Self {
         self.gfm = true;
         self.footnotes = true;
@@ -151,30 +310,109 @@ impl MdxCompileOptions {
         self
     }
 
-    pub fn with_plugin(mut self, plugin: Box<dyn MdxPlugin>) -> Self {
-        self.plugins.push(plugin);
+    pub fn with_plugin(mut self, plugin: impl MdxPlugin + 'static) -> Self {
+        self.plugins.push(Arc::new(plugin));
         self
     }
 
-    pub fn with_default_plugins(mut self) -> Self {
-        self.plugins
-            .push(Box::new(plugins::HeadingIdPlugin::default()));
-        self.plugins
-            .push(Box::new(plugins::ImageOptimizationPlugin::default()));
-        self
+    pub fn with_default_plugins(self) -> Self {
+        self.with_plugin(plugins::HeadingIdPlugin::default())
+            .with_plugin(plugins::ImageOptimizationPlugin::new())
+    }
+
+    /// Key covering every option and plugin configuration that affects the
+    /// output, for caching compiled results
+    ///
+    /// `None` when the output depends on something that can't be keyed: a
+    /// URL rewrite closure, or a plugin whose [`MdxPlugin::cache_key`] is
+    /// `None`.
+    pub fn cache_key(&self) -> Option<String> {
+        let url_policy = match &self.url_policy {
+            Some(policy) if policy.rewrite.is_some() => return None,
+            Some(policy) => serde_json::json!({
+                "allowedSchemes": policy.allowed_schemes,
+                "onViolation": match policy.on_violation {
+                    UrlViolation::Strip => "strip",
+                    UrlViolation::Replace => "replace",
+                    UrlViolation::Error => "error",
+                },
+            }),
+            None => serde_json::Value::Null,
+        };
+        let safe_mode = self.safe_mode.as_ref().map(|safe_mode| {
+            let expressions = match &safe_mode.expressions {
+                SafeExpressions::Reject => serde_json::Value::Null,
+                SafeExpressions::PropertyAccess(roots) => serde_json::json!(roots),
+            };
+            serde_json::json!({
+                "expressions": expressions,
+                "allowedComponents": safe_mode.allowed_components,
+            })
+        });
+        let plugins = self
+            .plugins
+            .iter()
+            .map(|plugin| plugin.cache_key())
+            .collect::<Option<Vec<_>>>()?;
+
+        let key = serde_json::json!({
+            "filepath": self.filepath,
+            "gfm": self.gfm,
+            "footnotes": self.footnotes,
+            "math": self.math,
+            "jsxRuntime": self.jsx_runtime,
+            "outputFormat": match self.output_format {
+                OutputFormat::Program => "program",
+                OutputFormat::FunctionBody => "function-body",
+                OutputFormat::Jsx => "jsx",
+            },
+            "sourceMap": self.source_map,
+            "development": self.development,
+            "providerImportSource": self.provider_import_source,
+            "rawHtml": match self.raw_html {
+                RawHtml::Drop => "drop",
+                RawHtml::Jsx => "jsx",
+                RawHtml::DangerouslySetInnerHtml => "dangerously-set-inner-html",
+            },
+            "safeMode": safe_mode,
+            "urlPolicy": url_policy,
+            "warningsAsErrors": self.warnings_as_errors,
+            "wordsPerMinute": self.words_per_minute,
+            "frontmatterSchema": self.frontmatter_schema.as_ref().map(FrontmatterSchema::cache_key),
+            "plugins": plugins,
+        });
+        Some(key.to_string())
     }
 }
 
 /// Result of MDX compilation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MdxCompileResult {
     pub code: String,
+    /// Source map (v3 JSON) when `MdxCompileOptions::source_map` is enabled
+    pub map: Option<String>,
     pub frontmatter: Option<FrontmatterData>,
     pub images: Vec<String>,
     pub named_exports: Vec<String>,
     pub reexports: Vec<String>,
     pub imports: Vec<String>,
     pub default_export: Option<String>,
+    /// Non-fatal issues found during compilation (e.g. unresolved references)
+    pub diagnostics: Vec<Diagnostic>,
+    /// Word count, reading time, and the headings, links, code languages and
+    /// components used
+    pub stats: DocumentStats,
+    /// Table of contents built by `HeadingIdPlugin` (empty without it)
+    pub toc: Vec<TocEntry>,
+}
+
+/// Result of [`compile_to_html`]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MdxHtmlResult {
+    pub html: String,
+    pub frontmatter: Option<FrontmatterData>,
+    /// Non-fatal issues, including expressions left out of the HTML
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 /// Compile an MDX string to JSX with optional plugins
@@ -182,12 +420,29 @@ pub fn compile(
     source: &str,
     options: MdxCompileOptions,
 ) -> Result<MdxCompileResult, Box<MdxError>> {
+    let root = parse(source, &options)?;
+    compile_tree(&markdown::mdast::Node::Root(root), Some(source), options)
+}
+
+/// Parse MDX to mdast without compiling it
+///
+/// Uses the syntax enabled in `options` (`gfm`, `footnotes`, `math`).
+/// Frontmatter stays in the tree as a `yaml` or `toml` node; see
+/// [`mdast::to_json`] for the JSON form.
+pub fn parse(
+    source: &str,
+    options: &MdxCompileOptions,
+) -> Result<markdown::mdast::Root, Box<MdxError>> {
     // Set up markdown parser options
     let mut parse_options = markdown::ParseOptions::mdx();
 
     // Enable ESM parsing with OXC validation
     parse_options.mdx_esm_parse = Some(Box::new(crate::esm::validate_esm_syntax));
 
+    // Validate expressions (`{...}` in prose and JSX attributes) with OXC
+    parse_options.mdx_expression_parse =
+        Some(Box::new(crate::expression::validate_expression_syntax));
+
     // Enable frontmatter parsing (YAML and TOML)
     parse_options.constructs.frontmatter = true;
 
@@ -202,6 +457,7 @@ pub fn compile(
     // Enable footnotes if requested
     if options.footnotes {
         parse_options.constructs.gfm_footnote_definition = true;
+        parse_options.constructs.gfm_label_start_footnote = true;
     }
 
     // Enable math if requested
@@ -212,63 +468,219 @@ pub fn compile(
 
     // Parse MDX to markdown AST
     let mdast = markdown::to_mdast(source, &parse_options).map_err(|e| {
-        let mut err = MdxError::parse_error(e.to_string());
+        let mut err = MdxError::from_markdown_message(&e, source);
         if let Some(filepath) = &options.filepath {
             err = err.with_file(filepath.clone());
         }
         Box::new(err)
     })?;
 
-    // Extract frontmatter (removes frontmatter nodes from AST)
-    let (cleaned_mdast, frontmatter) =
-        extract_frontmatter(&mdast).map_err(|e| Box::new(MdxError::new(e.to_string())))?;
-
-    // Set up MDX conversion options with plugins and jsx_runtime
-    let mut mdx_options = mdx::MdxOptions {
-        plugins: Vec::new(),
-        jsx_runtime: options.jsx_runtime.clone(),
-        output_format: options.output_format,
-        frontmatter: frontmatter.clone(),
-    };
-    for plugin in options.plugins {
-        mdx_options = mdx_options.with_plugin(plugin);
+    match mdast {
+        markdown::mdast::Node::Root(root) => Ok(root),
+        _ => Err(Box::new(MdxError::parse_error(
+            "Expected a root node".to_string(),
+        ))),
     }
+}
 
-    // Convert mdast to JSX (applies plugins during conversion)
-    let jsx_code = mdast_to_jsx_with_options(&cleaned_mdast, &mdx_options).map_err(|e| {
-        let mut err = MdxError::conversion_error(e.to_string());
-        if let Some(filepath) = &options.filepath {
-            err = err.with_file(filepath.clone());
-        }
-        Box::new(err)
+/// Compile an mdast tree in JSON (see [`mdast::from_json`]) to JSX
+///
+/// For trees changed outside bunny, such as by remark plugins. Safe mode,
+/// the URL policy and plugins apply as in [`compile`]. There is no source
+/// text, so errors have locations but no code context, and source maps have
+/// no `sourcesContent`.
+pub fn compile_from_mdast(
+    json: &str,
+    options: MdxCompileOptions,
+) -> Result<MdxCompileResult, Box<MdxError>> {
+    let root = mdast::from_json(json).map_err(|mut err| {
+        err.file = options.filepath.clone();
+        err
     })?;
+    compile_tree(&markdown::mdast::Node::Root(root), None, options)
+}
 
-    // Extract collected images from ImageOptimizationPlugin
-    let mut images = Vec::new();
-    for plugin in &mdx_options.plugins {
-        if let Some(image_plugin) = plugin
-            .as_any()
-            .downcast_ref::<crate::plugins::ImageOptimizationPlugin>()
-        {
-            images.extend(image_plugin.images());
-        }
-    }
+/// Compile a parsed tree; `source` is the text it was parsed from, if any
+fn compile_tree(
+    mdast: &markdown::mdast::Node,
+    source: Option<&str>,
+    options: MdxCompileOptions,
+) -> Result<MdxCompileResult, Box<MdxError>> {
+    let (cleaned_mdast, frontmatter, mdx_options) = prepare_tree(mdast, source, &options)?;
+
+    // Convert mdast to JSX (applies plugins during conversion)
+    let rendered = codegen::render(&cleaned_mdast, &mdx_options)
+        .map_err(|e| codegen_error(e, source, &options))?;
+    promote_diagnostics(&rendered.diagnostics, source, &options)?;
+
+    let mut stats = rendered.stats;
+    stats.set_reading_time(options.words_per_minute);
 
-    // Extract ESM statements from the original AST
+    // Images collected by ImageOptimizationPlugin for this document
+    let images = plugins::ImageOptimizationPlugin::images(&rendered.plugin_context).to_vec();
+    let toc = plugins::HeadingIdPlugin::toc(&rendered.plugin_context).to_vec();
+
+    // Extract ESM statements after plugins, which may add exports
     let parsed_exports =
-        extract_esm_info(&mdast).map_err(|e| Box::new(MdxError::new(e.to_string())))?;
+        extract_esm_info(&rendered.root).map_err(|e| Box::new(MdxError::new(e.to_string())))?;
 
     Ok(MdxCompileResult {
-        code: jsx_code,
+        code: rendered.code,
+        map: rendered.map,
         frontmatter,
         images,
         named_exports: parsed_exports.named_exports,
         reexports: parsed_exports.reexports,
         imports: parsed_exports.imports,
         default_export: parsed_exports.default_export,
+        diagnostics: rendered.diagnostics,
+        stats,
+        toc,
     })
 }
 
+/// Compile MDX to static HTML
+///
+/// For consumers that can't run JavaScript, such as RSS feeds, email and AMP
+/// pages. JSX components render through the templates in `html`; see
+/// [`HtmlOptions`] for the example. `{expressions}` and ESM are left out, with
+/// an info diagnostic for each expression, as are `<script>` elements and
+/// event handler attributes. Safe mode, the URL policy, `raw_html` and
+/// `transform_ast` plugins apply as in [`compile`].
+pub fn compile_to_html(
+    source: &str,
+    options: MdxCompileOptions,
+    html: &HtmlOptions,
+) -> Result<MdxHtmlResult, Box<MdxError>> {
+    let root = markdown::mdast::Node::Root(parse(source, &options)?);
+    let (cleaned_mdast, frontmatter, mdx_options) = prepare_tree(&root, Some(source), &options)?;
+
+    let rendered = codegen::render_html(&cleaned_mdast, &mdx_options, html)
+        .map_err(|e| codegen_error(e, Some(source), &options))?;
+    promote_diagnostics(&rendered.diagnostics, Some(source), &options)?;
+
+    Ok(MdxHtmlResult {
+        html: rendered.html,
+        frontmatter,
+        diagnostics: rendered.diagnostics,
+    })
+}
+
+/// Check safe mode and split off the frontmatter, ready for codegen
+fn prepare_tree(
+    mdast: &markdown::mdast::Node,
+    source: Option<&str>,
+    options: &MdxCompileOptions,
+) -> Result<
+    (
+        markdown::mdast::Node,
+        Option<FrontmatterData>,
+        mdx::MdxOptions,
+    ),
+    Box<MdxError>,
+> {
+    // Reject untrusted constructs before anything is compiled
+    if let Some(safe_mode) = &options.safe_mode {
+        safe_mode::check(mdast, safe_mode, source.unwrap_or_default()).map_err(|mut err| {
+            err.file = options.filepath.clone();
+            if source.is_none() {
+                err.context = None;
+            }
+            err
+        })?;
+    }
+
+    // Extract frontmatter (removes frontmatter nodes from AST)
+    let (cleaned_mdast, frontmatter) = extract_frontmatter(mdast).map_err(|e| {
+        let mut err = MdxError::new(e.to_string()).with_code(ErrorCode::InvalidFrontmatter);
+        err.file = options.filepath.clone();
+        Box::new(err)
+    })?;
+
+    if let Some(schema) = &options.frontmatter_schema {
+        let position = mdast
+            .children()
+            .into_iter()
+            .flatten()
+            .find_map(|node| match node {
+                markdown::mdast::Node::Yaml(_) | markdown::mdast::Node::Toml(_) => node.position(),
+                _ => None,
+            });
+        frontmatter::check_frontmatter(schema, frontmatter.as_ref(), position, source).map_err(
+            |mut err| {
+                err.file = options.filepath.clone();
+                err
+            },
+        )?;
+    }
+
+    // Set up MDX conversion options with plugins and jsx_runtime
+    let mdx_options = mdx::MdxOptions {
+        plugins: options.plugins.clone(),
+        jsx_runtime: options.jsx_runtime.clone(),
+        output_format: options.output_format,
+        frontmatter: frontmatter.clone(),
+        source_map: options.source_map,
+        source: source.filter(|_| options.source_map).map(str::to_string),
+        filepath: options.filepath.clone(),
+        development: options.development,
+        provider_import_source: options.provider_import_source.clone(),
+        raw_html: options.raw_html,
+        url_policy: options.url_policy.clone(),
+    };
+
+    Ok((cleaned_mdast, frontmatter, mdx_options))
+}
+
+/// Turn a codegen failure into an `MdxError`
+fn codegen_error(
+    e: anyhow::Error,
+    source: Option<&str>,
+    options: &MdxCompileOptions,
+) -> Box<MdxError> {
+    // Errors about the document (e.g. a disallowed URL) keep their details
+    let mut err = match e.downcast::<MdxError>() {
+        Ok(err) => match (err.line, source) {
+            (Some(line), Some(source)) if err.context.is_none() => {
+                err.with_context(MdxError::extract_context(source, line, 1))
+            }
+            _ => err,
+        },
+        Err(e) => MdxError::conversion_error(e.to_string()),
+    };
+    if let Some(filepath) = &options.filepath {
+        err = err.with_file(filepath.clone());
+    }
+    Box::new(err)
+}
+
+/// Fail with the warnings and errors when `warnings_as_errors` is set
+fn promote_diagnostics(
+    diagnostics: &[Diagnostic],
+    source: Option<&str>,
+    options: &MdxCompileOptions,
+) -> Result<(), Box<MdxError>> {
+    if !options.warnings_as_errors {
+        return Ok(());
+    }
+    let promoted: Vec<Diagnostic> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity != Severity::Info)
+        .cloned()
+        .collect();
+    if promoted.is_empty() {
+        return Ok(());
+    }
+    let mut err = MdxError::promoted_diagnostics(&promoted);
+    if let (Some(line), Some(source)) = (err.line, source) {
+        err = err.with_context(MdxError::extract_context(source, line, 1));
+    }
+    if let Some(filepath) = &options.filepath {
+        err = err.with_file(filepath.clone());
+    }
+    Err(Box::new(err))
+}
+
 /// Parsed ES module information from MDX
 struct ParsedExports {
     named_exports: Vec<String>,
@@ -292,18 +704,20 @@ fn extract_esm_info(root: &markdown::mdast::Node) -> Result<ParsedExports> {
 
     for child in &root_node.children {
         if let Node::MdxjsEsm(esm) = child {
-            let code = esm.value.trim();
-
-            if crate::esm::is_reexport(code) {
-                reexports.push(code.to_string());
-            } else if crate::esm::has_named_exports(code) {
-                named_exports.push(code.to_string());
-            } else if code.starts_with("export default") {
-                if let Some(name) = crate::esm::get_default_export_name(code) {
-                    default_export = Some(name);
+            for statement in crate::esm::split_statements(&esm.value) {
+                let code = statement.as_str();
+
+                if crate::esm::is_reexport(code) {
+                    reexports.push(statement);
+                } else if crate::esm::has_named_exports(code) {
+                    named_exports.push(statement);
+                } else if code.starts_with("export default") {
+                    if let Some(name) = crate::esm::get_default_export_name(code) {
+                        default_export = Some(name);
+                    }
+                } else if code.starts_with("import ") {
+                    imports.push(statement);
                 }
-            } else if code.starts_with("import ") {
-                imports.push(code.to_string());
             }
         }
     }
@@ -320,6 +734,12 @@ fn extract_esm_info(root: &markdown::mdast::Node) -> Result<ParsedExports> {
 mod tests {
     use super::*;
 
+    /// Compiled code without whitespace, so assertions don't depend on how
+    /// `oxc_codegen` breaks lines
+    fn compact(code: &str) -> String {
+        code.split_whitespace().collect()
+    }
+
     #[test]
     fn test_basic_compilation() {
         let mdx = "# Hello\n\nThis is **bold** text.";
@@ -339,6 +759,44 @@ mod tests {
         assert_eq!(fm.format, FrontmatterFormat::Yaml);
     }
 
+    #[test]
+    fn test_frontmatter_schema() {
+        #[derive(serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Post {
+            title: String,
+            date: String,
+            tags: Vec<String>,
+        }
+        let mut options =
+            MdxCompileOptions::new().with_frontmatter_schema(FrontmatterSchema::of::<Post>());
+        options.filepath = Some("post.mdx".to_string());
+
+        // TOML dates are strings
+        let mdx = "+++\ntitle = \"Hi\"\ndate = 2024-01-15\ntags = [\"a\"]\n+++\n\n# Hi";
+        let result = compile(mdx, options.clone()).unwrap();
+        assert_eq!(result.frontmatter.unwrap().data["date"], "2024-01-15");
+
+        let mdx = "---\ntitle: Hi\ndate: 2024-01-15\ntags:\n  - a\n  - 2\n---\n\n# Hi";
+        let err = compile(mdx, options.clone()).unwrap_err();
+        assert_eq!(err.code, Some(ErrorCode::FrontmatterSchema));
+        assert_eq!(
+            err.message,
+            "Frontmatter field `tags[1]` doesn't match the schema: invalid type: integer `2`, expected a string"
+        );
+        assert_eq!((err.line, err.column), (Some(6), Some(3)));
+        assert_eq!(err.file.as_deref(), Some("post.mdx"));
+        assert!(err.context.unwrap().contains("  - 2"));
+
+        // Without frontmatter, required fields are missing
+        let err = compile("# Hi", options).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Frontmatter doesn't match the schema: missing field `title`"
+        );
+        assert_eq!(err.line, None);
+    }
+
     #[test]
     fn test_with_gfm() {
         let mdx = "This is ~~strikethrough~~ text.";
@@ -357,6 +815,603 @@ mod tests {
         assert!(result.code.contains("math"));
     }
 
+    #[test]
+    fn test_reference_links_and_images() {
+        let mdx = "See [the docs][Docs] and ![logo][].\n\n[docs]: https://example.com/docs \"Docs\"\n[logo]: /logo.png";
+        let result = compile(mdx, MdxCompileOptions::new()).unwrap();
+
+        let code = compact(&result.code);
+        assert!(code.contains(&compact(
+            "_jsx(_components.a, {...props, href: \"https://example.com/docs\", children: \"the docs\"})"
+        )));
+        assert!(code.contains(&compact(
+            "_jsx(_components.img, {...props, src: \"/logo.png\", alt: \"logo\"})"
+        )));
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_unresolved_reference_diagnostic() {
+        use markdown::mdast::{LinkReference, Node, Paragraph, ReferenceKind, Root, Text};
+
+        let root = Node::Root(Root {
+            children: vec![Node::Paragraph(Paragraph {
+                children: vec![Node::LinkReference(LinkReference {
+                    children: vec![Node::Text(Text {
+                        value: "missing".to_string(),
+                        position: None,
+                    })],
+                    position: None,
+                    reference_kind: ReferenceKind::Shortcut,
+                    identifier: "missing".to_string(),
+                    label: Some("missing".to_string()),
+                })],
+                position: None,
+            })],
+            position: None,
+        });
+
+        let output = codegen::render(&root, &mdx::MdxOptions::default()).unwrap();
+        assert!(output.code.contains("\"[missing]\""));
+        assert_eq!(output.diagnostics.len(), 1);
+        assert_eq!(output.diagnostics[0].code, "unresolved-reference");
+        assert_eq!(output.diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_plugin_diagnostics() {
+        let mdx = "# Links\n\n[Empty]() and [docs](/docs/intro.mdx)";
+        let options = MdxCompileOptions::new().with_plugin(plugins::LinkValidationPlugin::new());
+        let result = compile(mdx, options.clone()).unwrap();
+
+        let diagnostics: Vec<_> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code.as_str(), d.plugin.as_deref()))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                (Severity::Warning, "empty-link", Some("link-validation")),
+                (
+                    Severity::Warning,
+                    "markdown-file-link",
+                    Some("link-validation")
+                ),
+            ]
+        );
+        let span = result.diagnostics[1].span.unwrap();
+        assert_eq!((span.start.line, span.start.column), (3, 15));
+
+        let mut options = options.with_warnings_as_errors();
+        options.filepath = Some("post.mdx".to_string());
+        let err = compile(mdx, options).unwrap_err();
+        assert!(err.message.starts_with("2 diagnostics treated as errors:"));
+        assert!(err
+            .message
+            .contains("warning[markdown-file-link]: Internal link points to a .md/.mdx file"));
+        assert!(err.message.contains("[link-validation]"));
+        assert_eq!((err.line, err.column), (Some(3), Some(1)));
+        assert_eq!(err.file.as_deref(), Some("post.mdx"));
+        assert!(err.context.is_some());
+
+        // Without warnings there is nothing to promote
+        let options = MdxCompileOptions::new().with_warnings_as_errors();
+        assert!(compile("[ok](/docs)", options).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_expression_is_an_error() {
+        let mdx = "# Title\n\nCall {foo(1 2)} here.";
+        let mut options = MdxCompileOptions::new();
+        options.filepath = Some("page.mdx".to_string());
+        let err = compile(mdx, options).unwrap_err();
+
+        assert!(err.message.contains("Could not parse expression with OXC"));
+        assert_eq!(err.file.as_deref(), Some("page.mdx"));
+        assert_eq!((err.line, err.column), (Some(3), Some(13)));
+        assert!(err
+            .context
+            .unwrap()
+            .contains(">   3 | Call {foo(1 2)} here."));
+
+        let err = compile("<a b={c d} />", MdxCompileOptions::new()).unwrap_err();
+        assert_eq!((err.line, err.column), (Some(1), Some(9)));
+        assert!(compile("<a {...b, c} />", MdxCompileOptions::new()).is_err());
+        assert!(compile("{foo(}", MdxCompileOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_keep_their_span_and_code() {
+        let mdx = "<a>\n\nSome </b> text";
+        let mut options = MdxCompileOptions::new();
+        options.filepath = Some("post.mdx".to_string());
+        let err = compile(mdx, options).unwrap_err();
+
+        assert_eq!(err.code, Some(ErrorCode::MismatchedClosingTag));
+        assert_eq!((err.line, err.column), (Some(3), Some(6)));
+        let span = err.span.unwrap();
+        assert_eq!(&mdx[span.start.offset..span.end.offset], "</b>");
+        assert!(err
+            .render(mdx)
+            .contains("3 | Some </b> text\n  |      ^^^^\n"));
+
+        let codes = [
+            ("<Callout>\n\ntext", ErrorCode::UnclosedTag),
+            ("<a></a b>", ErrorCode::InvalidClosingTag),
+            ("Some </b> text", ErrorCode::InvalidClosingTag),
+            ("{foo", ErrorCode::UnexpectedEndOfFile),
+            ("<a !>", ErrorCode::UnexpectedCharacter),
+            ("> <a\nb>", ErrorCode::LazyLine),
+            ("{foo(}", ErrorCode::InvalidExpression),
+            ("import { from", ErrorCode::InvalidEsm),
+            ("---\ntitle: [\n---", ErrorCode::InvalidFrontmatter),
+        ];
+        for (mdx, code) in codes {
+            let err = compile(mdx, MdxCompileOptions::new()).unwrap_err();
+            assert_eq!(err.code, Some(code), "{}", mdx);
+        }
+
+        // ESM errors point into the document, not into the ESM block
+        let err = compile("# Hi\n\nimport { from", MdxCompileOptions::new()).unwrap_err();
+        assert_eq!(err.line, Some(3));
+    }
+
+    #[test]
+    fn test_compile_from_mdast() {
+        let mdx = "---\ntitle: Hi\n---\n\n# Hello\n\n<Note>{props.name}</Note>";
+        let options = MdxCompileOptions::new().with_default_plugins();
+        let root = parse(mdx, &options).unwrap();
+        let json = mdast::to_json(&root);
+
+        let direct = compile(mdx, options.clone()).unwrap();
+        let from_json = compile_from_mdast(&json, options.clone()).unwrap();
+        assert_eq!(from_json.code, direct.code);
+        assert_eq!(from_json.frontmatter, direct.frontmatter);
+
+        // Trees edited in JavaScript compile like parsed ones
+        let json = json.replace(r#""value":"Hello""#, r#""value":"Changed""#);
+        let result = compile_from_mdast(&json, options).unwrap();
+        assert!(result.code.contains("\"Changed\""));
+        assert!(result.code.contains("id: \"changed\""));
+
+        // Safe mode still applies, without source context
+        let err = compile_from_mdast(
+            &json,
+            MdxCompileOptions::new().with_safe_mode(SafeMode::new()),
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Some(ErrorCode::UnsafeConstruct));
+        assert_eq!(err.line, Some(7));
+        assert!(err.context.is_none());
+    }
+
+    #[test]
+    fn test_compile_to_html() {
+        let mdx = "---\ntitle: Hi\n---\n\n# Hello\n\n<Note>Read {props.name}</Note>";
+        let html = HtmlOptions::new()
+            .with_component("Note", |props| format!("<aside>{}</aside>", props.children));
+        let result = compile_to_html(mdx, MdxCompileOptions::new(), &html).unwrap();
+        assert_eq!(result.html, "<h1>Hello</h1>\n<p><aside>Read </aside></p>\n");
+        assert_eq!(
+            result.frontmatter.unwrap().data["title"],
+            serde_json::json!("Hi")
+        );
+        assert_eq!(result.diagnostics[0].code, "expression-not-rendered");
+
+        // Info diagnostics aren't promoted, unknown components are
+        let options = MdxCompileOptions::new().with_warnings_as_errors();
+        assert!(compile_to_html(mdx, options.clone(), &html).is_ok());
+        let err = compile_to_html(mdx, options, &HtmlOptions::new()).unwrap_err();
+        assert_eq!(err.code, Some(ErrorCode::PromotedDiagnostics));
+    }
+
+    #[test]
+    fn test_expressions_with_braces_and_comments() {
+        let mdx = "{/* note */}\n\nA {/* inline */} b {\"}\"} {a(() => {})}";
+        let result = compile(mdx, MdxCompileOptions::new()).unwrap();
+
+        let code = compact(&result.code);
+        assert!(!result.code.contains("note"));
+        assert!(!result.code.contains("inline"));
+        assert!(code.contains(&compact(
+            r#"children: ["A ", " b ", "}", " ", a(() => {})]"#
+        )));
+    }
+
+    /// Appends raw HTML nodes to the document, as a remark-style plugin would
+    struct AppendHtml(&'static [&'static str]);
+
+    impl MdxPlugin for AppendHtml {
+        fn name(&self) -> &'static str {
+            "append-html"
+        }
+
+        fn transform_ast(
+            &self,
+            ast: &mut markdown::mdast::Node,
+            _cx: &mut PluginContext,
+        ) -> Result<()> {
+            use markdown::mdast::{Html, Node};
+
+            if let Some(children) = ast.children_mut() {
+                children.extend(self.0.iter().map(|value| {
+                    Node::Html(Html {
+                        value: value.to_string(),
+                        position: None,
+                    })
+                }));
+            }
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn compile_html(html: &'static [&'static str], raw_html: RawHtml) -> String {
+        let options = MdxCompileOptions::new()
+            .with_raw_html(raw_html)
+            .with_plugin(AppendHtml(html));
+        compile("", options).unwrap().code
+    }
+
+    #[test]
+    fn test_raw_html_to_jsx() {
+        let code = compact(&compile_html(
+            &["<div class=\"note\" style=\"margin-top: 1px; --gap: 2px\" onclick=\"x()\">\n  <!-- hidden -->\n  <label for=\"a\">A &amp; B</label><br>\n</div>"],
+            RawHtml::Jsx,
+        ));
+
+        assert!(code.contains(&compact(
+            r#"_jsxs("div", {className: "note", style: {marginTop: "1px", "--gap": "2px"}, children: [_jsx("label", {htmlFor: "a", children: "A & B"}), _jsx("br", {})]})"#
+        )));
+        assert!(!code.contains("hidden"));
+        assert!(!code.contains("onclick"));
+    }
+
+    #[test]
+    fn test_raw_html_to_jsx_is_sanitized() {
+        let html: &[&str] = &[concat!(
+            "<p><SCRIPT>alert(1)</SCRIPT><a href=\"javascript:x()\" ONCLICK=\"x()\">a</a>",
+            "<iframe srcdoc=\"<script>x()</script>\"></iframe><input disabled></p>"
+        )];
+        let options = MdxCompileOptions::new()
+            .with_url_policy(UrlPolicy::new())
+            .with_plugin(AppendHtml(html));
+        let code = compact(&compile("", options).unwrap().code);
+
+        assert!(code.contains(&compact(
+            r##"_jsxs("p", {children: [_jsx("a", {href: "#", children: "a"}), _jsx("iframe", {}), _jsx("input", {disabled: true})]})"##
+        )));
+        assert!(!code.contains("alert"));
+        assert!(!code.contains("x()"));
+    }
+
+    #[test]
+    fn test_raw_html_fragments() {
+        // Several top-level nodes need the runtime's Fragment
+        let code = compact(&compile_html(&["<b>a</b> <i>b</i>"], RawHtml::Jsx));
+        assert!(code.contains(&compact("Fragment as _Fragment")));
+        assert!(code.contains(&compact(
+            r#"_jsxs(_Fragment, {children: [_jsx("b", {children: "a"}), " ", _jsx("i", {children: "b"})]})"#
+        )));
+
+        // Comment-only HTML renders nothing, whatever the policy
+        for raw_html in [RawHtml::Jsx, RawHtml::DangerouslySetInnerHtml] {
+            let code = compile_html(&["<!-- note -->"], raw_html);
+            assert!(!code.contains("note"));
+            assert!(code.contains("const _content = null;"));
+        }
+    }
+
+    #[test]
+    fn test_raw_html_policies() {
+        let html: &[&str] = &[
+            "<table>\n<tr><td>1</td></tr>\n</table>",
+            "<b>bold</b><!-- c -->",
+        ];
+
+        let code = compile_html(html, RawHtml::Drop);
+        assert!(!code.contains("<tr>"));
+        assert!(!code.contains("bold"));
+
+        let code = compact(&compile_html(html, RawHtml::DangerouslySetInnerHtml));
+        assert!(code.contains(&compact(
+            r#"_jsx("div", {dangerouslySetInnerHTML: {__html: "<table>\n<tr><td>1</td></tr>\n</table>"}})"#
+        )));
+        assert!(code.contains(&compact(
+            r#"_jsx("span", {dangerouslySetInnerHTML: {__html: "<b>bold</b>"}})"#
+        )));
+    }
+
+    #[test]
+    fn test_url_policy() {
+        let mdx = "[a](javascript:alert(1)) ![b](data:image/png,x) [c][c] [d](/docs)\n\n[c]: vbscript:x\n\n<Card href=\"javascript:x\" src=\"https://a.com/b.png\" />";
+
+        // Unchecked by default
+        let code = compile(mdx, MdxCompileOptions::new()).unwrap().code;
+        assert!(code.contains("javascript:alert(1)"));
+
+        let options = MdxCompileOptions::new().with_url_policy(UrlPolicy::new());
+        let code = compact(&compile(mdx, options).unwrap().code);
+        assert!(code.contains(&compact(
+            r##"_jsx(_components.a, {...props, href: "#", children: "a"})"##
+        )));
+        assert!(code.contains(&compact(
+            r##"_jsx(_components.img, {...props, src: "#", alt: "b"})"##
+        )));
+        assert!(code.contains(&compact(
+            r##"_jsx(_components.a, {...props, href: "#", children: "c"})"##
+        )));
+        assert!(code.contains(&compact(r#"href: "/docs""#)));
+        assert!(code.contains(&compact(
+            r##"_jsx(_components.Card, {href: "#", src: "https://a.com/b.png"})"##
+        )));
+
+        let policy = UrlPolicy::new().on_violation(UrlViolation::Strip);
+        let code = compact(
+            &compile(mdx, MdxCompileOptions::new().with_url_policy(policy))
+                .unwrap()
+                .code,
+        );
+        assert!(code.contains(&compact(
+            r#"_jsx(_components.a, {...props, children: "a"})"#
+        )));
+        assert!(code.contains(&compact(r#"_jsx(_components.img, {...props, alt: "b"})"#)));
+        assert!(code.contains(&compact(
+            r#"_jsx(_components.Card, {src: "https://a.com/b.png"})"#
+        )));
+
+        let policy = UrlPolicy::new()
+            .on_violation(UrlViolation::Error)
+            .with_rewrite(|url| url.replace("/docs", "/v2/docs"));
+        let err = compile(
+            "[d](/docs)\n\nSee [x](javascript:alert(1))",
+            MdxCompileOptions::new().with_url_policy(policy.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.message,
+            "URL `javascript:alert(1)` uses a disallowed scheme `javascript:`"
+        );
+        assert_eq!((err.line, err.column), (Some(3), Some(5)));
+        assert!(err.context.unwrap().contains("See [x]"));
+
+        let code = compile(
+            "[d](/docs)",
+            MdxCompileOptions::new().with_url_policy(policy),
+        )
+        .unwrap()
+        .code;
+        assert!(code.contains("/v2/docs"));
+    }
+
+    #[test]
+    fn test_source_map_is_opt_in() {
+        let mdx = "# Hello\n\nSome *text*.";
+        let result = compile(mdx, MdxCompileOptions::new()).unwrap();
+        assert!(result.map.is_none());
+
+        let mut options = MdxCompileOptions::new().with_source_map();
+        options.filepath = Some("docs/intro.mdx".to_string());
+        let result = compile(mdx, options).unwrap();
+
+        let map: serde_json::Value = serde_json::from_str(result.map.as_deref().unwrap()).unwrap();
+        assert_eq!(map["version"], 3);
+        assert_eq!(map["sources"][0], "docs/intro.mdx");
+        assert_eq!(map["sourcesContent"][0], mdx);
+        assert!(!map["mappings"].as_str().unwrap().is_empty());
+    }
+
+    /// Decode source map `mappings` into absolute `[generated column,
+    /// source, source line, source column, name]` segments for each
+    /// generated line
+    fn decode_mappings(mappings: &str) -> Vec<Vec<[i64; 5]>> {
+        const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut state = [0i64; 5];
+        let mut lines = Vec::new();
+        for line in mappings.split(';') {
+            state[0] = 0;
+            let mut segments = Vec::new();
+            for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+                let (mut field, mut value, mut shift) = (0, 0i64, 0);
+                for byte in segment.bytes() {
+                    let digit = BASE64.iter().position(|&b| b == byte).unwrap() as i64;
+                    value |= (digit & 31) << shift;
+                    shift += 5;
+                    if digit & 32 == 0 {
+                        let delta = if value & 1 == 1 {
+                            -(value >> 1)
+                        } else {
+                            value >> 1
+                        };
+                        state[field] += delta;
+                        field += 1;
+                        (value, shift) = (0, 0);
+                    }
+                }
+                segments.push(state);
+            }
+            lines.push(segments);
+        }
+        lines
+    }
+
+    /// Source line and column (0-based) mapped from the start of `needle` in
+    /// the generated code
+    fn mapped_source(code: &str, map: &str, needle: &str) -> (i64, i64) {
+        let offset = code.find(needle).unwrap();
+        let line = code[..offset].matches('\n').count();
+        let line_start = code[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let column = code[line_start..offset].encode_utf16().count() as i64;
+
+        let map: serde_json::Value = serde_json::from_str(map).unwrap();
+        let lines = decode_mappings(map["mappings"].as_str().unwrap());
+        let segment = lines[line]
+            .iter()
+            .find(|segment| segment[0] == column)
+            .unwrap_or_else(|| panic!("no mapping for `{}`", needle));
+        (segment[2], segment[3])
+    }
+
+    #[test]
+    fn test_source_map_positions() {
+        let result = compile(
+            "# Hello\n\nSome *text*.",
+            MdxCompileOptions::new().with_source_map(),
+        )
+        .unwrap();
+        let map = result.map.unwrap();
+        assert_eq!(mapped_source(&result.code, &map, "_components.em"), (2, 5));
+        assert_eq!(mapped_source(&result.code, &map, "_components.h1"), (0, 0));
+
+        // After frontmatter, with UTF-16 columns past an emoji
+        let mdx = "---\ntitle: Hi\n---\n\n\u{1f600} *text*";
+        let result = compile(mdx, MdxCompileOptions::new().with_source_map()).unwrap();
+        let map = result.map.unwrap();
+        assert_eq!(mapped_source(&result.code, &map, "_components.em"), (4, 3));
+    }
+
+    #[test]
+    fn test_development_mode() {
+        let mdx = "# Hello\n\n<Callout>Note</Callout>";
+        let mut options = MdxCompileOptions::new().with_development();
+        options.filepath = Some("docs/intro.mdx".to_string());
+        let result = compile(mdx, options).unwrap();
+
+        let code = compact(&result.code);
+        assert!(code.contains(&compact(
+            "import { jsxDEV as _jsxDEV, Fragment as _Fragment } from \"react/jsx-dev-runtime\";"
+        )));
+        assert!(!result.code.contains("_jsx("));
+        assert!(code.contains(&compact(
+            "_jsxDEV(_components.h1, {...props, children: \"Hello\"}, undefined, false, {fileName: \"docs/intro.mdx\", lineNumber: 1, columnNumber: 1}, this)"
+        )));
+        assert!(code.contains(&compact(
+            "if (!_components.Callout) _missingMdxReference(\"Callout\", \"docs/intro.mdx:3:1\");"
+        )));
+        assert!(result
+            .code
+            .contains("function _missingMdxReference(id, place)"));
+    }
+
+    #[test]
+    fn test_development_function_body() {
+        let mut options = MdxCompileOptions::new().with_development();
+        options.output_format = OutputFormat::FunctionBody;
+        let result = compile("Hello", options).unwrap();
+
+        assert!(compact(&result.code).contains(&compact("const {jsxDEV: _jsxDEV} = arguments[0];")));
+        assert!(result.code.contains("fileName: \"<source.mdx>\""));
+        // No provider components referenced, so no runtime check
+        assert!(!result.code.contains("_missingMdxReference"));
+    }
+
+    #[test]
+    fn test_provider_import_source() {
+        let mdx = "# Hello";
+        let result = compile(mdx, MdxCompileOptions::new()).unwrap();
+        assert!(!result.code.contains("_provideComponents"));
+
+        let options = MdxCompileOptions::new().with_provider_import_source("@mdx-js/react");
+        let result = compile(mdx, options).unwrap();
+        assert!(result
+            .code
+            .contains("import { useMDXComponents as _provideComponents } from \"@mdx-js/react\";"));
+        assert!(result.code.contains("}, _provideComponents(), _cProp);"));
+
+        let mut options = MdxCompileOptions::new().with_provider_import_source("@mdx-js/react");
+        options.output_format = OutputFormat::FunctionBody;
+        let result = compile(mdx, options).unwrap();
+        assert!(result.code.contains(
+            "const { jsx: _jsx, jsxs: _jsxs, useMDXComponents: _provideComponents } = arguments[0];"
+        ));
+        assert!(!result.code.contains("import "));
+        assert!(result.code.contains("}, _provideComponents(), _cProp);"));
+    }
+
+    #[test]
+    fn test_export_default_layout() {
+        let mdx = "import Layout from './layout'\nexport default Layout\n\n# Hello";
+        let result = compile(mdx, MdxCompileOptions::new()).unwrap();
+
+        assert!(result.code.contains("import Layout from \"./layout\";"));
+        assert!(result.code.contains("const _content = _jsx(_components.h1"));
+        assert!(compact(&result.code).contains(&compact(
+            "return _jsx(Layout, {...props, children: _content});"
+        )));
+        assert!(!result.code.contains("export default Layout"));
+        assert_eq!(result.default_export.as_deref(), Some("Layout"));
+        assert_eq!(result.imports, vec!["import Layout from './layout'"]);
+        assert!(result.reexports.is_empty());
+
+        // Inline layouts are bound to MDXLayout first
+        let mdx = "export default function ({children}) {\n  return children\n}\n\n# Hello";
+        let result = compile(mdx, MdxCompileOptions::new()).unwrap();
+        assert!(result
+            .code
+            .contains("const MDXLayout = function({ children }) {\n  return children;\n};"));
+        assert!(compact(&result.code).contains(&compact(
+            "return _jsx(MDXLayout, {...props, children: _content});"
+        )));
+    }
+
+    #[test]
+    fn test_wrapper_component() {
+        let result = compile("# Hello", MdxCompileOptions::new()).unwrap();
+        assert!(compact(&result.code).contains(&compact(
+            "return _components.wrapper ? _jsx(_components.wrapper, {...props, children: _content}) : _content;"
+        )));
+    }
+
+    #[test]
+    fn test_jsx_output_format() {
+        let mdx = "# Hello *world*\n\n<my-widget data-id=\"1\" />\n\n- [link](/a)";
+        let mut options = MdxCompileOptions::new();
+        options.output_format = OutputFormat::Jsx;
+        let result = compile(mdx, options).unwrap();
+
+        assert!(!result.code.contains("react/jsx-runtime"));
+        assert!(!result.code.contains("_jsx("));
+        assert!(result.code.contains("export default function MDXContent"));
+        assert!(result.code.contains(
+            "const _content = <><_components.h1 {...props}>Hello <_components.em {...props}>world</_components.em></_components.h1>"
+        ));
+        assert!(result.code.contains("<my-widget data-id=\"1\" />"));
+        assert!(result
+            .code
+            .contains("<_components.a {...props} href=\"/a\">link</_components.a>"));
+        assert!(result.code.contains(
+            "return _components.wrapper ? <_components.wrapper {...props}>{_content}</_components.wrapper> : _content;"
+        ));
+    }
+
+    #[test]
+    fn test_default_plugins_in_every_output_format() {
+        let mdx = "# Intro\n\n## Intro\n\n![Photo](/photo.jpg)";
+        for format in [
+            OutputFormat::Program,
+            OutputFormat::FunctionBody,
+            OutputFormat::Jsx,
+        ] {
+            let mut options = MdxCompileOptions::new().with_default_plugins();
+            options.output_format = format;
+            let code = compact(&compile(mdx, options).unwrap().code);
+
+            if format == OutputFormat::Jsx {
+                assert!(code.contains(&compact(r#"<_components.h1 {...props} id="intro">"#)));
+                assert!(code.contains(&compact(r#"<_components.h2 {...props} id="intro-1">"#)));
+                assert!(code.contains(&compact(r#"data-fob-optimized={"true"}"#)));
+            } else {
+                assert!(code.contains(&compact(r#"{...props, id: "intro", children: "Intro"}"#)));
+                assert!(code.contains(&compact(r#"{...props, id: "intro-1", children: "Intro"}"#)));
+                assert!(code.contains(&compact(r#""data-fob-optimized": "true""#)));
+            }
+        }
+    }
+
     #[test]
     fn test_function_body_output_format() {
         let mdx = "---\ntitle: Test\n---\n\n# Hello";
@@ -375,8 +1430,9 @@ mod tests {
         // 4. NOT have import statements
         assert!(!result.code.contains("import {"));
         // 5. Have a return statement with exports
-        assert!(result.code.contains("return {default: MDXContent"));
-        assert!(result.code.contains("frontmatter: frontmatter"));
+        assert!(
+            compact(&result.code).contains(&compact("return {default: MDXContent, frontmatter};"))
+        );
     }
 
     #[test]
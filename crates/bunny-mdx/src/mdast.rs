@@ -0,0 +1,143 @@
+//! The markdown AST (mdast) and its JSON form
+//!
+//! [`parse`](crate::parse) returns the tree bunny compiles, and
+//! [`compile_from_mdast`](crate::compile_from_mdast) compiles a tree back.
+//! The JSON follows [mdast](https://github.com/syntax-tree/mdast) and
+//! [unist](https://github.com/syntax-tree/unist): every node has a `type`,
+//! positions have `line`, `column` and `offset`, and frontmatter is kept as
+//! `yaml`/`toml` nodes. Trees from remark (`mdast-util-from-markdown` with
+//! the MDX extensions) can be read back, so remark plugins can run in
+//! JavaScript between parsing and codegen.
+
+pub use markdown::mdast::*;
+pub use markdown::unist::{Point, Position};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{ErrorCode, MdxError};
+
+/// Root with its unist `type`, which `Root` alone doesn't serialize
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "root")]
+struct TaggedRoot<'a> {
+    children: &'a [Node],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<&'a Position>,
+}
+
+/// Serialize a tree to mdast JSON
+pub fn to_json(root: &Root) -> String {
+    serde_json::to_string(&TaggedRoot {
+        children: &root.children,
+        position: root.position.as_ref(),
+    })
+    .expect("mdast serializes to JSON")
+}
+
+/// Read a tree from mdast JSON
+pub fn from_json(json: &str) -> Result<Root, Box<MdxError>> {
+    let mut value: Value = serde_json::from_str(json).map_err(invalid)?;
+    add_missing_stops(&mut value);
+    match serde_json::from_value(value).map_err(invalid)? {
+        Node::Root(root) => Ok(root),
+        _ => Err(invalid("expected a `root` node")),
+    }
+}
+
+fn invalid(reason: impl std::fmt::Display) -> Box<MdxError> {
+    Box::new(
+        MdxError::new(format!("Invalid mdast JSON: {}", reason))
+            .with_code(ErrorCode::InvalidMdast)
+            .with_suggestion(
+                "Pass an mdast `root` node, as produced by `bunny_mdx::parse` or remark.",
+            ),
+    )
+}
+
+/// Default markdown-rs' `_markdownRsStops` on expression and ESM nodes
+///
+/// Stops map offsets in an expression back to the document. Trees made in
+/// JavaScript don't have them; without stops, errors in those expressions are
+/// reported at the start of the node.
+fn add_missing_stops(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let has_stops = matches!(
+                object.get("type").and_then(Value::as_str),
+                Some(
+                    "mdxjsEsm"
+                        | "mdxFlowExpression"
+                        | "mdxTextExpression"
+                        | "mdxJsxExpressionAttribute"
+                        | "mdxJsxAttributeValueExpression"
+                )
+            );
+            if has_stops {
+                object
+                    .entry("_markdownRsStops")
+                    .or_insert_with(|| Value::Array(Vec::new()));
+            }
+            object.values_mut().for_each(add_missing_stops);
+        }
+        Value::Array(values) => values.iter_mut().for_each(add_missing_stops),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, MdxCompileOptions};
+
+    #[test]
+    fn test_json_follows_mdast() {
+        let mdx = "---\ntitle: Hi\n---\n\n# Hello *world*";
+        let root = parse(mdx, &MdxCompileOptions::new()).unwrap();
+        let json: Value = serde_json::from_str(&to_json(&root)).unwrap();
+
+        assert_eq!(json["type"], "root");
+        assert_eq!(json["children"][0]["type"], "yaml");
+        assert_eq!(json["children"][0]["value"], "title: Hi");
+        let heading = &json["children"][1];
+        assert_eq!(heading["type"], "heading");
+        assert_eq!(heading["depth"], 1);
+        assert_eq!(heading["children"][1]["type"], "emphasis");
+        assert_eq!(
+            heading["position"]["start"],
+            serde_json::json!({"line": 5, "column": 1, "offset": 19})
+        );
+
+        assert_eq!(from_json(&to_json(&root)).unwrap(), root);
+    }
+
+    #[test]
+    fn test_reads_trees_without_stops() {
+        // As produced by remark-mdx, with `data.estree` left out
+        let json = r#"{
+            "type": "root",
+            "children": [
+                {"type": "mdxjsEsm", "value": "export const a = 1"},
+                {"type": "paragraph", "children": [
+                    {"type": "mdxTextExpression", "value": "a"},
+                    {"type": "mdxJsxTextElement", "name": "B", "attributes": [
+                        {"type": "mdxJsxAttribute", "name": "c", "value": {
+                            "type": "mdxJsxAttributeValueExpression", "value": "a"
+                        }},
+                        {"type": "mdxJsxExpressionAttribute", "value": "...props"}
+                    ], "children": []}
+                ]}
+            ]
+        }"#;
+        let root = from_json(json).unwrap();
+        assert_eq!(root.children.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let err = from_json(r#"{"type": "paragraph", "children": []}"#).unwrap_err();
+        assert_eq!(err.code, Some(ErrorCode::InvalidMdast));
+        assert!(from_json(r#"{"type": "nope"}"#).is_err());
+        assert!(from_json("[").is_err());
+    }
+}
@@ -1,39 +1,99 @@
 //! Block-level node conversions (headings, paragraphs, blockquotes)
 
+use std::borrow::Cow;
+
 use anyhow::Result;
-use markdown::mdast::{Blockquote, Heading, Paragraph};
+use markdown::mdast::{Blockquote, Heading, Node, Paragraph};
 
 use super::children_to_jsx;
-use crate::codegen::{CodegenContext, JsValue};
+use crate::codegen::{CodegenContext, JsValue, JsxElement};
+use crate::expression::heading_id;
+use crate::plugins::{AnchorPosition, HeadingAnchor};
 
 /// Convert heading node to JSX
+///
+/// The id is the `{#custom-id}` suffix, if any, or the one planned by
+/// `HeadingIdPlugin` (see `CodegenContext::heading_ids`).
 pub fn heading_to_jsx(heading: &Heading, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let level = heading.depth;
-    let children = children_to_jsx(&heading.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.h{}, {{...props, children: {}}})",
-        level,
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let (content, custom_id) = split_custom_id(&heading.children);
+    let id = ctx.next_heading_id(custom_id);
+    let mut children = children_to_jsx(&content, ctx)?;
+    let mut element = JsxElement::component(&format!("h{}", level)).spread_props();
+    if let Some(id) = id {
+        if let Some(anchor) = &ctx.heading_anchor {
+            children = anchor_children(anchor, &id, children);
+        }
+        element = element.prop("id", JsValue::text(id));
+    }
+    let element = element.children(children).at(heading.position.as_ref());
+    Ok(Some(element.into_value()))
+}
+
+/// Heading content with the `HeadingIdPlugin` anchor link added
+fn anchor_children(anchor: &HeadingAnchor, id: &str, children: JsValue) -> JsValue {
+    let link = |children: JsValue| {
+        let mut element = JsxElement::component("a")
+            .spread_props()
+            .prop("href", JsValue::text(format!("#{}", id)));
+        if let Some(aria_label) = &anchor.aria_label {
+            element = element.prop("aria-label", JsValue::text(aria_label.as_str()));
+        }
+        if let Some(class_name) = &anchor.class_name {
+            element = element.prop("className", JsValue::text(class_name.as_str()));
+        }
+        element.children(children).into_value()
+    };
+    let content = || JsValue::text(anchor.content.as_str());
+    let mut children = match children {
+        JsValue::Array(children) => children,
+        JsValue::Text(text) if text.is_empty() => Vec::new(),
+        child => vec![child],
+    };
+    match anchor.position {
+        AnchorPosition::Wrap if children.len() == 1 => link(children.remove(0)),
+        AnchorPosition::Wrap => link(JsValue::array(children)),
+        AnchorPosition::Prepend => JsValue::array([vec![link(content())], children].concat()),
+        AnchorPosition::Append => JsValue::array([children, vec![link(content())]].concat()),
+    }
+}
+
+/// Split a trailing `{#custom-id}` off heading content
+///
+/// The space before the suffix is removed with it.
+pub fn split_custom_id(children: &[Node]) -> (Cow<'_, [Node]>, Option<&str>) {
+    let Some((Node::MdxTextExpression(expression), content)) = children.split_last() else {
+        return (Cow::Borrowed(children), None);
+    };
+    let Some(id) = heading_id(&expression.value) else {
+        return (Cow::Borrowed(children), None);
+    };
+    let mut content = content.to_vec();
+    if let Some(Node::Text(text)) = content.last_mut() {
+        text.value.truncate(text.value.trim_end().len());
+        if text.value.is_empty() {
+            content.pop();
+        }
+    }
+    (Cow::Owned(content), Some(id))
 }
 
 /// Convert paragraph node to JSX
 pub fn paragraph_to_jsx(para: &Paragraph, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let children = children_to_jsx(&para.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.p, {{...props, children: {}}})",
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("p")
+        .spread_props()
+        .children(children)
+        .at(para.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert blockquote node to JSX
 pub fn blockquote_to_jsx(quote: &Blockquote, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let children = children_to_jsx(&quote.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.blockquote, {{...props, children: {}}})",
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("blockquote")
+        .spread_props()
+        .children(children)
+        .at(quote.position.as_ref());
+    Ok(Some(element.into_value()))
 }
@@ -3,7 +3,7 @@
 use anyhow::Result;
 use markdown::mdast::Code;
 
-use crate::codegen::{escape_js_string, CodegenContext, JsValue};
+use crate::codegen::{CodegenContext, JsValue, JsxElement};
 
 /// Metadata extracted from code fence (e.g., ```ts title="foo.ts" {1,3-5})
 #[derive(Debug, Default, Clone)]
@@ -95,44 +95,53 @@ pub fn code_block_to_jsx(code: &Code, _ctx: &mut CodegenContext) -> Result<Optio
     };
 
     // Generate CodeBlock component call with metadata
-    let mut props_parts = Vec::new();
-    props_parts.push(format!("lang: \"{}\"", escape_js_string(lang)));
-    props_parts.push(format!("code: \"{}\"", escape_js_string(value)));
+    let mut code_block = JsxElement::component("CodeBlock")
+        .prop("lang", JsValue::text(lang))
+        .prop("code", JsValue::text(value.as_str()));
 
     // Add title if present
     if let Some(title) = &fence_meta.title {
-        props_parts.push(format!("title: \"{}\"", escape_js_string(title)));
+        code_block = code_block.prop("title", JsValue::text(title.as_str()));
     }
 
     // Add line highlights if present
     if !fence_meta.line_highlights.is_empty() {
-        let lines: Vec<String> = fence_meta
+        let lines: Vec<JsValue> = fence_meta
             .line_highlights
             .iter()
-            .map(|n| n.to_string())
+            .map(|n| JsValue::raw(n.to_string()))
             .collect();
-        props_parts.push(format!("highlightLines: [{}]", lines.join(", ")));
+        code_block = code_block.prop("highlightLines", JsValue::array(lines));
     }
 
     // Add word highlights if present
     if !fence_meta.word_highlights.is_empty() {
-        let words: Vec<String> = fence_meta
+        let words: Vec<JsValue> = fence_meta
             .word_highlights
             .iter()
-            .map(|w| format!("\"{}\"", escape_js_string(w)))
+            .map(|w| JsValue::text(w.as_str()))
             .collect();
-        props_parts.push(format!("highlightWords: [{}]", words.join(", ")));
+        code_block = code_block.prop("highlightWords", JsValue::array(words));
     }
 
-    props_parts.push("...props".to_string());
+    let code_block = code_block.spread_props().at(code.position.as_ref());
+
+    // Fallback: plain pre/code
+    let fallback = JsxElement::component("pre")
+        .spread_props()
+        .children(
+            JsxElement::component("code")
+                .prop("className", JsValue::text(format!("language-{}", lang)))
+                .children(JsValue::text(value.as_str()))
+                .into_value(),
+        )
+        .at(code.position.as_ref());
 
     // Generate JSX with conditional rendering:
     // If CodeBlock exists in components, use it; otherwise fall back to pre/code
-    let jsx = format!(
-        "(_components.CodeBlock ? _jsx(_components.CodeBlock, {{{}}}) : _jsx(_components.pre, {{...props, children: _jsx(_components.code, {{className: \"language-{}\", children: \"{}\"}})}}))",
-        props_parts.join(", "),
-        escape_js_string(lang),
-        escape_js_string(value)
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    Ok(Some(JsValue::conditional(
+        "_components.CodeBlock",
+        code_block.into_value(),
+        fallback.into_value(),
+    )))
 }
@@ -0,0 +1,818 @@
+//! Raw HTML node conversion
+//!
+//! MDX doesn't parse raw HTML itself, but `Html` nodes can still reach the
+//! compiler (from plugins or hand-built trees). They are handled according to
+//! [`RawHtml`]: dropped, converted to JSX elements, or rendered through
+//! `dangerouslySetInnerHTML`. HTML comments are always stripped.
+
+use anyhow::Result;
+use markdown::mdast::Html;
+use markdown::unist::Position;
+
+use crate::codegen::{escape_html, escape_js_string, CodegenContext, JsValue, JsxElement};
+use crate::RawHtml;
+
+/// Elements that never have children
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Attributes holding a URL, checked against the URL policy
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// Elements whose content is text up to the closing tag
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+
+/// Elements that can appear inside a paragraph, used to pick an inline
+/// wrapper for `dangerouslySetInnerHTML`
+const PHRASING_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "i", "img",
+    "input", "ins", "kbd", "label", "mark", "q", "s", "samp", "small", "span", "strong", "sub",
+    "sup", "time", "u", "var", "wbr",
+];
+
+/// HTML attributes whose React prop name differs
+const PROP_NAMES: &[(&str, &str)] = &[
+    ("accept-charset", "acceptCharset"),
+    ("accesskey", "accessKey"),
+    ("allowfullscreen", "allowFullScreen"),
+    ("autocomplete", "autoComplete"),
+    ("autofocus", "autoFocus"),
+    ("autoplay", "autoPlay"),
+    ("cellpadding", "cellPadding"),
+    ("cellspacing", "cellSpacing"),
+    ("charset", "charSet"),
+    ("class", "className"),
+    ("colspan", "colSpan"),
+    ("contenteditable", "contentEditable"),
+    ("crossorigin", "crossOrigin"),
+    ("datetime", "dateTime"),
+    ("enctype", "encType"),
+    ("for", "htmlFor"),
+    ("frameborder", "frameBorder"),
+    ("http-equiv", "httpEquiv"),
+    ("inputmode", "inputMode"),
+    ("maxlength", "maxLength"),
+    ("minlength", "minLength"),
+    ("novalidate", "noValidate"),
+    ("playsinline", "playsInline"),
+    ("readonly", "readOnly"),
+    ("referrerpolicy", "referrerPolicy"),
+    ("rowspan", "rowSpan"),
+    ("spellcheck", "spellCheck"),
+    ("srcset", "srcSet"),
+    ("tabindex", "tabIndex"),
+    ("usemap", "useMap"),
+];
+
+/// Convert a raw HTML node according to the `RawHtml` policy
+pub fn html_to_jsx(html: &Html, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
+    match ctx.raw_html {
+        RawHtml::Drop => Ok(None),
+        RawHtml::Jsx => {
+            let mut values = Vec::new();
+            for node in parse_fragment(&html.value) {
+                values.extend(node.into_value(html, ctx)?);
+            }
+            Ok(match values.len() {
+                0 => None,
+                1 => values.pop(),
+                _ => {
+                    ctx.uses_fragment = true;
+                    Some(
+                        JsxElement::new("_Fragment")
+                            .children(JsValue::array(values))
+                            .into_value(),
+                    )
+                }
+            })
+        }
+        RawHtml::DangerouslySetInnerHtml => {
+            let content = strip_comments(&html.value);
+            if content.trim().is_empty() {
+                return Ok(None);
+            }
+
+            // A fragment starting with text or an inline element can sit in a
+            // paragraph, so it gets an inline wrapper
+            let inline = match parse_fragment(&content).first() {
+                Some(HtmlNode::Element { name, .. }) => {
+                    PHRASING_ELEMENTS.contains(&name.to_ascii_lowercase().as_str())
+                }
+                _ => true,
+            };
+            let wrapper = if inline { "span" } else { "div" };
+            let inner_html =
+                JsValue::object(vec![("__html".to_string(), JsValue::text(content.trim()))]);
+            Ok(Some(
+                JsxElement::new(format!("\"{}\"", wrapper))
+                    .prop("dangerouslySetInnerHTML", inner_html)
+                    .at(html.position.as_ref())
+                    .into_value(),
+            ))
+        }
+    }
+}
+
+/// Raw HTML for the static HTML backend, according to the `RawHtml` policy
+///
+/// `Jsx` keeps what the JSX conversion keeps: comments, scripts and unsafe
+/// attributes are removed, and URLs follow the URL policy.
+pub fn html_to_html(html: &Html, ctx: &CodegenContext) -> Result<String> {
+    match ctx.raw_html {
+        RawHtml::Drop => Ok(String::new()),
+        RawHtml::Jsx => {
+            let mut out = String::new();
+            for node in parse_fragment(&html.value) {
+                node.write_html(&mut out, false, html, ctx)?;
+            }
+            Ok(out)
+        }
+        RawHtml::DangerouslySetInnerHtml => Ok(strip_comments(&html.value)),
+    }
+}
+
+/// Whether `name` is a `<script>` element, which is left out of raw HTML
+/// and of HTML output
+pub(crate) fn is_script(name: &str) -> bool {
+    name.eq_ignore_ascii_case("script")
+}
+
+/// Whether an attribute of raw HTML or of an element in HTML output is left
+/// out: event handlers in any case (`onclick`, `ONERROR`) and `srcdoc`,
+/// which holds a whole document
+pub(crate) fn is_unsafe_attribute(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.starts_with("on") || lower == "srcdoc"
+}
+
+/// Attribute value with URLs (`href`, `src`, `action`, `formaction`,
+/// `poster`, `srcset`) checked against the URL policy, `None` when the
+/// policy strips it
+pub(crate) fn sanitize_attribute(
+    name: &str,
+    value: &str,
+    ctx: &CodegenContext,
+    position: Option<&Position>,
+) -> Result<Option<String>> {
+    let lower = name.to_ascii_lowercase();
+    if URL_ATTRIBUTES.contains(&lower.as_str()) {
+        return ctx.sanitize_url(value, position);
+    }
+    if lower != "srcset" {
+        return Ok(Some(value.to_string()));
+    }
+
+    // Each candidate is a URL and an optional descriptor (`a.png 2x`)
+    let mut candidates = Vec::new();
+    for candidate in value.split(',') {
+        let candidate = candidate.trim();
+        let (url, descriptor) = candidate
+            .split_once(char::is_whitespace)
+            .unwrap_or((candidate, ""));
+        if url.is_empty() {
+            continue;
+        }
+        if let Some(url) = ctx.sanitize_url(url, position)? {
+            candidates.push(
+                format!("{} {}", url, descriptor.trim())
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+    }
+    Ok((!candidates.is_empty()).then(|| candidates.join(", ")))
+}
+
+/// HTML attribute for a React prop name (`className` → `class`)
+pub(crate) fn html_attribute_name(prop: &str) -> &str {
+    PROP_NAMES
+        .iter()
+        .find(|(_, react)| *react == prop)
+        .map_or(prop, |(html, _)| html)
+}
+
+/// A parsed HTML node
+#[derive(Debug, PartialEq)]
+enum HtmlNode {
+    Element {
+        name: String,
+        attributes: Attributes,
+        children: Vec<HtmlNode>,
+    },
+    Text(String),
+}
+
+impl HtmlNode {
+    /// JSX for this node, `None` for whitespace between tags and scripts
+    ///
+    /// Server rendering writes the elements out as they are, so scripts and
+    /// unsafe attributes are removed as in HTML output.
+    fn into_value(self, html: &Html, ctx: &CodegenContext) -> Result<Option<JsValue>> {
+        match self {
+            HtmlNode::Text(text) if text.trim().is_empty() && text.contains('\n') => Ok(None),
+            HtmlNode::Text(text) => Ok(Some(JsValue::text(text))),
+            HtmlNode::Element { name, .. } if is_script(&name) => Ok(None),
+            HtmlNode::Element {
+                name,
+                attributes,
+                children,
+            } => {
+                let mut element = JsxElement::new(format!("\"{}\"", escape_js_string(&name)))
+                    .at(html.position.as_ref());
+                for (name, value) in attributes {
+                    if is_unsafe_attribute(&name) {
+                        continue;
+                    }
+                    let value = match value {
+                        Some(value) => {
+                            match sanitize_attribute(&name, &value, ctx, html.position.as_ref())? {
+                                Some(value) => Some(value),
+                                None => continue,
+                            }
+                        }
+                        None => None,
+                    };
+                    let (name, value) = jsx_prop(&name, value);
+                    element = element.prop(name, value);
+                }
+
+                let mut values = Vec::new();
+                for child in children {
+                    values.extend(child.into_value(html, ctx)?);
+                }
+                match values.len() {
+                    0 => {}
+                    1 => element = element.children(values.remove(0)),
+                    _ => element = element.children(JsValue::array(values)),
+                }
+                Ok(Some(element.into_value()))
+            }
+        }
+    }
+}
+
+impl HtmlNode {
+    /// Write this node as HTML; `raw` is set inside `<style>`, whose text
+    /// isn't escaped
+    fn write_html(
+        &self,
+        out: &mut String,
+        raw: bool,
+        html: &Html,
+        ctx: &CodegenContext,
+    ) -> Result<()> {
+        match self {
+            HtmlNode::Text(text) if raw => out.push_str(text),
+            HtmlNode::Text(text) => out.push_str(&escape_html(text)),
+            HtmlNode::Element {
+                name,
+                attributes,
+                children,
+            } => {
+                if is_script(name) {
+                    return Ok(());
+                }
+                out.push('<');
+                out.push_str(name);
+                for (name, value) in attributes {
+                    if is_unsafe_attribute(name) {
+                        continue;
+                    }
+                    let value = match value {
+                        Some(value) => {
+                            match sanitize_attribute(name, value, ctx, html.position.as_ref())? {
+                                Some(value) => Some(value),
+                                None => continue,
+                            }
+                        }
+                        None => None,
+                    };
+                    out.push(' ');
+                    out.push_str(name);
+                    if let Some(value) = value {
+                        out.push_str(&format!("=\"{}\"", escape_html(&value)));
+                    }
+                }
+                let lower = name.to_ascii_lowercase();
+                if VOID_ELEMENTS.contains(&lower.as_str()) {
+                    out.push_str(" />");
+                    return Ok(());
+                }
+                out.push('>');
+                for child in children {
+                    child.write_html(out, lower == "style", html, ctx)?;
+                }
+                out.push_str(&format!("</{}>", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// React prop for an HTML attribute (`class` becomes `className`, inline
+/// `style` strings become style objects)
+fn jsx_prop(name: &str, value: Option<String>) -> (String, JsValue) {
+    let lower = name.to_ascii_lowercase();
+    let value = match value {
+        Some(value) if lower == "style" => style_object(&value),
+        Some(value) => JsValue::text(value),
+        None => JsValue::bool(true),
+    };
+    let name = PROP_NAMES
+        .iter()
+        .find(|(html, _)| *html == lower)
+        .map_or(name, |(_, react)| react);
+    (name.to_string(), value)
+}
+
+/// Convert an inline `style` string to a React style object
+///
+/// `background-color: red; --gap: 1px` becomes
+/// `{backgroundColor: "red", "--gap": "1px"}`.
+fn style_object(style: &str) -> JsValue {
+    let properties = split_declarations(style)
+        .into_iter()
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            let (property, value) = (property.trim(), value.trim());
+            if property.is_empty() || value.is_empty() {
+                return None;
+            }
+            Some((style_property_name(property), JsValue::text(value)))
+        })
+        .collect();
+    JsValue::object(properties)
+}
+
+/// Split CSS declarations on `;`, ignoring ones inside quotes or parentheses
+/// (as in `url("a;b")`)
+fn split_declarations(style: &str) -> Vec<&str> {
+    let mut declarations = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (index, ch) in style.char_indices() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ';') if depth == 0 => {
+                declarations.push(&style[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.push(&style[start..]);
+    declarations
+}
+
+/// `background-color` → `backgroundColor`, `-webkit-x` → `WebkitX`,
+/// `-ms-x` → `msX`; custom properties (`--x`) are kept as-is
+fn style_property_name(property: &str) -> String {
+    if property.starts_with("--") {
+        return property.to_string();
+    }
+
+    let property = property.to_ascii_lowercase();
+    let property = match property.strip_prefix("-ms-") {
+        Some(rest) => format!("ms-{}", rest),
+        None => property,
+    };
+
+    let mut name = String::with_capacity(property.len());
+    let mut upper = false;
+    for ch in property.chars() {
+        if ch == '-' {
+            upper = true;
+        } else if upper {
+            name.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            name.push(ch);
+        }
+    }
+    name
+}
+
+/// Remove `<!-- comments -->` (an unclosed comment runs to the end)
+fn strip_comments(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<!--") {
+        result.push_str(&rest[..start]);
+        rest = match rest[start + 4..].find("-->") {
+            Some(end) => &rest[start + 4 + end + 3..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Parse an HTML fragment into a tree
+///
+/// This is a forgiving parser for the small fragments found in markdown:
+/// comments, doctypes and processing instructions are skipped, closing tags
+/// without an open element are ignored, and elements left open (as with an
+/// inline `<b>` whose `</b>` is in a later node) are closed at the end.
+fn parse_fragment(html: &str) -> Vec<HtmlNode> {
+    // Open elements: name, attributes, children
+    type Open = (String, Attributes, Vec<HtmlNode>);
+
+    fn close(stack: &mut Vec<Open>, root: &mut Vec<HtmlNode>) {
+        if let Some((name, attributes, children)) = stack.pop() {
+            let element = HtmlNode::Element {
+                name,
+                attributes,
+                children,
+            };
+            match stack.last_mut() {
+                Some((_, _, children)) => children.push(element),
+                None => root.push(element),
+            }
+        }
+    }
+
+    fn push(stack: &mut [Open], root: &mut Vec<HtmlNode>, node: HtmlNode) {
+        let nodes = match stack.last_mut() {
+            Some((_, _, children)) => children,
+            None => root,
+        };
+        match (nodes.last_mut(), node) {
+            (Some(HtmlNode::Text(previous)), HtmlNode::Text(text)) => previous.push_str(&text),
+            (_, node) => nodes.push(node),
+        }
+    }
+
+    let mut root = Vec::new();
+    let mut stack: Vec<Open> = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            push(&mut stack, &mut root, HtmlNode::Text(decode_entities(rest)));
+            break;
+        };
+        if start > 0 {
+            push(
+                &mut stack,
+                &mut root,
+                HtmlNode::Text(decode_entities(&rest[..start])),
+            );
+            rest = &rest[start..];
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            push(
+                &mut stack,
+                &mut root,
+                HtmlNode::Text(cdata[..end].to_string()),
+            );
+            rest = cdata.get(end + 3..).unwrap_or("");
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some((name, after)) = rest.strip_prefix("</").and_then(tag_name) {
+            rest = after.find('>').map_or("", |end| &after[end + 1..]);
+            let lower = name.to_ascii_lowercase();
+            if let Some(index) = stack
+                .iter()
+                .rposition(|(open, ..)| open.to_ascii_lowercase() == lower)
+            {
+                while stack.len() > index {
+                    close(&mut stack, &mut root);
+                }
+            }
+        } else if let Some((name, after)) = tag_name(&rest[1..]) {
+            let Some((attributes, self_closing, after)) = parse_attributes(after) else {
+                // Unterminated tag: keep it as text
+                push(&mut stack, &mut root, HtmlNode::Text(decode_entities(rest)));
+                break;
+            };
+            rest = after;
+
+            let lower = name.to_ascii_lowercase();
+            if self_closing || VOID_ELEMENTS.contains(&lower.as_str()) {
+                let element = HtmlNode::Element {
+                    name: name.to_string(),
+                    attributes,
+                    children: Vec::new(),
+                };
+                push(&mut stack, &mut root, element);
+            } else if RAW_TEXT_ELEMENTS.contains(&lower.as_str()) {
+                let end = find_closing_tag(rest, &lower).unwrap_or(rest.len());
+                let text = &rest[..end];
+                let children = if text.is_empty() {
+                    Vec::new()
+                } else if matches!(lower.as_str(), "textarea" | "title") {
+                    vec![HtmlNode::Text(decode_entities(text))]
+                } else {
+                    vec![HtmlNode::Text(text.to_string())]
+                };
+                let element = HtmlNode::Element {
+                    name: name.to_string(),
+                    attributes,
+                    children,
+                };
+                push(&mut stack, &mut root, element);
+                rest = &rest[end..];
+                rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            } else {
+                stack.push((name.to_string(), attributes, Vec::new()));
+            }
+        } else {
+            // A `<` that doesn't start a tag
+            push(&mut stack, &mut root, HtmlNode::Text("<".to_string()));
+            rest = &rest[1..];
+        }
+    }
+
+    while !stack.is_empty() {
+        close(&mut stack, &mut root);
+    }
+    root
+}
+
+/// Split a tag name off the start of `input`
+fn tag_name(input: &str) -> Option<(&str, &str)> {
+    if !input.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let end = input
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | ':' | '.' | '_')))
+        .unwrap_or(input.len());
+    Some((&input[..end], &input[end..]))
+}
+
+/// Attribute names and values (`None` for boolean attributes like `hidden`)
+type Attributes = Vec<(String, Option<String>)>;
+
+/// Parse attributes up to the end of a start tag
+///
+/// Returns the attributes, whether the tag is self-closing (`/>`), and the
+/// input after the tag, or `None` when the tag isn't terminated.
+fn parse_attributes(mut input: &str) -> Option<(Attributes, bool, &str)> {
+    let mut attributes = Vec::new();
+    loop {
+        input = input.trim_start();
+        if let Some(after) = input.strip_prefix("/>") {
+            return Some((attributes, true, after));
+        }
+        if let Some(after) = input.strip_prefix('>') {
+            return Some((attributes, false, after));
+        }
+        if let Some(after) = input.strip_prefix('/') {
+            input = after;
+            continue;
+        }
+        if input.is_empty() {
+            return None;
+        }
+
+        let end = input
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(input.len());
+        let name = &input[..end];
+        input = input[end..].trim_start();
+
+        let value = match input.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, rest) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let close = after[1..].find(quote)?;
+                        (&after[1..close + 1], &after[close + 2..])
+                    }
+                    _ => {
+                        let end = after
+                            .find(|c: char| c.is_whitespace() || c == '>')
+                            .unwrap_or(after.len());
+                        (&after[..end], &after[end..])
+                    }
+                };
+                input = rest;
+                Some(decode_entities(value))
+            }
+            None => None,
+        };
+        attributes.push((name.to_string(), value));
+    }
+}
+
+/// Byte offset of `</name` (case-insensitive) in `input`
+fn find_closing_tag(input: &str, name: &str) -> Option<usize> {
+    let lower = input.to_ascii_lowercase();
+    lower.find(&format!("</{}", name))
+}
+
+/// Decode character references (`&amp;`, `&#169;`, `&#xA9;`)
+///
+/// Only common named references are known; others are kept as written.
+fn decode_entities(text: &str) -> String {
+    const NAMED: &[(&str, &str)] = &[
+        ("amp", "&"),
+        ("lt", "<"),
+        ("gt", ">"),
+        ("quot", "\""),
+        ("apos", "'"),
+        ("nbsp", "\u{a0}"),
+        ("copy", "©"),
+        ("reg", "®"),
+        ("trade", "™"),
+        ("hellip", "…"),
+        ("mdash", "—"),
+        ("ndash", "–"),
+        ("lsquo", "‘"),
+        ("rsquo", "’"),
+        ("ldquo", "“"),
+        ("rdquo", "”"),
+        ("times", "×"),
+    ];
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let reference = &rest[1..end];
+            let value = if let Some(number) = reference.strip_prefix('#') {
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                };
+                code.and_then(char::from_u32).map(String::from)
+            } else {
+                NAMED
+                    .iter()
+                    .find(|(name, _)| *name == reference)
+                    .map(|(_, value)| value.to_string())
+            };
+            value.map(|value| (value, end + 1))
+        });
+        match decoded {
+            Some((value, length)) => {
+                result.push_str(&value);
+                rest = &rest[length..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UrlPolicy, UrlViolation};
+
+    fn element(
+        name: &str,
+        attributes: &[(&str, Option<&str>)],
+        children: Vec<HtmlNode>,
+    ) -> HtmlNode {
+        HtmlNode::Element {
+            name: name.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+                .collect(),
+            children,
+        }
+    }
+
+    fn text(value: &str) -> HtmlNode {
+        HtmlNode::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_parse_fragment() {
+        assert_eq!(
+            parse_fragment("<div class=\"x\" hidden>a &amp; <b>b</b><br></div><!-- c -->"),
+            vec![element(
+                "div",
+                &[("class", Some("x")), ("hidden", None)],
+                vec![
+                    text("a & "),
+                    element("b", &[], vec![text("b")]),
+                    element("br", &[], vec![]),
+                ],
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_unbalanced_fragments() {
+        // Open elements are closed at the end, stray closing tags ignored
+        assert_eq!(parse_fragment("<b>"), vec![element("b", &[], vec![])]);
+        assert_eq!(parse_fragment("</b>"), vec![]);
+        assert_eq!(
+            parse_fragment("<p><i>a</p>b"),
+            vec![
+                element("p", &[], vec![element("i", &[], vec![text("a")])]),
+                text("b")
+            ]
+        );
+        assert_eq!(parse_fragment("a < b"), vec![text("a < b")]);
+    }
+
+    #[test]
+    fn test_parse_raw_text_elements() {
+        assert_eq!(
+            parse_fragment("<style>a > b { color: red }</style>"),
+            vec![element("style", &[], vec![text("a > b { color: red }")])]
+        );
+    }
+
+    #[test]
+    fn test_style_object() {
+        let text = |key: &str, value: &str| (key.to_string(), JsValue::text(value));
+        assert_eq!(
+            style_object(
+                "background-color: red; --gap: 1px; -webkit-transition: none; -ms-transform: none;"
+            ),
+            JsValue::object(vec![
+                text("backgroundColor", "red"),
+                text("--gap", "1px"),
+                text("WebkitTransition", "none"),
+                text("msTransform", "none"),
+            ])
+        );
+        assert_eq!(
+            style_object("background: url(\"a;b.png\")"),
+            JsValue::object(vec![text("background", "url(\"a;b.png\")")])
+        );
+    }
+
+    #[test]
+    fn test_html_to_html() {
+        let html = Html {
+            value: "<p class=\"a\" onclick=\"x()\">1 &lt; 2<br><script>x()</script></p><!-- c --><style>a > b {}</style>".to_string(),
+            position: None,
+        };
+        let mut ctx = CodegenContext::new();
+        assert_eq!(
+            html_to_html(&html, &ctx).unwrap(),
+            "<p class=\"a\">1 &lt; 2<br /></p><style>a > b {}</style>"
+        );
+        ctx.raw_html = RawHtml::DangerouslySetInnerHtml;
+        assert_eq!(
+            html_to_html(&html, &ctx).unwrap(),
+            "<p class=\"a\" onclick=\"x()\">1 &lt; 2<br><script>x()</script></p><style>a > b {}</style>"
+        );
+        ctx.raw_html = RawHtml::Drop;
+        assert_eq!(html_to_html(&html, &ctx).unwrap(), "");
+    }
+
+    #[test]
+    fn test_html_to_html_removes_unsafe_attributes() {
+        let html = Html {
+            value: concat!(
+                "<SCRIPT>x()</SCRIPT><img src=\"javascript:x()\" ONERROR=\"x()\" ",
+                "srcset=\"/a.png 1x, javascript:x() 2x\"><iframe srcdoc=\"<script>x()</script>\"></iframe>",
+                "<form action=\"javascript:x()\"><button formaction=\"/ok\">Go</button></form>"
+            )
+            .to_string(),
+            position: None,
+        };
+        let mut ctx = CodegenContext::new();
+        ctx.url_policy = Some(UrlPolicy::new().on_violation(UrlViolation::Strip));
+        assert_eq!(
+            html_to_html(&html, &ctx).unwrap(),
+            "<img srcset=\"/a.png 1x\" /><iframe></iframe><form><button formaction=\"/ok\">Go</button></form>"
+        );
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(strip_comments("a<!-- b -->c<!-- d"), "ac");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("&lt;a&gt; &#169; &#xA9; &unknown; &"),
+            "<a> © © &unknown; &"
+        );
+    }
+}
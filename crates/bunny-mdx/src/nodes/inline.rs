@@ -2,85 +2,166 @@
 
 use anyhow::Result;
 use markdown::mdast::{
-    Delete, Emphasis, FootnoteDefinition, FootnoteReference, Image, InlineCode, InlineMath, Link,
-    Math, Strong,
+    Delete, Emphasis, FootnoteDefinition, FootnoteReference, Image, ImageReference, InlineCode,
+    InlineMath, Link, LinkReference, Math, Node, ReferenceKind, Strong,
 };
 
 use super::children_to_jsx;
-use crate::codegen::{escape_js_string, CodegenContext, JsValue};
+use crate::codegen::{CodegenContext, JsValue, JsxElement};
+use crate::diagnostic::Diagnostic;
 
 /// Convert inline code node to JSX
 pub fn inline_code_to_jsx(code: &InlineCode, _ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
-    let escaped = escape_js_string(&code.value);
-    let jsx = format!(
-        "_jsx(_components.code, {{...props, children: \"{}\"}})",
-        escaped
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("code")
+        .spread_props()
+        .children(JsValue::text(code.value.as_str()))
+        .at(code.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert emphasis node to JSX
 pub fn emphasis_to_jsx(emph: &Emphasis, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let children = children_to_jsx(&emph.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.em, {{...props, children: {}}})",
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("em")
+        .spread_props()
+        .children(children)
+        .at(emph.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert strong node to JSX
 pub fn strong_to_jsx(strong: &Strong, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let children = children_to_jsx(&strong.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.strong, {{...props, children: {}}})",
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("strong")
+        .spread_props()
+        .children(children)
+        .at(strong.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert link node to JSX
 pub fn link_to_jsx(link: &Link, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
-    let href = escape_js_string(&link.url);
+    let href = ctx.sanitize_url(&link.url, link.position.as_ref())?;
     let children = children_to_jsx(&link.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.a, {{...props, href: \"{}\", children: {}}})",
-        href,
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let mut element = JsxElement::component("a").spread_props();
+    if let Some(href) = href {
+        element = element.prop("href", JsValue::text(href));
+    }
+    let element = element.children(children).at(link.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert image node to JSX
-pub fn image_to_jsx(image: &Image, _ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
-    let src = escape_js_string(&image.url);
-    let alt = escape_js_string(&image.alt);
+pub fn image_to_jsx(image: &Image, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
+    let mut element = JsxElement::component("img").spread_props();
+    if let Some(src) = ctx.sanitize_url(&image.url, image.position.as_ref())? {
+        element = element.prop("src", JsValue::text(src));
+    }
+    element = element.prop("alt", JsValue::text(image.alt.as_str()));
 
     // Include title attribute if present (shows as tooltip on hover)
-    let jsx = if let Some(title) = &image.title {
-        let title = escape_js_string(title);
-        format!(
-            "_jsx(_components.img, {{...props, src: \"{}\", alt: \"{}\", title: \"{}\"}})",
-            src, alt, title
-        )
-    } else {
-        format!(
-            "_jsx(_components.img, {{...props, src: \"{}\", alt: \"{}\"}})",
-            src, alt
-        )
+    if let Some(title) = &image.title {
+        element = element.prop("title", JsValue::text(title.as_str()));
+    }
+
+    Ok(Some(element.at(image.position.as_ref()).into_value()))
+}
+
+/// Convert link reference (`[text][label]`) to JSX
+///
+/// Resolves the reference against the document's definitions and renders it
+/// exactly like an inline link. Unresolved references are reported as a
+/// diagnostic and rendered back as their literal source text.
+pub fn link_reference_to_jsx(
+    link_ref: &LinkReference,
+    ctx: &mut CodegenContext,
+) -> Result<Option<JsValue>> {
+    let Some(definition) = ctx.definition(&link_ref.identifier).cloned() else {
+        report_unresolved_reference(&link_ref.identifier, link_ref.position.as_ref(), ctx);
+
+        let mut children = vec![Node::Text(markdown::mdast::Text {
+            value: "[".to_string(),
+            position: None,
+        })];
+        children.extend(link_ref.children.iter().cloned());
+        children.push(Node::Text(markdown::mdast::Text {
+            value: format!(
+                "]{}",
+                reference_suffix(&link_ref.reference_kind, link_ref.label.as_deref())
+            ),
+            position: None,
+        }));
+        return Ok(Some(children_to_jsx(&children, ctx)?));
     };
 
-    Ok(Some(JsValue::raw(jsx)))
+    let link = Link {
+        children: link_ref.children.clone(),
+        position: link_ref.position.clone(),
+        url: definition.url,
+        title: definition.title,
+    };
+    link_to_jsx(&link, ctx)
+}
+
+/// Convert image reference (`![alt][label]`) to JSX
+///
+/// Resolved references render exactly like inline images; unresolved ones
+/// are reported as a diagnostic and rendered as literal text.
+pub fn image_reference_to_jsx(
+    image_ref: &ImageReference,
+    ctx: &mut CodegenContext,
+) -> Result<Option<JsValue>> {
+    let Some(definition) = ctx.definition(&image_ref.identifier).cloned() else {
+        report_unresolved_reference(&image_ref.identifier, image_ref.position.as_ref(), ctx);
+
+        return Ok(Some(JsValue::text(format!(
+            "![{}]{}",
+            image_ref.alt,
+            reference_suffix(&image_ref.reference_kind, image_ref.label.as_deref())
+        ))));
+    };
+
+    let image = Image {
+        position: image_ref.position.clone(),
+        alt: image_ref.alt.clone(),
+        url: definition.url,
+        title: definition.title,
+    };
+    image_to_jsx(&image, ctx)
+}
+
+/// Source text that follows the `[text]` part of a reference
+pub(crate) fn reference_suffix(kind: &ReferenceKind, label: Option<&str>) -> String {
+    match kind {
+        ReferenceKind::Full => format!("[{}]", label.unwrap_or_default()),
+        ReferenceKind::Collapsed => "[]".to_string(),
+        ReferenceKind::Shortcut => String::new(),
+    }
+}
+
+pub(crate) fn report_unresolved_reference(
+    identifier: &str,
+    position: Option<&markdown::unist::Position>,
+    ctx: &mut CodegenContext,
+) {
+    tracing::warn!(identifier = identifier, "Unresolved link reference in MDX");
+    ctx.push_diagnostic(
+        Diagnostic::warning(
+            "unresolved-reference",
+            format!("No definition found for reference `[{}]`", identifier),
+        )
+        .with_position(position),
+    );
 }
 
 /// Convert delete (strikethrough) node to JSX
 pub fn delete_to_jsx(del: &Delete, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let children = children_to_jsx(&del.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.del, {{...props, children: {}}})",
-        children.to_js()
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("del")
+        .spread_props()
+        .children(children)
+        .at(del.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert footnote reference to JSX
@@ -90,11 +171,15 @@ pub fn footnote_reference_to_jsx(
 ) -> Result<Option<JsValue>> {
     let id = &footnote_ref.identifier;
     let label = footnote_ref.label.as_deref().unwrap_or(id);
-    let jsx = format!(
-        "_jsx(_components.sup, {{...props, children: _jsx(_components.a, {{href: \"#fn-{}\", id: \"fnref-{}\", children: \"{}\"}})}})",
-        id, id, escape_js_string(label)
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let anchor = JsxElement::component("a")
+        .prop("href", JsValue::text(format!("#fn-{}", id)))
+        .prop("id", JsValue::text(format!("fnref-{}", id)))
+        .children(JsValue::text(label));
+    let element = JsxElement::component("sup")
+        .spread_props()
+        .children(anchor.into_value())
+        .at(footnote_ref.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert footnote definition to JSX
@@ -105,21 +190,32 @@ pub fn footnote_definition_to_jsx(
     let id = &footnote_def.identifier;
     let label = footnote_def.label.as_deref().unwrap_or(id);
     let children = children_to_jsx(&footnote_def.children, ctx)?;
-    let jsx = format!(
-        "_jsx(_components.div, {{...props, id: \"fn-{}\", children: _jsx(_components.p, {{children: [\"{}\", \". \", {}, \" \", _jsx(_components.a, {{href: \"#fnref-{}\", children: \"\u{21a9}\"}})]}})}}))",
-        id, escape_js_string(label), children.to_js(), id
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let backref = JsxElement::component("a")
+        .prop("href", JsValue::text(format!("#fnref-{}", id)))
+        .children(JsValue::text("\u{21a9}"));
+    let content = JsxElement::component("p").children(JsValue::array(vec![
+        JsValue::text(label),
+        JsValue::text(". "),
+        children,
+        JsValue::text(" "),
+        backref.into_value(),
+    ]));
+    let element = JsxElement::component("div")
+        .spread_props()
+        .prop("id", JsValue::text(format!("fn-{}", id)))
+        .children(content.into_value())
+        .at(footnote_def.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert block math node to JSX
 pub fn math_to_jsx(math: &Math, _ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
-    let value = escape_js_string(&math.value);
-    let jsx = format!(
-        "_jsx(_components.span, {{...props, className: \"math math-display\", children: \"{}\"}})",
-        value
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("span")
+        .spread_props()
+        .prop("className", JsValue::text("math math-display"))
+        .children(JsValue::text(math.value.as_str()))
+        .at(math.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert inline math node to JSX
@@ -127,10 +223,10 @@ pub fn inline_math_to_jsx(
     inline_math: &InlineMath,
     _ctx: &mut CodegenContext,
 ) -> Result<Option<JsValue>> {
-    let value = escape_js_string(&inline_math.value);
-    let jsx = format!(
-        "_jsx(_components.span, {{...props, className: \"math math-inline\", children: \"{}\"}})",
-        value
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("span")
+        .spread_props()
+        .prop("className", JsValue::text("math math-inline"))
+        .children(JsValue::text(inline_math.value.as_str()))
+        .at(inline_math.position.as_ref());
+    Ok(Some(element.into_value()))
 }
@@ -4,17 +4,17 @@ use anyhow::Result;
 use markdown::mdast::{List, ListItem};
 
 use super::{children_to_jsx, children_to_jsx_array};
-use crate::codegen::{CodegenContext, JsValue};
+use crate::codegen::{CodegenContext, JsValue, JsxElement};
 
 /// Convert list node to JSX
 pub fn list_to_jsx(list: &List, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
     let tag = if list.ordered { "ol" } else { "ul" };
     let children = children_to_jsx_array(&list.children, ctx, true)?;
-    let jsx = format!(
-        "_jsxs(_components.{}, {{...props, children: [{}]}})",
-        tag, children
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component(tag)
+        .spread_props()
+        .children(JsValue::array(children))
+        .at(list.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert list item node to JSX
@@ -26,52 +26,31 @@ pub fn list_item_to_jsx(
     let children_value = children_to_jsx(&item.children, ctx)?;
 
     // Task list item support: render a disabled checkbox when `checked` is present
-    let jsx;
-    if let Some(checked) = item.checked {
+    let children = if let Some(checked) = item.checked {
         // Build children array: [<input .../>, " ", ...original children]
         let task_id = ctx.next_key();
-        let checkbox = format!(
-            "_jsx(_components.input, {{type: \"checkbox\", checked: {}, \"data-task-id\": \"{}\", onChange: _handleTaskToggle}})",
-            if checked { "true" } else { "false" },
-            task_id
-        );
-        let mut parts: Vec<String> = Vec::new();
-        parts.push(checkbox);
-        parts.push("\" \"".to_string());
+        let checkbox = JsxElement::component("input")
+            .prop("type", JsValue::text("checkbox"))
+            .prop("checked", JsValue::raw(checked.to_string()))
+            .prop("data-task-id", JsValue::text(task_id))
+            .prop("onChange", JsValue::raw("_handleTaskToggle"));
+        let mut parts = vec![checkbox.into_value(), JsValue::text(" ")];
         match children_value {
-            JsValue::Array(items) => {
-                parts.extend(items.into_iter().map(|v| v.to_js()));
-            }
-            other => parts.push(other.to_js()),
-        }
-        let children = parts.join(", ");
-        if in_list {
-            let key = ctx.next_key();
-            jsx = format!(
-                "_jsxs(_components.li, {{...props, children: [{}]}}, \"{}\")",
-                children, key
-            );
-        } else {
-            jsx = format!(
-                "_jsxs(_components.li, {{...props, children: [{}]}})",
-                children
-            );
+            JsValue::Array(items) => parts.extend(items),
+            other => parts.push(other),
         }
+        JsValue::array(parts)
     } else {
         // Regular list item
-        if in_list {
-            let key = ctx.next_key();
-            jsx = format!(
-                "_jsx(_components.li, {{...props, children: {}}}, \"{}\")",
-                children_value.to_js(),
-                key
-            );
-        } else {
-            jsx = format!(
-                "_jsx(_components.li, {{...props, children: {}}})",
-                children_value.to_js()
-            );
-        }
+        children_value
+    };
+
+    let mut element = JsxElement::component("li")
+        .spread_props()
+        .children(children)
+        .at(item.position.as_ref());
+    if in_list {
+        element = element.key(ctx.next_key());
     }
-    Ok(Some(JsValue::raw(jsx)))
+    Ok(Some(element.into_value()))
 }
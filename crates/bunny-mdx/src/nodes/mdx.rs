@@ -2,12 +2,24 @@
 
 use anyhow::Result;
 use markdown::mdast::{MdxJsxFlowElement, MdxJsxTextElement, Node};
+use markdown::unist::Position;
 
 use super::children_to_jsx;
-use crate::codegen::{escape_js_string, CodegenContext, JsValue};
+use crate::codegen::{escape_js_string, CodegenContext, JsValue, JsxElement};
+use crate::expression::is_comment_only;
+
+/// Convert an MDX expression (`{...}` in prose) to JSX
+///
+/// Comment-only expressions like `{/* note */}` render nothing.
+pub fn expression_to_jsx(value: &str, position: Option<&Position>) -> Option<JsValue> {
+    if is_comment_only(value) {
+        return None;
+    }
+    Some(JsValue::expression(value.to_string(), position))
+}
 
 /// Check if component name should use _components map
-fn should_use_components_map(name: &str) -> bool {
+pub(crate) fn should_use_components_map(name: &str) -> bool {
     if name.is_empty() {
         return false;
     }
@@ -58,7 +70,13 @@ pub fn jsx_flow_element_to_string(
     element: &MdxJsxFlowElement,
     ctx: &mut CodegenContext,
 ) -> Result<JsValue> {
-    jsx_element_to_string(&element.name, &element.attributes, &element.children, ctx)
+    jsx_element_to_string(
+        &element.name,
+        &element.attributes,
+        &element.children,
+        element.position.as_ref(),
+        ctx,
+    )
 }
 
 /// Convert MDX JSX text element to JsValue
@@ -66,7 +84,13 @@ pub fn jsx_text_element_to_string(
     element: &MdxJsxTextElement,
     ctx: &mut CodegenContext,
 ) -> Result<JsValue> {
-    jsx_element_to_string(&element.name, &element.attributes, &element.children, ctx)
+    jsx_element_to_string(
+        &element.name,
+        &element.attributes,
+        &element.children,
+        element.position.as_ref(),
+        ctx,
+    )
 }
 
 /// Generic JSX element converter (generates _jsx calls)
@@ -74,6 +98,7 @@ fn jsx_element_to_string(
     name: &Option<String>,
     attributes: &[markdown::mdast::AttributeContent],
     children: &[Node],
+    position: Option<&Position>,
     ctx: &mut CodegenContext,
 ) -> Result<JsValue> {
     let tag_expr = name
@@ -92,6 +117,10 @@ fn jsx_element_to_string(
                 "Resolving JSX component reference"
             );
 
+            if is_imported || uses_component_map {
+                ctx.stats.record_component(n);
+            }
+
             if is_imported {
                 // Use the imported component directly (e.g., Button)
                 n.clone()
@@ -99,7 +128,9 @@ fn jsx_element_to_string(
                 // Use _components map for provider-injected components
                 // No fallback - if component not provided, let React handle undefined
                 // This avoids creating invalid HTML tags like <CustomAlert>
-                format_component_access(n)
+                let access = format_component_access(n);
+                ctx.reference_component(n, &access, position);
+                access
             } else {
                 // HTML element
                 format!("\"{}\"", escape_js_string(n))
@@ -108,47 +139,43 @@ fn jsx_element_to_string(
         .unwrap_or_else(|| "\"div\"".to_string());
 
     // Convert attributes to props object
-    let mut props = Vec::new();
+    let mut element = JsxElement::new(tag_expr).at(position);
 
     for attr in attributes {
         match attr {
             markdown::mdast::AttributeContent::Property(prop) => {
-                let prop_name = &prop.name;
                 let prop_value = match &prop.value {
+                    // Literal URLs go through the URL policy
+                    Some(markdown::mdast::AttributeValue::Literal(lit))
+                        if matches!(prop.name.as_str(), "href" | "src") =>
+                    {
+                        match ctx.sanitize_url(lit, position)? {
+                            Some(url) => JsValue::text(url),
+                            None => continue,
+                        }
+                    }
                     Some(markdown::mdast::AttributeValue::Literal(lit)) => {
-                        format!("\"{}\"", escape_js_string(lit))
+                        JsValue::text(lit.as_str())
+                    }
+                    Some(markdown::mdast::AttributeValue::Expression(expr)) => {
+                        JsValue::expression(expr.value.clone(), None)
                     }
-                    Some(markdown::mdast::AttributeValue::Expression(expr)) => expr.value.clone(),
-                    None => "true".to_string(),
+                    None => JsValue::bool(true),
                 };
-                props.push(format!("{}: {}", prop_name, prop_value));
+                element = element.prop(prop.name.clone(), prop_value);
             }
             markdown::mdast::AttributeContent::Expression(expr) => {
-                // Spread expression
-                props.push(format!("...{}", expr.value));
+                // Spread expression: markdown-rs keeps the leading `...`
+                let value = expr.value.trim();
+                element = element.spread(value.strip_prefix("...").unwrap_or(value).trim());
             }
         }
     }
 
     // Handle children
-    let jsx = if children.is_empty() {
-        // Self-closing: _jsx(Tag, {props})
-        if props.is_empty() {
-            format!("_jsx({}, {{}})", tag_expr)
-        } else {
-            format!("_jsx({}, {{{}}})", tag_expr, props.join(", "))
-        }
-    } else {
-        // With children: _jsx(Tag, {props, children: ...})
-        let children_value = children_to_jsx(children, ctx)?;
-        props.push(format!("children: {}", children_value.to_js()));
-
-        if children.len() == 1 {
-            format!("_jsx({}, {{{}}})", tag_expr, props.join(", "))
-        } else {
-            format!("_jsxs({}, {{{}}})", tag_expr, props.join(", "))
-        }
-    };
+    if !children.is_empty() {
+        element = element.children(children_to_jsx(children, ctx)?);
+    }
 
-    Ok(JsValue::raw(jsx))
+    Ok(element.into_value())
 }
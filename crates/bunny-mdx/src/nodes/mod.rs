@@ -4,6 +4,7 @@
 
 mod block;
 mod code;
+mod html;
 mod inline;
 mod list;
 mod mdx;
@@ -12,10 +13,13 @@ mod table;
 use anyhow::Result;
 use markdown::mdast::Node;
 
-use crate::codegen::{CodegenContext, JsValue};
+use crate::codegen::{CodegenContext, JsValue, JsxElement};
+use crate::diagnostic::Diagnostic;
+use crate::expression::heading_id;
 
 pub use block::*;
 pub use code::*;
+pub use html::*;
 pub use inline::*;
 pub use list::*;
 pub use mdx::*;
@@ -29,22 +33,36 @@ pub fn node_to_jsx(
     ctx: &mut CodegenContext,
     in_list: bool,
 ) -> Result<Option<JsValue>> {
+    ctx.stats.record(node);
     match node {
         // MDX JSX elements
         Node::MdxJsxFlowElement(element) => Ok(Some(jsx_flow_element_to_string(element, ctx)?)),
         Node::MdxJsxTextElement(element) => Ok(Some(jsx_text_element_to_string(element, ctx)?)),
 
-        // MDX expressions
-        Node::MdxFlowExpression(expr) => Ok(Some(JsValue::raw(expr.value.clone()))),
-        Node::MdxTextExpression(expr) => Ok(Some(JsValue::raw(expr.value.clone()))),
+        // `{#id}` only sets an id at the end of a heading
+        Node::MdxFlowExpression(expr) if heading_id(&expr.value).is_some() => {
+            report_misplaced_heading_id(expr.position.as_ref(), ctx);
+            Ok(None)
+        }
+        Node::MdxTextExpression(expr) if heading_id(&expr.value).is_some() => {
+            report_misplaced_heading_id(expr.position.as_ref(), ctx);
+            Ok(None)
+        }
+
+        // MDX expressions (comment-only ones like `{/* note */}` render nothing)
+        Node::MdxFlowExpression(expr) => Ok(expression_to_jsx(&expr.value, expr.position.as_ref())),
+        Node::MdxTextExpression(expr) => Ok(expression_to_jsx(&expr.value, expr.position.as_ref())),
 
         // Block elements
         Node::Heading(heading) => block::heading_to_jsx(heading, ctx),
         Node::Paragraph(para) => block::paragraph_to_jsx(para, ctx),
         Node::Blockquote(quote) => block::blockquote_to_jsx(quote, ctx),
-        Node::ThematicBreak(_) => Ok(Some(JsValue::raw(
-            "_jsx(_components.hr, {...props})".to_string(),
-        ))),
+        Node::ThematicBreak(thematic_break) => Ok(Some(
+            JsxElement::component("hr")
+                .spread_props()
+                .at(thematic_break.position.as_ref())
+                .into_value(),
+        )),
 
         // Code blocks
         Node::Code(code) => code::code_block_to_jsx(code, ctx),
@@ -60,9 +78,14 @@ pub fn node_to_jsx(
         Node::Strong(strong) => inline::strong_to_jsx(strong, ctx),
         Node::Link(link) => inline::link_to_jsx(link, ctx),
         Node::Image(image) => inline::image_to_jsx(image, ctx),
-        Node::Break(_) => Ok(Some(JsValue::raw(
-            "_jsx(_components.br, {...props})".to_string(),
-        ))),
+        Node::LinkReference(link_ref) => inline::link_reference_to_jsx(link_ref, ctx),
+        Node::ImageReference(image_ref) => inline::image_reference_to_jsx(image_ref, ctx),
+        Node::Break(line_break) => Ok(Some(
+            JsxElement::component("br")
+                .spread_props()
+                .at(line_break.position.as_ref())
+                .into_value(),
+        )),
         Node::Delete(del) => inline::delete_to_jsx(del, ctx),
 
         // Tables
@@ -82,10 +105,10 @@ pub fn node_to_jsx(
             inline::footnote_definition_to_jsx(footnote_def, ctx)
         }
 
-        // HTML (pass through if safe)
-        Node::Html(html) => Ok(Some(JsValue::raw(html.value.clone()))),
+        // Raw HTML (see `RawHtml`)
+        Node::Html(html) => html::html_to_jsx(html, ctx),
 
-        // Skip these nodes
+        // Skip these nodes (definitions are resolved through CodegenContext)
         Node::Definition(_) | Node::Yaml(_) | Node::Toml(_) => Ok(None),
 
         // Fallback for unhandled nodes
@@ -93,6 +116,20 @@ pub fn node_to_jsx(
     }
 }
 
+/// Warn about a `{#id}` that isn't at the end of a heading
+pub(crate) fn report_misplaced_heading_id(
+    position: Option<&markdown::unist::Position>,
+    ctx: &mut CodegenContext,
+) {
+    ctx.push_diagnostic(
+        Diagnostic::warning(
+            "misplaced-heading-id",
+            "`{#id}` sets a heading id only at the end of a heading",
+        )
+        .with_position(position),
+    );
+}
+
 /// Convert list of child nodes to JSX
 ///
 /// Returns a JsValue that correctly handles text-only, mixed content, and complex scenarios.
@@ -118,9 +155,7 @@ pub fn children_to_jsx(children: &[Node], ctx: &mut CodegenContext) -> Result<Js
     }
 
     // Multiple values: check if we need an array
-    let has_non_text = values
-        .iter()
-        .any(|v| matches!(v, JsValue::Raw(_) | JsValue::Array(_)));
+    let has_non_text = values.iter().any(|v| !v.is_text());
 
     if has_non_text {
         // Mixed content: return as array
@@ -138,20 +173,14 @@ pub fn children_to_jsx(children: &[Node], ctx: &mut CodegenContext) -> Result<Js
     }
 }
 
-/// Convert list of child nodes to JSX array (comma-separated for _jsxs)
+/// Convert list of child nodes to JSX values (children array for _jsxs)
 pub fn children_to_jsx_array(
     children: &[Node],
     ctx: &mut CodegenContext,
     in_list: bool,
-) -> Result<String> {
-    let items: Result<Vec<String>> = children
+) -> Result<Vec<JsValue>> {
+    children
         .iter()
-        .filter_map(|child| {
-            node_to_jsx(child, ctx, in_list)
-                .transpose()
-                .map(|result| result.map(|v| v.to_js()))
-        })
-        .collect();
-
-    Ok(items?.join(", "))
+        .filter_map(|child| node_to_jsx(child, ctx, in_list).transpose())
+        .collect()
 }
@@ -4,7 +4,7 @@ use anyhow::Result;
 use markdown::mdast::{AlignKind, Table, TableCell, TableRow};
 
 use super::{children_to_jsx, children_to_jsx_array};
-use crate::codegen::{CodegenContext, JsValue};
+use crate::codegen::{CodegenContext, JsValue, JsxElement};
 
 /// Convert table node to JSX
 pub fn table_to_jsx(table: &Table, ctx: &mut CodegenContext) -> Result<Option<JsValue>> {
@@ -12,43 +12,38 @@ pub fn table_to_jsx(table: &Table, ctx: &mut CodegenContext) -> Result<Option<Js
 
     if table.children.is_empty() {
         ctx.exit_table();
-        let jsx = "_jsx(_components.table, {...props, children: []})";
-        return Ok(Some(JsValue::raw(jsx.to_string())));
+        let element = JsxElement::component("table")
+            .spread_props()
+            .children(JsValue::array(Vec::new()))
+            .at(table.position.as_ref());
+        return Ok(Some(element.into_value()));
     }
 
     let mut sections = Vec::new();
 
     if let Some(header_row) = table.children.first() {
         if let Some(jsx) = super::node_to_jsx(header_row, ctx, false)? {
-            sections.push(format!(
-                "_jsx(_components.thead, {{children: {}}})",
-                jsx.to_js()
-            ));
+            sections.push(JsxElement::component("thead").children(jsx).into_value());
         }
     }
 
     if table.children.len() > 1 {
         let body_rows = &table.children[1..];
         let body_content = children_to_jsx_array(body_rows, ctx, false)?;
-        sections.push(format!(
-            "_jsx(_components.tbody, {{children: [{}]}})",
-            body_content
-        ));
+        sections.push(
+            JsxElement::component("tbody")
+                .children(JsValue::array(body_content))
+                .into_value(),
+        );
     }
 
     ctx.exit_table();
 
-    let children = if sections.is_empty() {
-        "[]".to_string()
-    } else {
-        format!("[{}]", sections.join(", "))
-    };
-
-    let jsx = format!(
-        "_jsxs(_components.table, {{...props, children: {}}})",
-        children
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("table")
+        .spread_props()
+        .children(JsValue::array(sections))
+        .at(table.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert table row node to JSX
@@ -57,11 +52,12 @@ pub fn table_row_to_jsx(row: &TableRow, ctx: &mut CodegenContext) -> Result<Opti
     let children = children_to_jsx_array(&row.children, ctx, false)?;
     ctx.end_table_row();
     let key = ctx.next_key();
-    let jsx = format!(
-        "_jsxs(_components.tr, {{...props, children: [{}]}}, \"{}\")",
-        children, key
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = JsxElement::component("tr")
+        .spread_props()
+        .children(JsValue::array(children))
+        .key(key)
+        .at(row.position.as_ref());
+    Ok(Some(element.into_value()))
 }
 
 /// Convert table cell node to JSX
@@ -70,7 +66,7 @@ pub fn table_cell_to_jsx(cell: &TableCell, ctx: &mut CodegenContext) -> Result<O
     let key = ctx.next_key();
     let tag = if ctx.is_header_row() { "th" } else { "td" };
 
-    let mut props_parts = Vec::new();
+    let mut element = JsxElement::component(tag);
 
     if let Some(align) = ctx.current_cell_alignment() {
         let text_align = match align {
@@ -81,20 +77,19 @@ pub fn table_cell_to_jsx(cell: &TableCell, ctx: &mut CodegenContext) -> Result<O
         };
 
         if let Some(value) = text_align {
-            props_parts.push(format!("style: {{textAlign: \"{}\"}}", value));
+            element = element.prop(
+                "style",
+                JsValue::raw(format!("{{textAlign: \"{}\"}}", value)),
+            );
         }
     }
 
-    props_parts.push("...props".to_string());
-    props_parts.push(format!("children: {}", children.to_js()));
-
     ctx.next_table_cell();
 
-    let jsx = format!(
-        "_jsx(_components.{}, {{{}}}, \"{}\")",
-        tag,
-        props_parts.join(", "),
-        key
-    );
-    Ok(Some(JsValue::raw(jsx)))
+    let element = element
+        .spread_props()
+        .children(children)
+        .key(key)
+        .at(cell.position.as_ref());
+    Ok(Some(element.into_value()))
 }
@@ -0,0 +1,109 @@
+//! Per-document state for plugins
+//!
+//! Plugin instances are shared between documents (and threads), so anything
+//! a plugin learns about one document belongs in the [`PluginContext`] of
+//! that compile rather than in the plugin itself.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::diagnostic::Diagnostic;
+
+/// State scoped to a single compile, passed to every plugin hook
+///
+/// Plugins report problems with [`PluginContext::report`]; they end up on
+/// `MdxCompileResult::diagnostics` tagged with the plugin's name.
+///
+/// Values are stored by type, so a plugin keeps its state in a type of its
+/// own and can't clash with other plugins:
+///
+/// ```
+/// use bunny_mdx::plugins::PluginContext;
+///
+/// #[derive(Default)]
+/// struct WordCount(usize);
+///
+/// let mut cx = PluginContext::new(None);
+/// cx.get_or_default::<WordCount>().0 += 3;
+/// assert_eq!(cx.get::<WordCount>().map(|count| count.0), Some(3));
+/// ```
+#[derive(Debug, Default)]
+pub struct PluginContext {
+    filepath: Option<String>,
+    state: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    diagnostics: Vec<Diagnostic>,
+    /// Plugin whose hook is running
+    plugin: Option<&'static str>,
+}
+
+impl PluginContext {
+    pub fn new(filepath: Option<String>) -> Self {
+        Self {
+            filepath,
+            ..Self::default()
+        }
+    }
+
+    /// Path of the document being compiled, if known
+    pub fn filepath(&self) -> Option<&str> {
+        self.filepath.as_deref()
+    }
+
+    /// Report a diagnostic for the document, attributed to the running plugin
+    pub fn report(&mut self, mut diagnostic: Diagnostic) {
+        if diagnostic.plugin.is_none() {
+            diagnostic.plugin = self.plugin.map(String::from);
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Diagnostics reported so far
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub(crate) fn set_plugin(&mut self, plugin: Option<&'static str>) {
+        self.plugin = plugin;
+    }
+
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Store a value, returning the previous value of the same type
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+        self.state
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.state
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.state
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    /// Get a value, inserting `T::default()` first if there is none
+    pub fn get_or_default<T: Any + Send + Sync + Default>(&mut self) -> &mut T {
+        self.state
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()))
+            .downcast_mut()
+            .expect("State is stored under its own type")
+    }
+
+    /// Remove a value and return it
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.state
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+}
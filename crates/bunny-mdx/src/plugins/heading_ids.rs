@@ -5,10 +5,13 @@
 //!
 //! # Features
 //!
-//! - Generates URL-safe slugs from heading text
-//! - Handles duplicate IDs with numeric suffixes
+//! - Generates URL-safe slugs from heading text, with a configurable
+//!   [`Slugger`] (GitHub-compatible slugs with [`GithubSlugger`])
+//! - Handles duplicate IDs with numeric suffixes (`-1`, `-2`, ...)
 //! - Preserves existing IDs if manually set
 //! - Works with all heading levels (h1-h6)
+//! - Builds a nested table of contents, optionally exported as `toc` or
+//!   rendered in place of a `[[toc]]` marker
 //!
 //! # Example
 //!
@@ -23,183 +26,392 @@
 //! - `<h2 id="hello-world-1">Hello World</h2>`
 //! - `<h3 id="special-characters">Special Characters: @#$%</h3>`
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 
 use anyhow::Result;
-use markdown::mdast::Node;
+use markdown::mdast::{Link, List, ListItem, MdxjsEsm, Node, Paragraph, Text};
+use serde::{Deserialize, Serialize};
 
-use super::MdxPlugin;
+use super::{DefaultSlugger, MdxPlugin, PluginContext, Slugger};
+use crate::nodes::split_custom_id;
+use crate::text::{to_text, TextOptions};
+
+/// Paragraph text replaced by the table of contents
+const TOC_MARKER: &str = "[[toc]]";
+
+/// Component replaced by the table of contents
+const TOC_COMPONENT: &str = "TableOfContents";
+
+/// A heading in the table of contents
+///
+/// Headings deeper than the previous one are nested in its `children`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub depth: u8,
+    pub text: String,
+    pub id: String,
+    pub children: Vec<TocEntry>,
+}
+
+/// Where [`HeadingAnchor`] puts the link in a heading
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnchorPosition {
+    /// Before the heading content: `<h2><a href="#id">#</a>Title</h2>`
+    Prepend,
+    /// After the heading content: `<h2>Title<a href="#id">#</a></h2>`
+    Append,
+    /// Around the heading content: `<h2><a href="#id">Title</a></h2>`
+    Wrap,
+}
+
+/// A link from each heading to itself, added with
+/// [`HeadingIdPlugin::with_anchor`]
+///
+/// The link is an `_components.a` element, so it follows the `a` component
+/// of the provider or `components` prop. Its `className` and `aria-label`
+/// are left out unless set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadingAnchor {
+    pub position: AnchorPosition,
+    /// Text of prepended and appended links (`#` by default)
+    pub content: String,
+    pub aria_label: Option<String>,
+    pub class_name: Option<String>,
+}
+
+impl HeadingAnchor {
+    pub fn new(position: AnchorPosition) -> Self {
+        Self {
+            position,
+            content: "#".to_string(),
+            aria_label: None,
+            class_name: None,
+        }
+    }
+
+    pub fn with_content(mut self, content: impl Into<String>) -> Self {
+        self.content = content.into();
+        self
+    }
+
+    pub fn with_aria_label(mut self, aria_label: impl Into<String>) -> Self {
+        self.aria_label = Some(aria_label.into());
+        self
+    }
+
+    pub fn with_class_name(mut self, class_name: impl Into<String>) -> Self {
+        self.class_name = Some(class_name.into());
+        self
+    }
+
+    /// Take the anchor configured for the current document out of `cx`, for
+    /// codegen
+    pub(crate) fn take(cx: &mut PluginContext) -> Option<Self> {
+        cx.remove::<Self>()
+    }
+}
 
 /// Plugin that generates unique anchor IDs for heading elements
 ///
-/// The plugin walks the AST and adds `id` attributes to all heading nodes.
-/// IDs are generated by:
+/// The plugin walks the AST and plans an `id` for every heading, which
+/// codegen sets on the heading element. Headings are matched in document
+/// order, so register this plugin after plugins that add or remove headings.
+/// A `{#custom-id}` suffix (`## Setup {#install}`) pins a heading's id.
+/// Other IDs are generated by:
 /// 1. Extracting text content from the heading
-/// 2. Converting to lowercase
-/// 3. Replacing non-alphanumeric characters with hyphens
-/// 4. Removing leading/trailing hyphens
-/// 5. Appending numeric suffix if ID already exists
+/// 2. Turning it into a slug with the [`Slugger`] ([`DefaultSlugger`]
+///    lowercases it and joins words with hyphens)
+/// 3. Appending numeric suffix if ID already exists
+///
+/// It also builds the table of contents, available as
+/// `MdxCompileResult::toc` and optionally as `export const toc = [...]` or in
+/// place of a `[[toc]]` / `<TableOfContents />` marker:
+///
+/// ```
+/// use bunny_mdx::plugins::HeadingIdPlugin;
+/// use bunny_mdx::{compile, MdxCompileOptions};
+///
+/// let plugin = HeadingIdPlugin::new().with_toc_depth(2, 3).with_toc_export();
+/// let result = compile(
+///     "# Guide\n\n## Install\n\n### From source\n\n## Usage",
+///     MdxCompileOptions::new().with_plugin(plugin),
+/// )
+/// .unwrap();
+///
+/// let toc: Vec<(&str, usize)> = result
+///     .toc
+///     .iter()
+///     .map(|entry| (entry.id.as_str(), entry.children.len()))
+///     .collect();
+/// assert_eq!(toc, [("install", 1), ("usage", 0)]);
+/// assert!(result.code.contains("export const toc = ["));
+/// ```
 ///
 /// # Thread Safety
 ///
-/// This plugin uses internal state to track used IDs. Each instance maintains
-/// its own ID counter, so use separate instances for parallel processing.
-#[derive(Default)]
+/// The plugin only holds its configuration; IDs and the table of contents of
+/// each document are kept in the compile's [`PluginContext`].
+#[derive(Clone, Debug)]
 pub struct HeadingIdPlugin {
-    // Internal state is not needed here as we'll process the entire AST in one pass
-    // and collect IDs during that pass
+    slugger: Arc<dyn Slugger>,
+    anchor: Option<HeadingAnchor>,
+    toc_min_depth: u8,
+    toc_max_depth: u8,
+    toc_export: bool,
+    toc_marker: bool,
+}
+
+impl Default for HeadingIdPlugin {
+    fn default() -> Self {
+        Self {
+            slugger: Arc::new(DefaultSlugger),
+            anchor: None,
+            toc_min_depth: 1,
+            toc_max_depth: 6,
+            toc_export: false,
+            toc_marker: false,
+        }
+    }
 }
 
+/// IDs of the current document's headings, in document order
+#[derive(Debug, Default)]
+pub(crate) struct HeadingIds(Vec<String>);
+
+impl HeadingIds {
+    /// Take the planned IDs out of `cx`, for codegen
+    pub(crate) fn take(cx: &mut PluginContext) -> VecDeque<String> {
+        cx.remove::<Self>()
+            .map(|ids| ids.0.into())
+            .unwrap_or_default()
+    }
+
+    /// IDs planned by `HeadingIdPlugin`, if it ran before
+    pub(crate) fn get(cx: &PluginContext) -> Option<&[String]> {
+        cx.get::<Self>().map(|ids| ids.0.as_slice())
+    }
+}
+
+/// Table of contents of the current document
+#[derive(Debug, Default)]
+struct TableOfContents(Vec<TocEntry>);
+
 impl HeadingIdPlugin {
     /// Create a new heading ID plugin
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Generate a URL-safe slug from heading text
-    ///
-    /// # Algorithm
+    /// Only include headings from `min` to `max` depth (1 to 6 by default)
+    pub fn with_toc_depth(mut self, min: u8, max: u8) -> Self {
+        self.toc_min_depth = min;
+        self.toc_max_depth = max;
+        self
+    }
+
+    /// Add `export const toc = [...]` to the module
     ///
-    /// 1. Extract text content from all child nodes
-    /// 2. Convert to lowercase
-    /// 3. Replace sequences of non-alphanumeric chars with single hyphen
-    /// 4. Remove leading/trailing hyphens
+    /// Entries have the same `depth`, `text`, `id` and `children` fields as
+    /// [`TocEntry`].
+    pub fn with_toc_export(mut self) -> Self {
+        self.toc_export = true;
+        self
+    }
+
+    /// Replace a `[[toc]]` paragraph or a `<TableOfContents />` element with
+    /// the table of contents, as a nested list of links
+    pub fn with_toc_marker(mut self) -> Self {
+        self.toc_marker = true;
+        self
+    }
+
+    /// Table of contents of the document compiled with `cx`
+    pub fn toc(cx: &PluginContext) -> &[TocEntry] {
+        cx.get::<TableOfContents>()
+            .map_or(&[], |toc| toc.0.as_slice())
+    }
+
+    /// Make IDs with `slugger` ([`DefaultSlugger`] by default)
     ///
-    /// # Examples
+    /// ```
+    /// use bunny_mdx::plugins::{GithubSlugger, HeadingIdPlugin};
     ///
-    /// - "Hello World" → "hello-world"
-    /// - "Rust & TypeScript" → "rust-typescript"
-    /// - "  Multiple   Spaces  " → "multiple-spaces"
-    pub fn generate_slug(&self, text: &str) -> String {
-        let mut slug = String::new();
-        let mut last_was_sep = true; // Start true to skip leading hyphens
-
-        for ch in text.chars() {
-            if ch.is_alphanumeric() {
-                slug.push(ch.to_ascii_lowercase());
-                last_was_sep = false;
-            } else if !last_was_sep {
-                slug.push('-');
-                last_was_sep = true;
-            }
-        }
+    /// let plugin = HeadingIdPlugin::new().with_slugger(GithubSlugger);
+    /// assert_eq!(plugin.generate_slug("Rust & TypeScript"), "rust--typescript");
+    /// ```
+    pub fn with_slugger(mut self, slugger: impl Slugger + 'static) -> Self {
+        self.slugger = Arc::new(slugger);
+        self
+    }
 
-        // Remove trailing hyphen if present
-        if slug.ends_with('-') {
-            slug.pop();
-        }
+    /// Add a link to itself in every heading
+    ///
+    /// ```
+    /// use bunny_mdx::plugins::{AnchorPosition, HeadingAnchor, HeadingIdPlugin};
+    /// use bunny_mdx::{compile_to_html, HtmlOptions, MdxCompileOptions};
+    ///
+    /// let anchor = HeadingAnchor::new(AnchorPosition::Append)
+    ///     .with_aria_label("Link to this section")
+    ///     .with_class_name("anchor");
+    /// let options = MdxCompileOptions::new().with_plugin(HeadingIdPlugin::new().with_anchor(anchor));
+    /// let html = compile_to_html("## Install", options, &HtmlOptions::new())?.html;
+    /// assert_eq!(
+    ///     html,
+    ///     "<h2 id=\"install\">Install<a href=\"#install\" aria-label=\"Link to this section\" class=\"anchor\">#</a></h2>\n"
+    /// );
+    /// # Ok::<(), Box<bunny_mdx::MdxError>>(())
+    /// ```
+    pub fn with_anchor(mut self, anchor: HeadingAnchor) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
 
-        // Return "heading" as fallback for empty slugs
-        if slug.is_empty() {
-            String::from("heading")
-        } else {
-            slug
-        }
+    /// Slug of heading text, before duplicates are numbered
+    pub fn generate_slug(&self, text: &str) -> String {
+        self.slugger.slug(text)
     }
 
     /// Extract text content from a node and its children
     fn extract_text(node: &Node) -> String {
-        match node {
-            Node::Text(text) => text.value.clone(),
-            Node::InlineCode(code) => code.value.clone(),
-            Node::Strong(strong) => strong
-                .children
-                .iter()
-                .map(Self::extract_text)
-                .collect::<Vec<_>>()
-                .join(""),
-            Node::Emphasis(em) => em
-                .children
-                .iter()
-                .map(Self::extract_text)
-                .collect::<Vec<_>>()
-                .join(""),
-            Node::Delete(del) => del
-                .children
-                .iter()
-                .map(Self::extract_text)
-                .collect::<Vec<_>>()
-                .join(""),
-            Node::Link(link) => link
-                .children
-                .iter()
-                .map(Self::extract_text)
-                .collect::<Vec<_>>()
-                .join(""),
-            Node::LinkReference(link_ref) => link_ref
-                .children
-                .iter()
-                .map(Self::extract_text)
-                .collect::<Vec<_>>()
-                .join(""),
-            Node::MdxTextExpression(expr) => {
-                // For expressions, just use placeholder text
-                format!("expr-{}", expr.value.len())
-            }
-            // Ignore other node types
-            _ => String::new(),
+        to_text(node, &TextOptions::new())
+    }
+
+    /// Collect `(depth, text, custom id)` for every heading, in document order
+    fn collect_headings(node: &Node, headings: &mut Vec<(u8, String, Option<String>)>) {
+        if let Node::Heading(heading) = node {
+            let (content, custom_id) = split_custom_id(&heading.children);
+            let text: String = content.iter().map(Self::extract_text).collect();
+            headings.push((
+                heading.depth,
+                text.trim().to_string(),
+                custom_id.map(str::to_string),
+            ));
+            return;
+        }
+        for child in node.children().into_iter().flatten() {
+            Self::collect_headings(child, headings);
         }
     }
 
-    /// Process headings and add IDs
+    /// Give every heading a unique ID
     ///
-    /// Note: Since mdast doesn't support custom attributes, this method just
-    /// validates the AST structure. The actual ID injection happens in the
-    /// JSX transformation phase.
-    fn process_headings(&self, node: &mut Node, used_ids: &mut HashMap<String, usize>) {
-        match node {
-            Node::Heading(heading) => {
-                // Extract text from all children
-                let text = heading
-                    .children
-                    .iter()
-                    .map(Self::extract_text)
-                    .collect::<Vec<_>>()
-                    .join("");
-
-                // Generate base slug
-                let base_slug = self.generate_slug(&text);
-
-                // Make unique if needed
-                let _unique_id = if let Some(count) = used_ids.get_mut(&base_slug) {
-                    *count += 1;
-                    format!("{}-{}", base_slug, count)
-                } else {
-                    used_ids.insert(base_slug.clone(), 0);
-                    base_slug
-                };
-
-                // Note: We can't store the ID in the AST since mdast doesn't support
-                // custom attributes. The ID generation will need to happen during
-                // JSX transformation where we can parse and modify the output.
-            }
-            Node::Root(root) => {
-                for child in &mut root.children {
-                    self.process_headings(child, used_ids);
-                }
-            }
-            Node::Blockquote(blockquote) => {
-                for child in &mut blockquote.children {
-                    self.process_headings(child, used_ids);
-                }
-            }
-            Node::List(list) => {
-                for child in &mut list.children {
-                    self.process_headings(child, used_ids);
-                }
-            }
-            Node::ListItem(item) => {
-                for child in &mut item.children {
-                    self.process_headings(child, used_ids);
-                }
+    /// Custom IDs are reserved first, so generated ones don't take them.
+    fn assign_ids(&self, headings: Vec<(u8, String, Option<String>)>) -> Vec<(u8, String, String)> {
+        let mut used_ids: HashMap<String, usize> = headings
+            .iter()
+            .filter_map(|(.., custom_id)| custom_id.clone())
+            .map(|id| (id, 0))
+            .collect();
+        headings
+            .into_iter()
+            .map(|(depth, text, custom_id)| {
+                let id = custom_id
+                    .unwrap_or_else(|| unique_id(&mut used_ids, self.generate_slug(&text)));
+                (depth, text, id)
+            })
+            .collect()
+    }
+
+    /// Nest headings within the configured depths
+    fn build_toc(&self, headings: &[(u8, String, String)]) -> Vec<TocEntry> {
+        let mut toc = Vec::new();
+        let depths = self.toc_min_depth..=self.toc_max_depth;
+        for (depth, text, id) in headings.iter().filter(|(depth, ..)| depths.contains(depth)) {
+            let entry = TocEntry {
+                depth: *depth,
+                text: text.clone(),
+                id: id.clone(),
+                children: Vec::new(),
+            };
+            // Descend while the last entry is shallower than this one
+            let mut siblings = &mut toc;
+            while siblings
+                .last()
+                .is_some_and(|last: &TocEntry| last.depth < *depth)
+            {
+                siblings = &mut siblings.last_mut().expect("checked above").children;
             }
-            Node::FootnoteDefinition(def) => {
-                for child in &mut def.children {
-                    self.process_headings(child, used_ids);
+            siblings.push(entry);
+        }
+        toc
+    }
+}
+
+/// `slug`, with a numeric suffix when it is already used
+///
+/// Suffixes skip IDs that are taken too, as in `github-slugger`: after
+/// `# A 1`, two `# A` headings get `a` and `a-2`.
+fn unique_id(used_ids: &mut HashMap<String, usize>, slug: String) -> String {
+    let mut id = slug.clone();
+    while used_ids.contains_key(&id) {
+        let count = used_ids.get_mut(&slug).expect("slug is used");
+        *count += 1;
+        id = format!("{}-{}", slug, count);
+    }
+    used_ids.insert(id.clone(), 0);
+    id
+}
+
+/// The table of contents as a nested list of links
+fn toc_list(entries: &[TocEntry]) -> Node {
+    Node::List(List {
+        children: entries
+            .iter()
+            .map(|entry| {
+                let link = Node::Link(Link {
+                    children: vec![Node::Text(Text {
+                        value: entry.text.clone(),
+                        position: None,
+                    })],
+                    position: None,
+                    url: format!("#{}", entry.id),
+                    title: None,
+                });
+                let mut children = vec![Node::Paragraph(Paragraph {
+                    children: vec![link],
+                    position: None,
+                })];
+                if !entry.children.is_empty() {
+                    children.push(toc_list(&entry.children));
                 }
-            }
-            // Other container nodes can be added as needed
-            _ => {}
+                Node::ListItem(ListItem {
+                    children,
+                    position: None,
+                    spread: false,
+                    checked: None,
+                })
+            })
+            .collect(),
+        position: None,
+        ordered: false,
+        start: None,
+        spread: false,
+    })
+}
+
+fn is_toc_marker(node: &Node) -> bool {
+    match node {
+        Node::Paragraph(paragraph) => {
+            matches!(paragraph.children.as_slice(), [Node::Text(text)] if text.value.trim() == TOC_MARKER)
+        }
+        Node::MdxJsxFlowElement(element) => {
+            element.name.as_deref() == Some(TOC_COMPONENT) && element.children.is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Replace TOC markers in block content with `list`
+fn replace_toc_markers(node: &mut Node, list: &Node) {
+    let Some(children) = node.children_mut() else {
+        return;
+    };
+    for child in children.iter_mut() {
+        if is_toc_marker(child) {
+            *child = list.clone();
+        } else {
+            replace_toc_markers(child, list);
         }
     }
 }
@@ -209,162 +421,61 @@ impl MdxPlugin for HeadingIdPlugin {
         "heading-ids"
     }
 
-    fn transform_ast(&self, ast: &mut Node) -> Result<()> {
-        let mut used_ids = HashMap::new();
-        self.process_headings(ast, &mut used_ids);
-        Ok(())
-    }
-
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
+    fn cache_key(&self) -> Option<String> {
+        let anchor = self.anchor.as_ref().map(|anchor| {
+            serde_json::json!({
+                "position": match anchor.position {
+                    AnchorPosition::Prepend => "prepend",
+                    AnchorPosition::Append => "append",
+                    AnchorPosition::Wrap => "wrap",
+                },
+                "content": anchor.content,
+                "ariaLabel": anchor.aria_label,
+                "className": anchor.class_name,
+            })
+        });
+        let key = serde_json::json!({
+            "slugger": self.slugger.cache_key()?,
+            "anchor": anchor,
+            "tocDepth": [self.toc_min_depth, self.toc_max_depth],
+            "tocExport": self.toc_export,
+            "tocMarker": self.toc_marker,
+        });
+        Some(format!("heading-ids {}", key))
     }
 
-    fn transform_jsx(&self, jsx: &mut String) -> Result<()> {
-        // For this initial implementation, we'll use a simpler string-based approach
-        // A production implementation would parse the JSX AST properly
-        //
-        // We look for patterns like: _jsx(_components.h1, {...props, children:
-        // and inject the id attribute before children
-        //
-        // SAFETY: All string slicing operations in this function are carefully
-        // validated to ensure they occur on UTF-8 character boundaries. This is
-        // critical because the JSX may contain multi-byte UTF-8 characters (emoji,
-        // international text, etc.) and slicing mid-character causes panics.
+    fn transform_ast(&self, ast: &mut Node, cx: &mut PluginContext) -> Result<()> {
+        let mut headings = Vec::new();
+        Self::collect_headings(ast, &mut headings);
+        let headings = self.assign_ids(headings);
+        let toc = self.build_toc(&headings);
 
-        let mut result = String::with_capacity(jsx.len() + 1024);
-        let mut used_ids = HashMap::new();
-        let mut last_end = 0;
-
-        // Simple state machine to find heading elements
-        let heading_pattern = "_jsx(_components.h";
-        let mut pos = 0;
-
-        while let Some(start) = jsx[pos..].find(heading_pattern) {
-            let abs_start = pos + start;
-
-            // Check if this is a heading (h1-h6)
-            let after_h = abs_start + heading_pattern.len();
-            if after_h < jsx.len() {
-                if let Some(level_char) = jsx[after_h..].chars().next() {
-                    if level_char.is_ascii_digit() && ('1'..='6').contains(&level_char) {
-                        // Found a heading! Look for the children: part
-                        if let Some(children_pos) = jsx[after_h..].find(", {...props, children: ") {
-                            let abs_children = after_h + children_pos;
-
-                            // Calculate the potential snippet extraction bounds
-                            // CRITICAL: These are byte offsets from find() operations,
-                            // which may not align with UTF-8 char boundaries!
-                            let raw_snippet_start = abs_children + ", {...props, children: ".len();
-
-                            // Validate snippet_start is on a char boundary
-                            // If not, advance to the next valid boundary
-                            let snippet_start = if jsx.is_char_boundary(raw_snippet_start) {
-                                raw_snippet_start
-                            } else {
-                                // Find the next char boundary by advancing forward
-                                // This is safe because we're finding the START of the next char
-                                (raw_snippet_start..jsx.len())
-                                    .find(|&i| jsx.is_char_boundary(i))
-                                    .unwrap_or(jsx.len())
-                            };
-
-                            // Find safe char boundary for snippet_end
-                            // We look backward to ensure we don't go past the end
-                            let raw_snippet_end = std::cmp::min(snippet_start + 100, jsx.len());
-                            let snippet_end = if jsx.is_char_boundary(raw_snippet_end) {
-                                raw_snippet_end
-                            } else {
-                                // Walk backward to find the previous char boundary
-                                (snippet_start..=raw_snippet_end)
-                                    .rev()
-                                    .find(|&i| jsx.is_char_boundary(i))
-                                    .unwrap_or(snippet_start)
-                            };
-
-                            // Ensure we have a valid range
-                            if snippet_start < snippet_end {
-                                let snippet = &jsx[snippet_start..snippet_end];
-
-                                // Extract text (very simplified)
-                                let text = snippet
-                                    .split(['"', '}', ','])
-                                    .next()
-                                    .unwrap_or("")
-                                    .trim()
-                                    .chars()
-                                    .take(50)
-                                    .collect::<String>();
-
-                                // Generate slug
-                                let base_slug = self.generate_slug(&text);
-                                let unique_id = if let Some(count) = used_ids.get_mut(&base_slug) {
-                                    *count += 1;
-                                    format!("{}-{}", base_slug, count)
-                                } else {
-                                    used_ids.insert(base_slug.clone(), 0);
-                                    base_slug
-                                };
-
-                                // Calculate the injection point (where we insert the id attribute)
-                                let raw_inject_point = abs_children + ", {...props, ".len();
-
-                                // Validate injection point is on a char boundary
-                                let inject_point = if jsx.is_char_boundary(raw_inject_point) {
-                                    raw_inject_point
-                                } else {
-                                    // This should be rare since the pattern is ASCII,
-                                    // but we validate for safety
-                                    (raw_inject_point..jsx.len())
-                                        .find(|&i| jsx.is_char_boundary(i))
-                                        .unwrap_or(jsx.len())
-                                };
-
-                                // Validate last_end is on a char boundary before slicing
-                                let safe_last_end = if jsx.is_char_boundary(last_end) {
-                                    last_end
-                                } else {
-                                    // Find next char boundary if last_end got corrupted somehow
-                                    (last_end..jsx.len())
-                                        .find(|&i| jsx.is_char_boundary(i))
-                                        .unwrap_or(jsx.len())
-                                };
-
-                                // Ensure inject_point is valid for slicing
-                                if safe_last_end <= inject_point && inject_point <= jsx.len() {
-                                    // Copy everything up to {...props,
-                                    result.push_str(&jsx[safe_last_end..inject_point]);
-
-                                    // Inject id attribute
-                                    result.push_str(&format!("id: \"{}\", ", unique_id));
-
-                                    // Update last_end to be after the injection point
-                                    // This is safe because inject_point is validated
-                                    last_end = inject_point;
-                                }
-                            }
-                        }
-                    }
-                }
+        if self.toc_marker {
+            replace_toc_markers(ast, &toc_list(&toc));
+        }
+        if self.toc_export {
+            if let Some(children) = ast.children_mut() {
+                children.push(Node::MdxjsEsm(MdxjsEsm {
+                    value: format!("export const toc = {};", serde_json::to_string(&toc)?),
+                    position: None,
+                    stops: Vec::new(),
+                }));
             }
-
-            pos = abs_start + heading_pattern.len();
         }
 
-        // Validate last_end one final time before the final slice
-        let safe_last_end = if jsx.is_char_boundary(last_end) {
-            last_end
-        } else {
-            (last_end..jsx.len())
-                .find(|&i| jsx.is_char_boundary(i))
-                .unwrap_or(jsx.len())
-        };
-
-        // Copy remaining content
-        result.push_str(&jsx[safe_last_end..]);
-
-        *jsx = result;
+        cx.insert(HeadingIds(
+            headings.into_iter().map(|(.., id)| id).collect(),
+        ));
+        cx.insert(TableOfContents(toc));
+        if let Some(anchor) = &self.anchor {
+            cx.insert(anchor.clone());
+        }
         Ok(())
     }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 #[cfg(test)]
@@ -443,208 +554,200 @@ mod tests {
         assert_eq!(unique_slug3, "introduction-2");
     }
 
-    // UTF-8 safety tests - these ensure we never panic on multibyte characters
-    #[test]
-    fn test_jsx_transform_with_emoji() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Test case with emoji before heading
-        // The JSX must match the exact pattern that the plugin searches for
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx} from 'react/jsx-runtime';
-function _createMdxContent(props) {
-  const _components = {};
-  return _jsx("div", {children: "🎉 Welcome! 🚀"});
-}
-_jsx(_components.h1, {...props, children: "Hello World"});"#,
-        );
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(result.is_ok(), "Should handle emoji without panic");
-        // Just verify it doesn't panic - the actual ID injection requires the exact pattern
-        assert!(result.is_ok());
+    fn compile_with(plugin: HeadingIdPlugin, mdx: &str) -> crate::MdxCompileResult {
+        crate::compile(mdx, crate::MdxCompileOptions::new().with_plugin(plugin)).unwrap()
     }
 
     #[test]
-    fn test_jsx_transform_with_chinese_characters() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Chinese characters are 3 bytes each in UTF-8
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx} from 'react/jsx-runtime';
-const text = "欢迎来到我的网站";
-_jsx(_components.h2, {...props, children: "介绍"});
-"#,
+    fn test_toc_nesting_and_depths() {
+        let mdx =
+            "# Title\n\n### Deep *first*\n\n## Setup\n\n### Install\n\n#### Linux\n\n## Setup";
+        let toc = compile_with(HeadingIdPlugin::new(), mdx).toc;
+        let outline: Vec<(u8, &str, usize)> = toc[0]
+            .children
+            .iter()
+            .map(|entry| (entry.depth, entry.id.as_str(), entry.children.len()))
+            .collect();
+        assert_eq!(toc.len(), 1);
+        assert_eq!(
+            outline,
+            [(3, "deep-first", 0), (2, "setup", 1), (2, "setup-1", 0)]
         );
+        assert_eq!(toc[0].children[0].text, "Deep first");
+        assert_eq!(toc[0].children[1].children[0].children[0].id, "linux");
 
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle Chinese characters without panic"
-        );
+        let toc = compile_with(HeadingIdPlugin::new().with_toc_depth(2, 3), mdx).toc;
+        let ids: Vec<&str> = toc.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["deep-first", "setup", "setup-1"]);
+        assert!(toc[1].children[0].children.is_empty());
     }
 
     #[test]
-    fn test_jsx_transform_with_arabic_characters() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Arabic characters and RTL text
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx} from 'react/jsx-runtime';
-const greeting = "مرحبا";
-_jsx(_components.h3, {...props, children: "العنوان"});
-"#,
-        );
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(result.is_ok(), "Should handle Arabic text without panic");
+    fn test_ids_match_toc() {
+        let mdx = "## Hello `code` {props.x}\n\n## Hello code";
+        let result = compile_with(HeadingIdPlugin::new(), mdx);
+        assert_eq!(result.toc[0].id, "hello-code");
+        assert_eq!(result.toc[1].id, "hello-code-1");
+        assert!(result.code.contains("id: \"hello-code\""));
+        assert!(result.code.contains("id: \"hello-code-1\""));
     }
 
     #[test]
-    fn test_jsx_transform_with_mixed_multibyte() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Mix of emoji (4 bytes), Chinese (3 bytes), and ASCII
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx} from 'react/jsx-runtime';
-// Comment with emoji: 🔥 and Chinese: 中文 and more emoji: 💻
-const data = "Test 测试 🎨 Data";
-_jsx(_components.h1, {...props, children: "API 文档 📚"});
-_jsx(_components.h2, {...props, children: "Getting Started"});
-"#,
+    fn test_github_slugger() {
+        let mdx = "# A 1\n\n## A\n\n## A\n\n## Rust & *Wasm*\n\n## Über 文档";
+        let result = compile_with(
+            HeadingIdPlugin::new().with_slugger(crate::plugins::GithubSlugger),
+            mdx,
         );
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle mixed multibyte chars without panic"
-        );
-        // The main goal is to ensure no panic occurs with mixed UTF-8 characters
-        // The actual ID generation depends on the exact JSX pattern structure
+        let ids: Vec<&str> = result.toc[0]
+            .children
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect();
+        assert_eq!(result.toc[0].id, "a-1");
+        assert_eq!(ids, ["a", "a-2", "rust--wasm", "über-文档"]);
     }
 
     #[test]
-    fn test_jsx_transform_boundary_at_exact_offset() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Construct a string where a multibyte char lands exactly where
-        // we would calculate snippet_start
-        // This is a regression test for the specific panic scenario
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx, jsxs as _jsxs, Fragment as _Fragment} from 'react/jsx-runtime';
-"#,
-        );
-
-        // Add enough content with multibyte chars to trigger boundary issues
-        for _ in 0..50 {
-            jsx.push_str("Text with emoji 🔥 and more content. ");
-        }
-
-        jsx.push_str(r#"_jsx(_components.h1, {...props, children: "Test Heading"});"#);
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle boundary at exact offset without panic"
-        );
+    fn test_toc_export() {
+        let result = compile_with(HeadingIdPlugin::new().with_toc_export(), "## A \"quote\"");
+        assert!(result.code.contains(
+            "export const toc = [{\n  \"depth\": 2,\n  \"text\": \"A \\\"quote\\\"\",\n  \"id\": \"a-quote\",\n  \"children\": []\n}];"
+        ));
+        assert_eq!(result.named_exports.len(), 1);
+        assert!(result.named_exports[0].starts_with("export const toc = ["));
+        assert!(!compile_with(HeadingIdPlugin::new(), "## A")
+            .code
+            .contains("toc"));
     }
 
     #[test]
-    fn test_jsx_transform_with_surrogate_pairs() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Emoji that use surrogate pairs (4 bytes in UTF-8)
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx} from 'react/jsx-runtime';
-const emojis = "👨‍👩‍👧‍👦 Family 👍🏽 Thumbs Up";
-_jsx(_components.h1, {...props, children: "Emoji Support 🎉"});
-"#,
-        );
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle emoji surrogate pairs without panic"
-        );
+    fn test_toc_marker() {
+        let mdx = "[[toc]]\n\n## One\n\n### Two\n\n<Card>\n  <TableOfContents />\n</Card>";
+        let code = compile_with(HeadingIdPlugin::new().with_toc_marker(), mdx).code;
+        assert_eq!(code.matches("href: \"#one\"").count(), 2);
+        assert_eq!(code.matches("href: \"#two\"").count(), 2);
+        assert!(!code.contains("[[toc]]"));
+        assert!(!code.contains("TableOfContents"));
+
+        // Without the option, markers are left alone
+        let code = compile_with(HeadingIdPlugin::new(), mdx).code;
+        assert!(code.contains("[[toc]]"));
+        assert!(code.contains("TableOfContents"));
     }
 
     #[test]
-    fn test_jsx_transform_empty_heading() {
-        let plugin = HeadingIdPlugin::new();
-
-        let mut jsx = String::from(r#"_jsx(_components.h1, {...props, children: ""});"#);
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(result.is_ok(), "Should handle empty heading");
-        assert!(jsx.contains(r#"id: "heading""#)); // Default fallback
+    fn test_unicode_headings() {
+        let mdx =
+            "# API 文档 📚\n\n## 介绍\n\n### العنوان\n\n# Emoji Support 🎉\n\n#\n\n# !@#$%^&*()";
+        let result = compile_with(HeadingIdPlugin::new(), mdx);
+        let ids: Vec<&str> = result.toc.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["api-文档", "emoji-support", "heading", "heading-1"]);
+        for id in [
+            "api-文档",
+            "介绍",
+            "العنوان",
+            "emoji-support",
+            "heading",
+            "heading-1",
+        ] {
+            assert!(result.code.contains(&format!("id: \"{}\"", id)), "{}", id);
+        }
     }
 
     #[test]
-    fn test_jsx_transform_special_chars_only() {
-        let plugin = HeadingIdPlugin::new();
-
-        let mut jsx = String::from(r#"_jsx(_components.h1, {...props, children: "!@#$%^&*()"});"#);
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(result.is_ok(), "Should handle special chars only");
-        assert!(jsx.contains(r#"id: "heading""#)); // Default fallback
+    fn test_ids_are_set_by_codegen() {
+        // Headings with several children compile to `_jsxs`
+        let code = compile_with(HeadingIdPlugin::new(), "## Set *up*\n\n<h2>Raw</h2>").code;
+        assert!(code.contains("_jsxs(_components.h2, {\n    ...props,\n    id: \"set-up\","));
+        // Only markdown headings get an id
+        assert_eq!(code.matches("id:").count(), 1);
+
+        let mut options = crate::MdxCompileOptions::new().with_plugin(HeadingIdPlugin::new());
+        options.output_format = crate::OutputFormat::Jsx;
+        let code = crate::compile("# Hello *World*", options).unwrap().code;
+        assert!(code.contains("<_components.h1 {...props} id=\"hello-world\">"));
     }
 
     #[test]
-    fn test_jsx_transform_multiple_headings_with_unicode() {
-        let plugin = HeadingIdPlugin::new();
-
-        let mut jsx = String::from(
-            r#"import {jsx as _jsx} from 'react/jsx-runtime';
-_jsx(_components.h1, {...props, children: "Introduction 介绍"});
-_jsx(_components.h2, {...props, children: "Features 特性 🚀"});
-_jsx(_components.h3, {...props, children: "Documentation 文档"});
-"#,
-        );
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle multiple headings with unicode"
+    fn test_custom_ids() {
+        let mdx = "## Setup {#install}\n\n## Install\n\n## Other {#a_b-1}";
+        let result = compile_with(HeadingIdPlugin::new(), mdx);
+        let ids: Vec<(&str, &str)> = result
+            .toc
+            .iter()
+            .map(|entry| (entry.text.as_str(), entry.id.as_str()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                ("Setup", "install"),
+                ("Install", "install-1"),
+                ("Other", "a_b-1")
+            ]
         );
+        let code: String = result.code.split_whitespace().collect();
+        assert!(code.contains(r#"id:"install",children:"Setup"}"#));
+        assert!(!result.code.contains("#install"));
+
+        // Custom ids don't need the plugin
+        let result = crate::compile(mdx, crate::MdxCompileOptions::new()).unwrap();
+        assert!(result.code.contains("id: \"install\""));
+        assert_eq!(result.code.matches("id:").count(), 2);
+
+        let html = crate::compile_to_html(
+            mdx,
+            crate::MdxCompileOptions::new().with_plugin(HeadingIdPlugin::new()),
+            &crate::HtmlOptions::new(),
+        )
+        .unwrap()
+        .html;
+        assert!(html.starts_with("<h2 id=\"install\">Setup</h2>\n<h2 id=\"install-1\">"));
     }
 
     #[test]
-    fn test_jsx_transform_very_long_heading() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Test with a very long heading that exceeds the 100-byte snippet window
-        let long_text = "A".repeat(200) + " with emoji 🎉 " + &"B".repeat(200);
-        let mut jsx = format!(
-            r#"_jsx(_components.h1, {{...props, children: "{}"}})"#,
-            long_text
-        );
+    fn test_anchors() {
+        let link = r##"_jsx(_components.a,{...props,href:"#hi","aria-label":"Permalink",className:"anchor",children:"#"})"##;
+        for (position, children) in [
+            (AnchorPosition::Prepend, format!("[{},\"Hi\"]", link)),
+            (AnchorPosition::Append, format!("[\"Hi\",{}]", link)),
+            (
+                AnchorPosition::Wrap,
+                link.replace(r##"children:"#""##, r#"children:"Hi""#),
+            ),
+        ] {
+            let anchor = HeadingAnchor::new(position)
+                .with_aria_label("Permalink")
+                .with_class_name("anchor");
+            let result = compile_with(HeadingIdPlugin::new().with_anchor(anchor), "# Hi");
+            let code: String = result.code.split_whitespace().collect();
+            assert!(
+                code.contains(&format!(r#"id:"hi",children:{}}}"#, children)),
+                "{:?}",
+                position
+            );
+            // The table of contents has the heading text only
+            assert_eq!(result.toc[0].text, "Hi");
+        }
 
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle very long headings without panic"
+        let anchor = HeadingAnchor::new(AnchorPosition::Prepend).with_content("¶");
+        let html = crate::compile_to_html(
+            "## Set *up* {#setup}",
+            crate::MdxCompileOptions::new().with_plugin(HeadingIdPlugin::new().with_anchor(anchor)),
+            &crate::HtmlOptions::new(),
+        )
+        .unwrap()
+        .html;
+        assert_eq!(
+            html,
+            "<h2 id=\"setup\"><a href=\"#setup\">¶</a>Set <em>up</em></h2>\n"
         );
     }
 
     #[test]
-    fn test_jsx_transform_multibyte_at_snippet_boundary() {
-        let plugin = HeadingIdPlugin::new();
-
-        // Construct a case where a 4-byte emoji lands exactly at position 100
-        // This tests the snippet_end boundary validation
-        let mut jsx = String::from(r#"_jsx(_components.h1, {...props, children: ""#);
-
-        // Add exactly the right amount to put an emoji at the boundary
-        let padding = "a".repeat(80);
-        jsx.push_str(&padding);
-        jsx.push('🔥'); // 4-byte emoji
-        jsx.push_str(r#""});"#);
-
-        let result = plugin.transform_jsx(&mut jsx);
-        assert!(
-            result.is_ok(),
-            "Should handle multibyte char at snippet boundary"
-        );
+    fn test_misplaced_custom_id() {
+        let result = crate::compile("Some {#text}", crate::MdxCompileOptions::new()).unwrap();
+        assert!(!result.code.contains("#text"));
+        assert_eq!(result.diagnostics[0].code, "misplaced-heading-id");
     }
 }
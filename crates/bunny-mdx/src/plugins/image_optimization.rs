@@ -7,7 +7,7 @@
 //! # Features
 //!
 //! - Collects both relative and absolute image URLs
-//! - Thread-safe image collection using Arc<Mutex<Vec<String>>>
+//! - Per-document image collection in the compile's `PluginContext`
 //! - Adds `data-fob-optimized="true"` attribute for tracking
 //! - Supports both inline images and MDX JSX image components
 //!
@@ -20,83 +20,64 @@
 //!
 //! Both images will be collected and marked with `data-fob-optimized="true"`.
 
-use std::sync::{Arc, Mutex};
-
 use anyhow::Result;
 use markdown::mdast::Node;
+use oxc_ast::ast::Program;
+use oxc_ast::AstBuilder;
 
-use super::MdxPlugin;
+use super::{MdxPlugin, PluginContext};
+use crate::codegen::add_element_props;
 
 /// Plugin that collects images for optimization and adds tracking attributes
 ///
 /// This plugin walks the AST and:
 /// 1. Finds all `Node::Image` nodes (markdown images)
 /// 2. Extracts image URLs (both relative and absolute)
-/// 3. Stores them in a thread-safe collection
+/// 3. Stores them in the compile's [`PluginContext`]
 /// 4. Adds data attributes during JSX transformation
 ///
 /// # Thread Safety
 ///
-/// Uses `Arc<Mutex<Vec<String>>>` to safely collect images across multiple
-/// documents processed in parallel.
+/// The plugin holds no state, so one instance can be shared by documents
+/// compiled in parallel; each document collects its own images.
 ///
 /// # Usage
 ///
-/// ```rust,no_run
+/// ```rust
 /// use bunny_mdx::mdx::plugins::ImageOptimizationPlugin;
+/// use bunny_mdx::{compile, MdxCompileOptions};
 ///
-/// let plugin = ImageOptimizationPlugin::new();
-/// // ... use plugin in MdxOptions ...
+/// let options = MdxCompileOptions::new().with_plugin(ImageOptimizationPlugin::new());
+/// let result = compile("![Photo](/photo.jpg)", options).unwrap();
 ///
-/// // After processing, retrieve collected images
-/// let images = plugin.images();
-/// println!("Found {} images", images.len());
+/// // Collected images end up on the compile result
+/// assert_eq!(result.images, ["/photo.jpg"]);
 /// ```
-#[derive(Clone)]
-pub struct ImageOptimizationPlugin {
-    /// Thread-safe collection of image URLs found during AST traversal
-    images: Arc<Mutex<Vec<String>>>,
-}
+#[derive(Clone, Debug, Default)]
+pub struct ImageOptimizationPlugin;
+
+/// Images collected for the current document
+#[derive(Debug, Default)]
+struct CollectedImages(Vec<String>);
 
 impl ImageOptimizationPlugin {
     /// Create a new image optimization plugin
     pub fn new() -> Self {
-        Self {
-            images: Arc::new(Mutex::new(Vec::new())),
-        }
-    }
-
-    /// Get a clone of all collected image URLs
-    ///
-    /// This returns a snapshot of images collected so far. Safe to call
-    /// from any thread.
-    pub fn images(&self) -> Vec<String> {
-        self.images
-            .lock()
-            .expect("Image collection mutex poisoned")
-            .clone()
+        Self
     }
 
-    /// Clear all collected images
-    ///
-    /// Useful for resetting state between batch processing runs.
-    pub fn clear(&self) {
-        self.images
-            .lock()
-            .expect("Image collection mutex poisoned")
-            .clear();
+    /// Image URLs collected from the document compiled with `cx`
+    pub fn images(cx: &PluginContext) -> &[String] {
+        cx.get::<CollectedImages>()
+            .map_or(&[], |images| images.0.as_slice())
     }
 
     /// Walk the AST and collect all image URLs
-    fn collect_images(&self, node: &Node) {
+    fn collect_images(&self, node: &Node, images: &mut Vec<String>) {
         match node {
             Node::Image(image) => {
                 // Collect the image URL
-                let url = image.url.clone();
-                self.images
-                    .lock()
-                    .expect("Image collection mutex poisoned")
-                    .push(url);
+                images.push(image.url.clone());
 
                 tracing::debug!(
                     url = image.url,
@@ -106,77 +87,77 @@ impl ImageOptimizationPlugin {
             }
             Node::Root(root) => {
                 for child in &root.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Paragraph(para) => {
                 for child in &para.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Link(link) => {
                 for child in &link.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::LinkReference(link_ref) => {
                 for child in &link_ref.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Strong(strong) => {
                 for child in &strong.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Emphasis(em) => {
                 for child in &em.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Delete(del) => {
                 for child in &del.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Blockquote(blockquote) => {
                 for child in &blockquote.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::List(list) => {
                 for child in &list.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::ListItem(item) => {
                 for child in &item.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Table(table) => {
                 for child in &table.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::TableRow(row) => {
                 for child in &row.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::TableCell(cell) => {
                 for child in &cell.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::Heading(heading) => {
                 for child in &heading.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             Node::FootnoteDefinition(def) => {
                 for child in &def.children {
-                    self.collect_images(child);
+                    self.collect_images(child, images);
                 }
             }
             // Other node types don't contain images
@@ -185,34 +166,35 @@ impl ImageOptimizationPlugin {
     }
 }
 
-impl Default for ImageOptimizationPlugin {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 impl MdxPlugin for ImageOptimizationPlugin {
     fn name(&self) -> &'static str {
         "image-optimization"
     }
 
-    fn transform_ast(&self, ast: &mut Node) -> Result<()> {
-        self.collect_images(ast);
+    fn cache_key(&self) -> Option<String> {
+        Some(self.name().to_string())
+    }
+
+    fn transform_ast(&self, ast: &mut Node, cx: &mut PluginContext) -> Result<()> {
+        let images = &mut cx.get_or_default::<CollectedImages>().0;
+        self.collect_images(ast, images);
         Ok(())
     }
 
-    fn transform_jsx(&self, jsx: &mut String) -> Result<()> {
+    fn transform_program<'a>(
+        &self,
+        program: &mut Program<'a>,
+        ast: AstBuilder<'a>,
+        _cx: &mut PluginContext,
+    ) -> Result<()> {
         // Add data-fob-optimized="true" to all img elements
-        // Pattern: _jsx(_components.img, {
-        // We need to inject data-fob-optimized: "true" into the props
-
-        // Simple string replacement approach
-        // In production, you'd want to parse the JSX AST for safety
-        *jsx = jsx.replace(
-            "_jsx(_components.img, {",
-            "_jsx(_components.img, {\"data-fob-optimized\": \"true\", ",
-        );
-
+        add_element_props(program, ast, |element| {
+            if element.tag == "_components.img" {
+                vec![("data-fob-optimized".to_string(), "true".to_string())]
+            } else {
+                Vec::new()
+            }
+        });
         Ok(())
     }
 
@@ -225,6 +207,10 @@ impl MdxPlugin for ImageOptimizationPlugin {
 mod tests {
     use super::*;
     use markdown::mdast::{Image, Paragraph, Root};
+    use oxc_allocator::Allocator;
+    use oxc_codegen::Codegen;
+    use oxc_parser::Parser;
+    use oxc_span::SourceType;
 
     #[test]
     fn test_image_collection() {
@@ -255,19 +241,18 @@ mod tests {
             position: None,
         });
 
-        plugin.collect_images(&ast);
+        let mut cx = PluginContext::default();
+        plugin.transform_ast(&mut ast.clone(), &mut cx).unwrap();
 
-        let images = plugin.images();
+        let images = ImageOptimizationPlugin::images(&cx);
         assert_eq!(images.len(), 2);
         assert_eq!(images[0], "/images/photo1.jpg");
         assert_eq!(images[1], "./local.png");
     }
 
     #[test]
-    fn test_clear_images() {
+    fn test_images_are_per_context() {
         let plugin = ImageOptimizationPlugin::new();
-
-        // Add some images
         let ast = Node::Root(Root {
             children: vec![Node::Paragraph(Paragraph {
                 children: vec![Node::Image(Image {
@@ -281,12 +266,14 @@ mod tests {
             position: None,
         });
 
-        plugin.collect_images(&ast);
-        assert_eq!(plugin.images().len(), 1);
+        let mut first = PluginContext::default();
+        plugin.transform_ast(&mut ast.clone(), &mut first).unwrap();
+        let mut second = PluginContext::default();
+        plugin.transform_ast(&mut ast.clone(), &mut second).unwrap();
 
-        // Clear and verify
-        plugin.clear();
-        assert_eq!(plugin.images().len(), 0);
+        assert_eq!(ImageOptimizationPlugin::images(&first), ["/test.jpg"]);
+        assert_eq!(ImageOptimizationPlugin::images(&second), ["/test.jpg"]);
+        assert!(ImageOptimizationPlugin::images(&PluginContext::default()).is_empty());
     }
 
     #[test]
@@ -312,21 +299,31 @@ mod tests {
             position: None,
         });
 
-        plugin.collect_images(&ast);
-        let images = plugin.images();
+        let mut cx = PluginContext::default();
+        plugin.transform_ast(&mut ast.clone(), &mut cx).unwrap();
+        let images = ImageOptimizationPlugin::images(&cx);
         assert_eq!(images.len(), 1);
         assert_eq!(images[0], "/nested.jpg");
     }
 
     #[test]
-    fn test_jsx_transformation() {
+    fn test_program_transformation() {
         let plugin = ImageOptimizationPlugin::new();
-
-        let mut jsx = String::from(r#"_jsx(_components.img, {src: "/test.jpg", alt: "Test"})"#);
-
-        plugin.transform_jsx(&mut jsx).unwrap();
-
-        assert!(jsx.contains("data-fob-optimized"));
-        assert!(jsx.contains("\"true\""));
+        let allocator = Allocator::default();
+        let code = r#"_jsx(_components.img, {src: "/test.jpg", alt: "Test"})"#;
+        let mut program = Parser::new(&allocator, code, SourceType::mjs())
+            .parse()
+            .program;
+
+        plugin
+            .transform_program(
+                &mut program,
+                AstBuilder::new(&allocator),
+                &mut PluginContext::default(),
+            )
+            .unwrap();
+
+        let js = Codegen::new().build(&program).code;
+        assert!(js.contains(r#""data-fob-optimized": "true""#));
     }
 }
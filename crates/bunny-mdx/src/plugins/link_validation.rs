@@ -1,15 +1,18 @@
 //! Link validation plugin for detecting broken internal links
 //!
 //! This plugin checks all links in MDX documents and warns about potentially
-//! broken internal links. It does not fail compilation, only logs warnings
-//! to help developers catch broken links early.
+//! broken internal links. It does not fail compilation on its own: warnings
+//! are reported as diagnostics (and logged) to help developers catch broken
+//! links early. Set `MdxCompileOptions::warnings_as_errors` to fail on them.
 //!
 //! # Features
 //!
 //! - Validates internal links (starting with `/` or `#`)
 //! - Warns about empty href attributes
 //! - Distinguishes between anchor links and page links
-//! - Non-blocking: logs warnings without failing compilation
+//! - Checks that `#anchor` links point to a heading, using the IDs (and so
+//!   the [`Slugger`](super::Slugger)) of `HeadingIdPlugin`
+//! - Non-blocking: reports warning diagnostics without failing compilation
 //!
 //! # Example
 //!
@@ -22,12 +25,15 @@
 //!
 //! The plugin will warn about the empty link but allow compilation to proceed.
 
+use std::collections::HashSet;
+
 use anyhow::Result;
-use markdown::mdast::Node;
+use markdown::mdast::{AttributeContent, AttributeValue, MdxJsxAttribute, Node};
 
-use super::MdxPlugin;
+use super::{HeadingIds, MdxPlugin, PluginContext};
+use crate::diagnostic::Diagnostic;
 
-/// Plugin that validates internal links and logs warnings for potential issues
+/// Plugin that validates internal links and reports warnings for potential issues
 ///
 /// This plugin performs static analysis of links without accessing the filesystem
 /// or network. It warns about:
@@ -38,8 +44,17 @@ use super::MdxPlugin;
 ///
 /// # Non-Blocking Behavior
 ///
-/// This plugin never returns an error. All issues are reported via `tracing::warn!`
-/// to avoid breaking the build for link issues that might be false positives.
+/// This plugin never returns an error. Issues are reported as warning
+/// diagnostics (codes `empty-link`, `empty-anchor`, `double-slash-link`,
+/// `markdown-file-link` and `missing-anchor`) so link issues that might be
+/// false positives only break the build when warnings are promoted to errors.
+///
+/// # Anchors
+///
+/// Register the plugin after [`HeadingIdPlugin`](super::HeadingIdPlugin) to
+/// check `#anchor` links: they must match a heading ID, as made by that
+/// plugin's slugger, or the `id` of a JSX element. Without it, anchors aren't
+/// checked.
 ///
 /// # Usage
 ///
@@ -64,93 +79,18 @@ impl LinkValidationPlugin {
     /// Returns true if the link appears valid, false otherwise.
     /// Logs warnings for suspicious patterns.
     pub fn validate_link(&self, url: &str, context: &str) -> bool {
-        // Empty URLs
-        if url.is_empty() {
-            tracing::warn!(
-                url = url,
-                context = context,
-                "Empty link URL detected in MDX"
-            );
-            return false;
-        }
-
-        // Anchor links (#section)
-        if url.starts_with('#') {
-            if url.len() == 1 {
-                tracing::warn!(
-                    url = url,
-                    context = context,
-                    "Anchor link with no target (#) detected"
-                );
-                return false;
-            }
-            // Anchor links are generally fine, but we could validate the ID exists
-            tracing::debug!(url = url, "Anchor link detected");
-            return true;
-        }
-
-        // Internal page links (/path/to/page)
-        if url.starts_with('/') {
-            // Basic validation: check for suspicious patterns
-            if url.contains("//") && !url.starts_with("//") {
-                tracing::warn!(
-                    url = url,
-                    context = context,
-                    "Internal link contains double slashes (potential typo)"
-                );
-            }
-
-            if url.ends_with('/') && url.len() > 1 {
-                tracing::debug!(
-                    url = url,
-                    "Internal link ends with slash (might want trailing slash handling)"
-                );
-            }
-
-            // Check for common file extensions that might indicate broken links
-            if url.ends_with(".md") || url.ends_with(".mdx") {
-                tracing::warn!(
-                    url = url,
-                    context = context,
-                    "Internal link points to .md/.mdx file (should link to rendered page instead)"
-                );
-            }
-
-            return true;
-        }
-
-        // Relative links (./path or ../path)
-        if url.starts_with("./") || url.starts_with("../") {
-            tracing::debug!(
-                url = url,
-                "Relative link detected (ensure relative paths are correct)"
-            );
-            return true;
-        }
-
-        // External links (http://, https://, mailto:, etc.)
-        if url.starts_with("http://")
-            || url.starts_with("https://")
-            || url.starts_with("mailto:")
-            || url.starts_with("tel:")
-        {
-            tracing::debug!(url = url, "External link detected");
-            return true;
-        }
-
-        // Protocol-relative URLs (//example.com)
-        if url.starts_with("//") {
-            tracing::debug!(url = url, "Protocol-relative URL detected");
-            return true;
-        }
-
-        // Other patterns might be valid (e.g., custom protocols, data URIs)
-        tracing::debug!(url = url, "Link with non-standard format detected");
-        true
+        log_issues(url, context, &link_issues(url))
     }
 
-    /// Walk the AST and validate all links
-    fn validate_links(&self, node: &Node) {
+    /// Walk the AST and report issues with all links
+    ///
+    /// `anchors` are the IDs `#fragment` links may point to, when known.
+    fn validate_links(
+        &self,
+        node: &Node,
+        anchors: Option<&HashSet<String>>,
+        cx: &mut PluginContext,
+    ) {
         match node {
             Node::Link(link) => {
                 let context = link
@@ -166,7 +106,25 @@ impl LinkValidationPlugin {
                     .collect::<Vec<_>>()
                     .join("");
 
-                self.validate_link(&link.url, &context);
+                let issues = link_issues(&link.url);
+                log_issues(&link.url, &context, &issues);
+                for (code, message) in issues {
+                    cx.report(
+                        Diagnostic::warning(code, format!("{}: `{}`", message, link.url))
+                            .with_position(link.position.as_ref()),
+                    );
+                }
+                if let (Some(anchors), Some(fragment)) = (anchors, link.url.strip_prefix('#')) {
+                    if !fragment.is_empty() && !anchors.contains(&percent_decode(fragment)) {
+                        cx.report(
+                            Diagnostic::warning(
+                                "missing-anchor",
+                                format!("Anchor link target not found: `{}`", link.url),
+                            )
+                            .with_position(link.position.as_ref()),
+                        );
+                    }
+                }
             }
             Node::LinkReference(link_ref) => {
                 // Link references use a definition elsewhere
@@ -178,67 +136,67 @@ impl LinkValidationPlugin {
             }
             Node::Root(root) => {
                 for child in &root.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Paragraph(para) => {
                 for child in &para.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Heading(heading) => {
                 for child in &heading.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Strong(strong) => {
                 for child in &strong.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Emphasis(em) => {
                 for child in &em.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Delete(del) => {
                 for child in &del.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Blockquote(blockquote) => {
                 for child in &blockquote.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::List(list) => {
                 for child in &list.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::ListItem(item) => {
                 for child in &item.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::Table(table) => {
                 for child in &table.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::TableRow(row) => {
                 for child in &row.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::TableCell(cell) => {
                 for child in &cell.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             Node::FootnoteDefinition(def) => {
                 for child in &def.children {
-                    self.validate_links(child);
+                    self.validate_links(child, anchors, cx);
                 }
             }
             // Other node types don't contain links
@@ -252,8 +210,19 @@ impl MdxPlugin for LinkValidationPlugin {
         "link-validation"
     }
 
-    fn transform_ast(&self, ast: &mut Node) -> Result<()> {
-        self.validate_links(ast);
+    fn cache_key(&self) -> Option<String> {
+        Some(self.name().to_string())
+    }
+
+    fn transform_ast(&self, ast: &mut Node, cx: &mut PluginContext) -> Result<()> {
+        // Heading IDs come from `HeadingIdPlugin`, so anchors are only
+        // checked when it ran first, and with its slugger
+        let anchors = HeadingIds::get(cx).map(|ids| {
+            let mut anchors: HashSet<String> = ids.iter().cloned().collect();
+            collect_element_ids(ast, &mut anchors);
+            anchors
+        });
+        self.validate_links(ast, anchors.as_ref(), cx);
         Ok(())
     }
 
@@ -264,6 +233,90 @@ impl MdxPlugin for LinkValidationPlugin {
     // No JSX transformation needed
 }
 
+/// Literal `id` attributes of JSX elements, which anchors may point to
+fn collect_element_ids(node: &Node, ids: &mut HashSet<String>) {
+    let attributes = match node {
+        Node::MdxJsxFlowElement(element) => element.attributes.as_slice(),
+        Node::MdxJsxTextElement(element) => element.attributes.as_slice(),
+        _ => &[],
+    };
+    for attribute in attributes {
+        if let AttributeContent::Property(MdxJsxAttribute {
+            name,
+            value: Some(AttributeValue::Literal(id)),
+            ..
+        }) = attribute
+        {
+            if name == "id" {
+                ids.insert(id.clone());
+            }
+        }
+    }
+    for child in node.children().into_iter().flatten() {
+        collect_element_ids(child, ids);
+    }
+}
+
+/// Decode `%XX` escapes, as browsers do before matching a fragment
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Log `issues` found in `url`; true when none of them makes the link
+/// invalid
+fn log_issues(url: &str, context: &str, issues: &[(&str, &str)]) -> bool {
+    for (code, message) in issues {
+        tracing::warn!(url = url, context = context, code = code, "{}", message);
+    }
+    !issues
+        .iter()
+        .any(|(code, _)| matches!(*code, "empty-link" | "empty-anchor"))
+}
+
+/// Problems with a link URL, as (diagnostic code, message)
+fn link_issues(url: &str) -> Vec<(&'static str, &'static str)> {
+    let mut issues = Vec::new();
+    if url.is_empty() {
+        issues.push(("empty-link", "Empty link URL"));
+    } else if url == "#" {
+        issues.push(("empty-anchor", "Anchor link with no target"));
+    } else if url.starts_with('/') && !url.starts_with("//") {
+        if url.contains("//") {
+            issues.push((
+                "double-slash-link",
+                "Internal link contains double slashes (potential typo)",
+            ));
+        }
+        if url.ends_with(".md") || url.ends_with(".mdx") {
+            issues.push((
+                "markdown-file-link",
+                "Internal link points to a .md/.mdx file (link to the rendered page instead)",
+            ));
+        }
+    }
+    issues
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,8 +398,10 @@ mod tests {
         });
 
         // Should not panic or error
-        let result = plugin.transform_ast(&mut ast.clone());
+        let mut cx = PluginContext::default();
+        let result = plugin.transform_ast(&mut ast.clone(), &mut cx);
         assert!(result.is_ok());
+        assert!(cx.diagnostics().is_empty());
     }
 
     #[test]
@@ -368,4 +423,60 @@ mod tests {
         let plugin = LinkValidationPlugin::new();
         assert!(plugin.validate_link("//cdn.example.com/script.js", "CDN"));
     }
+
+    #[test]
+    fn test_issues_are_reported_as_diagnostics() {
+        let mut ast = markdown::to_mdast(
+            "[a]() and [b](/docs//page.mdx)",
+            &markdown::ParseOptions::default(),
+        )
+        .unwrap();
+        let mut cx = PluginContext::default();
+        LinkValidationPlugin::new()
+            .transform_ast(&mut ast, &mut cx)
+            .unwrap();
+
+        let codes: Vec<&str> = cx.diagnostics().iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            ["empty-link", "double-slash-link", "markdown-file-link"]
+        );
+        let span = cx.diagnostics()[1].span.unwrap();
+        assert_eq!((span.start.line, span.start.column), (1, 11));
+    }
+
+    #[test]
+    fn test_anchors_use_heading_ids() {
+        use crate::plugins::{GithubSlugger, HeadingIdPlugin};
+        use crate::{compile, MdxCompileOptions};
+
+        let mdx = "## Rust & Wasm\n\n## 文档 {#docs}\n\n<div id=\"box\" />\n\n\
+                   [a](#rust--wasm) [b](#rust-wasm) [c](#docs) [d](#box) [e](#%E6%96%87%E6%A1%A3)";
+        let options = MdxCompileOptions::new()
+            .with_plugin(HeadingIdPlugin::new().with_slugger(GithubSlugger))
+            .with_plugin(LinkValidationPlugin::new());
+        let diagnostics = compile(mdx, options).unwrap().diagnostics;
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| d.code == "missing-anchor")
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Anchor link target not found: `#rust-wasm`",
+                "Anchor link target not found: `#%E6%96%87%E6%A1%A3`"
+            ]
+        );
+
+        // Without heading IDs, anchors aren't checked
+        let options = MdxCompileOptions::new().with_plugin(LinkValidationPlugin::new());
+        assert!(compile(mdx, options).unwrap().diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("api-%E6%96%87%E6%A1%A3"), "api-文档");
+        assert_eq!(percent_decode("100%-done%2"), "100%-done%2");
+    }
 }
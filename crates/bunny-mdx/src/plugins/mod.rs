@@ -1,11 +1,16 @@
 //! Plugin system for MDX transformations
 
+mod context;
 mod heading_ids;
 mod image_optimization;
 mod link_validation;
+mod slugger;
 mod trait_def;
 
-pub use heading_ids::HeadingIdPlugin;
+pub use context::PluginContext;
+pub(crate) use heading_ids::HeadingIds;
+pub use heading_ids::{AnchorPosition, HeadingAnchor, HeadingIdPlugin, TocEntry};
 pub use image_optimization::ImageOptimizationPlugin;
 pub use link_validation::LinkValidationPlugin;
+pub use slugger::{DefaultSlugger, GithubSlugger, Slugger};
 pub use trait_def::MdxPlugin;
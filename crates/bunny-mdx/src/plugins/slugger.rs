@@ -0,0 +1,122 @@
+//! Slug algorithms for heading IDs
+//!
+//! [`HeadingIdPlugin`](super::HeadingIdPlugin) turns heading text into an ID
+//! with a [`Slugger`]. Duplicates get `-1`, `-2`, ... suffixes whichever
+//! slugger is used, the same way as `github-slugger`.
+
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Turns heading text into an anchor ID
+///
+/// `slug` is called once per heading, with its plain text. It doesn't need to
+/// make IDs unique: the caller adds suffixes to duplicates.
+pub trait Slugger: Debug + Send + Sync {
+    fn slug(&self, text: &str) -> String;
+
+    /// Key identifying the slug algorithm for
+    /// [`MdxPlugin::cache_key`](super::MdxPlugin::cache_key)
+    ///
+    /// `None` (the default) disables caching of compilers using this slugger.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Bunny's default slugs
+///
+/// ASCII letters are lowercased and every run of other characters than
+/// letters and digits becomes one `-`, trimmed at both ends. Empty slugs fall
+/// back to `heading`.
+///
+/// - "Hello World" → "hello-world"
+/// - "Rust & TypeScript" → "rust-typescript"
+/// - "  Multiple   Spaces  " → "multiple-spaces"
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultSlugger;
+
+impl Slugger for DefaultSlugger {
+    fn cache_key(&self) -> Option<String> {
+        Some("default".to_string())
+    }
+
+    fn slug(&self, text: &str) -> String {
+        let mut slug = String::new();
+        let mut last_was_sep = true; // Start true to skip leading hyphens
+
+        for ch in text.chars() {
+            if ch.is_alphanumeric() {
+                slug.push(ch.to_ascii_lowercase());
+                last_was_sep = false;
+            } else if !last_was_sep {
+                slug.push('-');
+                last_was_sep = true;
+            }
+        }
+
+        // Remove trailing hyphen if present
+        if slug.ends_with('-') {
+            slug.pop();
+        }
+
+        // Return "heading" as fallback for empty slugs
+        if slug.is_empty() {
+            String::from("heading")
+        } else {
+            slug
+        }
+    }
+}
+
+/// Slugs as GitHub makes them for READMEs (`github-slugger`)
+///
+/// Text is lowercased, characters other than letters, marks, digits, `_`,
+/// `-` and spaces are removed, and each space becomes a `-`. Non-Latin text
+/// is kept as is. Unlike [`DefaultSlugger`], runs aren't collapsed and there
+/// is no fallback, so a heading of only symbols gets an empty slug.
+///
+/// - "Hello World" → "hello-world"
+/// - "Rust & TypeScript" → "rust--typescript"
+/// - "API 文档" → "api-文档"
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GithubSlugger;
+
+impl Slugger for GithubSlugger {
+    fn cache_key(&self) -> Option<String> {
+        Some("github".to_string())
+    }
+
+    fn slug(&self, text: &str) -> String {
+        static REMOVED: OnceLock<Regex> = OnceLock::new();
+        let removed = REMOVED
+            .get_or_init(|| Regex::new(r"[^\p{L}\p{M}\p{Nd}\p{Pc} -]").expect("valid regex"));
+        removed
+            .replace_all(&text.to_lowercase(), "")
+            .replace(' ', "-")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_slugs() {
+        // As github-slugger slugs them
+        for (text, slug) in [
+            ("Hello World", "hello-world"),
+            ("Rust & TypeScript", "rust--typescript"),
+            ("foo_bar-baz", "foo_bar-baz"),
+            ("Привет non-latin 你好", "привет-non-latin-你好"),
+            ("😄 emoji", "-emoji"),
+            ("Über Straße", "über-straße"),
+            ("v2.0 (beta)!", "v20-beta"),
+            ("देवनागरी लिपि", "देवनागरी-लिपि"),
+            ("@#$%", ""),
+        ] {
+            assert_eq!(GithubSlugger.slug(text), slug, "{}", text);
+        }
+    }
+}
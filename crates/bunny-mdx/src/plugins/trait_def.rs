@@ -1,23 +1,29 @@
 //! Plugin trait for transforming MDX AST and JSX output
 //!
-//! The MDX plugin system allows custom transformations of both the markdown AST
-//! (before JSX conversion) and the generated JSX string (after conversion).
+//! The MDX plugin system allows custom transformations of the markdown AST
+//! (before JSX conversion), the compiled `oxc_ast` program (before printing)
+//! and the generated JSX string (after printing).
 
 use anyhow::Result;
 use markdown::mdast::Node;
+use oxc_ast::ast::Program;
+use oxc_ast::AstBuilder;
 use std::any::Any;
 
+use super::PluginContext;
+
 /// Plugin for transforming MDX AST and JSX output
 ///
 /// Implement this trait to create custom MDX transformations. Plugins can
-/// modify the markdown AST before JSX generation or transform the JSX string
-/// after generation.
+/// modify the markdown AST before JSX generation, the compiled program
+/// before it is printed, or the JSX string after generation.
 ///
 /// # Thread Safety
 ///
 /// Plugins must be `Send + Sync` because MDX files may be processed in parallel.
-/// If you need to accumulate state across transformations, use thread-safe
-/// primitives like `Arc<Mutex<T>>` or `Arc<RwLock<T>>`.
+/// One instance is shared by every document compiled with the same options,
+/// so keep per-document state (collected images, counters) in the
+/// [`PluginContext`] passed to each hook, not in the plugin.
 ///
 /// # Performance
 ///
@@ -31,6 +37,21 @@ pub trait MdxPlugin: Send + Sync {
     /// lowercase identifier like "heading-ids" or "image-optimization".
     fn name(&self) -> &'static str;
 
+    /// Key covering everything in the plugin's configuration that affects
+    /// the output
+    ///
+    /// Compilers that cache results (like `bunny::Compiler`) hash this with
+    /// the other options, so two plugins with the same name and key must
+    /// produce the same output. Return `None` when the configuration can't
+    /// be described (a closure, say) and results won't be cached.
+    ///
+    /// # Default Implementation
+    ///
+    /// Returns `None`. Plugins without configuration can return their name.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+
     /// Transform the markdown AST before JSX conversion
     ///
     /// This method receives a mutable reference to the AST root node, allowing
@@ -41,8 +62,35 @@ pub trait MdxPlugin: Send + Sync {
     ///
     /// The default implementation does nothing and returns `Ok(())`. Override this
     /// method to perform AST transformations.
-    fn transform_ast(&self, ast: &mut Node) -> Result<()> {
-        let _ = ast;
+    fn transform_ast(&self, ast: &mut Node, cx: &mut PluginContext) -> Result<()> {
+        let _ = (ast, cx);
+        Ok(())
+    }
+
+    /// Transform the compiled program before it is printed
+    ///
+    /// The program holds the imports, exports and the `MDXContent` component.
+    /// Elements are `_jsx`/`_jsxs` calls, `_jsxDEV` calls in development mode,
+    /// or JSX syntax with `OutputFormat::Jsx`. Allocate new nodes with `ast`;
+    /// [`crate::codegen::add_element_props`] covers the common case of adding
+    /// props to elements in any of these shapes.
+    ///
+    /// # Default Implementation
+    ///
+    /// The default implementation does nothing and returns `Ok(())`. Override this
+    /// method to perform program transformations.
+    ///
+    /// # Source Maps
+    ///
+    /// Source maps are generated from node spans when the program is printed,
+    /// so they stay accurate after this hook. New nodes can use `SPAN`.
+    fn transform_program<'a>(
+        &self,
+        program: &mut Program<'a>,
+        ast: AstBuilder<'a>,
+        cx: &mut PluginContext,
+    ) -> Result<()> {
+        let _ = (program, ast, cx);
         Ok(())
     }
 
@@ -60,15 +108,21 @@ pub trait MdxPlugin: Send + Sync {
     ///
     /// Be careful when injecting user content into JSX strings. Ensure proper
     /// escaping to prevent XSS vulnerabilities.
-    fn transform_jsx(&self, jsx: &mut String) -> Result<()> {
-        let _ = jsx;
+    ///
+    /// # Source Maps
+    ///
+    /// Source maps are generated before this hook runs. Edits that insert or
+    /// remove text shift the generated columns that follow them, so prefer
+    /// `transform_program` when source maps matter.
+    fn transform_jsx(&self, jsx: &mut String, cx: &mut PluginContext) -> Result<()> {
+        let _ = (jsx, cx);
         Ok(())
     }
 
     /// Enable downcasting to concrete plugin types
     ///
-    /// This method allows the bundler to downcast trait objects to specific
-    /// plugin implementations to access plugin-specific data (e.g., collected images).
+    /// This method allows callers to downcast trait objects to specific
+    /// plugin implementations (e.g., to read their configuration).
     ///
     /// # Implementation
     ///
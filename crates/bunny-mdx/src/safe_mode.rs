@@ -0,0 +1,365 @@
+//! Safe mode for untrusted MDX
+//!
+//! MDX is a programming language: ESM blocks and `{expressions}` run with the
+//! privileges of whatever renders the compiled output. When content comes
+//! from authors who shouldn't have that (a CMS, a database), [`SafeMode`]
+//! rejects those constructs before anything is compiled.
+
+use markdown::mdast::{AttributeContent, AttributeValue, Node};
+use markdown::unist::Position;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{ChainElement, Expression};
+use oxc_parser::Parser;
+use oxc_span::SourceType;
+
+use crate::error::MdxError;
+use crate::expression::{heading_id, is_comment_only};
+use crate::utils::offset_to_line_col;
+
+/// Restrictions for compiling untrusted MDX
+///
+/// ESM (`import`/`export`) is always rejected. Expressions are rejected
+/// unless [`SafeExpressions::PropertyAccess`] allows them, and JSX may only
+/// use the names in `allowed_components`.
+///
+/// ```
+/// use bunny_mdx::{compile, MdxCompileOptions, SafeMode};
+///
+/// let safe_mode = SafeMode::new()
+///     .allow_component("Callout")
+///     .allow_property_access(["props", "frontmatter"]);
+/// let options = MdxCompileOptions::new().with_safe_mode(safe_mode);
+///
+/// assert!(compile("<Callout>{frontmatter.title}</Callout>", options.clone()).is_ok());
+/// assert!(compile("import fs from 'node:fs'", options).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SafeMode {
+    /// Which `{expressions}` are allowed (in prose and JSX attributes)
+    pub expressions: SafeExpressions,
+    /// JSX element names allowed in the document (`Callout`, `Tabs.Tab`,
+    /// `div`); fragments are always allowed
+    pub allowed_components: Vec<String>,
+}
+
+/// Expressions allowed in safe mode
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SafeExpressions {
+    /// Reject every expression (comment-only ones render nothing and are
+    /// allowed)
+    #[default]
+    Reject,
+    /// Allow property access on the given roots only, such as
+    /// `props.title`, `frontmatter.tags[0]` or `{...props}`
+    PropertyAccess(Vec<String>),
+}
+
+impl SafeMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow a JSX element name
+    pub fn allow_component(mut self, name: impl Into<String>) -> Self {
+        self.allowed_components.push(name.into());
+        self
+    }
+
+    /// Allow expressions that only access properties of `roots`
+    pub fn allow_property_access<I, S>(mut self, roots: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.expressions =
+            SafeExpressions::PropertyAccess(roots.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+/// Check a parsed document against safe mode restrictions
+///
+/// Fails on the first rejected construct, with its location in `source`.
+pub(crate) fn check(node: &Node, safe_mode: &SafeMode, source: &str) -> Result<(), Box<MdxError>> {
+    match node {
+        Node::MdxjsEsm(esm) => {
+            let first_line = esm.value.lines().next().unwrap_or_default();
+            return Err(rejected(
+                format!("ESM `{}`", first_line),
+                Location::Position(esm.position.as_ref()),
+                source,
+            ));
+        }
+        Node::MdxFlowExpression(expression) => {
+            check_expression(
+                &expression.value,
+                safe_mode,
+                Location::Position(expression.position.as_ref()),
+                source,
+            )?;
+        }
+        Node::MdxTextExpression(expression) => {
+            check_expression(
+                &expression.value,
+                safe_mode,
+                Location::Position(expression.position.as_ref()),
+                source,
+            )?;
+        }
+        Node::MdxJsxFlowElement(element) => {
+            check_element(
+                element.name.as_deref(),
+                &element.attributes,
+                element.position.as_ref(),
+                safe_mode,
+                source,
+            )?;
+        }
+        Node::MdxJsxTextElement(element) => {
+            check_element(
+                element.name.as_deref(),
+                &element.attributes,
+                element.position.as_ref(),
+                safe_mode,
+                source,
+            )?;
+        }
+        _ => {}
+    }
+
+    for child in node.children().into_iter().flatten() {
+        check(child, safe_mode, source)?;
+    }
+    Ok(())
+}
+
+/// Where a construct is in the source
+enum Location<'a> {
+    Position(Option<&'a Position>),
+    /// Byte offset (from markdown-rs stops)
+    Offset(Option<usize>),
+}
+
+fn check_element(
+    name: Option<&str>,
+    attributes: &[AttributeContent],
+    position: Option<&Position>,
+    safe_mode: &SafeMode,
+    source: &str,
+) -> Result<(), Box<MdxError>> {
+    if let Some(name) = name {
+        if !safe_mode
+            .allowed_components
+            .iter()
+            .any(|allowed| allowed == name)
+        {
+            return Err(rejected(
+                format!("Component `<{}>`", name),
+                Location::Position(position),
+                source,
+            ));
+        }
+    }
+
+    for attribute in attributes {
+        match attribute {
+            AttributeContent::Property(property) => {
+                if let Some(AttributeValue::Expression(expression)) = &property.value {
+                    let location = expression.stops.first().map(|(_, offset)| *offset);
+                    check_expression(
+                        &expression.value,
+                        safe_mode,
+                        Location::Offset(location),
+                        source,
+                    )?;
+                }
+            }
+            AttributeContent::Expression(expression) => {
+                let location =
+                    Location::Offset(expression.stops.first().map(|(_, offset)| *offset));
+                let argument = expression.value.trim().strip_prefix("...");
+                let allowed = match (&safe_mode.expressions, argument) {
+                    (SafeExpressions::PropertyAccess(roots), Some(argument)) => {
+                        is_property_access_code(argument, roots)
+                    }
+                    _ => false,
+                };
+                if !allowed {
+                    return Err(rejected(
+                        format!("Spread attribute `{{{}}}`", expression.value.trim()),
+                        location,
+                        source,
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_expression(
+    code: &str,
+    safe_mode: &SafeMode,
+    location: Location<'_>,
+    source: &str,
+) -> Result<(), Box<MdxError>> {
+    if is_comment_only(code) || heading_id(code).is_some() {
+        return Ok(());
+    }
+    let allowed = match &safe_mode.expressions {
+        SafeExpressions::Reject => false,
+        SafeExpressions::PropertyAccess(roots) => is_property_access_code(code, roots),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(rejected(
+            format!("Expression `{{{}}}`", code.trim()),
+            location,
+            source,
+        ))
+    }
+}
+
+/// Whether `code` only reads a property of one of `roots`
+fn is_property_access_code(code: &str, roots: &[String]) -> bool {
+    let allocator = Allocator::default();
+    Parser::new(&allocator, code, SourceType::mjs())
+        .parse_expression()
+        .is_ok_and(|expression| is_property_access(&expression, roots))
+}
+
+/// `root`, `root.a.b`, `root["a"][0]` and `root?.a`
+fn is_property_access(expression: &Expression<'_>, roots: &[String]) -> bool {
+    match expression {
+        Expression::Identifier(identifier) => {
+            roots.iter().any(|root| root == identifier.name.as_str())
+        }
+        Expression::StaticMemberExpression(member) => is_property_access(&member.object, roots),
+        Expression::ComputedMemberExpression(member) => {
+            matches!(
+                member.expression,
+                Expression::StringLiteral(_) | Expression::NumericLiteral(_)
+            ) && is_property_access(&member.object, roots)
+        }
+        Expression::ChainExpression(chain) => match &chain.expression {
+            ChainElement::StaticMemberExpression(member) => {
+                is_property_access(&member.object, roots)
+            }
+            ChainElement::ComputedMemberExpression(member) => {
+                matches!(
+                    member.expression,
+                    Expression::StringLiteral(_) | Expression::NumericLiteral(_)
+                ) && is_property_access(&member.object, roots)
+            }
+            _ => false,
+        },
+        Expression::ParenthesizedExpression(parenthesized) => {
+            is_property_access(&parenthesized.expression, roots)
+        }
+        _ => false,
+    }
+}
+
+/// Error for a rejected construct, located in `source` when possible
+fn rejected(construct: String, location: Location<'_>, source: &str) -> Box<MdxError> {
+    let point = match location {
+        Location::Position(position) => {
+            position.map(|position| (position.start.line, position.start.column))
+        }
+        Location::Offset(offset) => offset.map(|offset| offset_to_line_col(source, offset)),
+    };
+
+    let err = MdxError::unsafe_construct(construct);
+    Box::new(match point {
+        Some((line, column)) => err
+            .with_location(line, column)
+            .with_context(MdxError::extract_context(source, line, 1)),
+        None => err,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compile, MdxCompileOptions};
+
+    fn compile_safe(mdx: &str, safe_mode: SafeMode) -> Result<String, Box<MdxError>> {
+        let options = MdxCompileOptions::new().with_safe_mode(safe_mode);
+        compile(mdx, options).map(|result| result.code)
+    }
+
+    #[test]
+    fn test_esm_is_rejected() {
+        let err = compile_safe("# Hi\n\nimport fs from 'node:fs'\n", SafeMode::new()).unwrap_err();
+        assert_eq!(
+            err.message,
+            "ESM `import fs from 'node:fs'` is not allowed in safe mode"
+        );
+        assert_eq!((err.line, err.column), (Some(3), Some(1)));
+        assert!(err.suggestion.is_some());
+    }
+
+    #[test]
+    fn test_expressions() {
+        let err = compile_safe("Hi {globalThis.fetch('/x')}", SafeMode::new()).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Expression `{globalThis.fetch('/x')}` is not allowed in safe mode"
+        );
+        assert_eq!((err.line, err.column), (Some(1), Some(4)));
+
+        // Comments render nothing, so they are always fine
+        assert!(compile_safe("Hi {/* note */}", SafeMode::new()).is_ok());
+        // So are heading ids
+        assert!(compile_safe("## Setup {#install}", SafeMode::new()).is_ok());
+
+        let safe_mode = SafeMode::new().allow_property_access(["props", "frontmatter"]);
+        assert!(compile_safe(
+            "{props.user?.name} {frontmatter.tags[0]}",
+            safe_mode.clone()
+        )
+        .is_ok());
+        assert!(compile_safe("{props.run()}", safe_mode.clone()).is_err());
+        assert!(compile_safe("{props[key]}", safe_mode.clone()).is_err());
+        assert!(compile_safe("{globalThis.process}", safe_mode).is_err());
+    }
+
+    #[test]
+    fn test_components() {
+        let safe_mode = SafeMode::new()
+            .allow_component("Callout")
+            .allow_property_access(["props"]);
+        assert!(compile_safe(
+            "<Callout kind=\"info\">*Hi*</Callout>\n\n<>a</>",
+            safe_mode.clone()
+        )
+        .is_ok());
+
+        let err = compile_safe(
+            "<Callout>\n  <script>alert(1)</script>\n</Callout>",
+            safe_mode.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Component `<script>` is not allowed in safe mode"
+        );
+        assert_eq!((err.line, err.column), (Some(2), Some(3)));
+
+        // Attribute values and spreads follow the expression rules
+        assert!(compile_safe(
+            "<Callout title={props.title} {...props} />",
+            safe_mode.clone()
+        )
+        .is_ok());
+        let err =
+            compile_safe("<Callout\n  title={fetch('/x')} />", safe_mode.clone()).unwrap_err();
+        assert_eq!((err.line, err.column), (Some(2), Some(10)));
+        let err = compile_safe("<Callout {...window} />", safe_mode).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Spread attribute `{...window}` is not allowed in safe mode"
+        );
+    }
+}
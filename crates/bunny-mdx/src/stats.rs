@@ -0,0 +1,155 @@
+//! Document statistics gathered while the document is compiled
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use markdown::mdast::Node;
+use serde::{Deserialize, Serialize};
+
+/// Reading speed used when `MdxCompileOptions::words_per_minute` isn't set
+pub const DEFAULT_WORDS_PER_MINUTE: u32 = 200;
+
+/// Statistics about a compiled document (`MdxCompileResult::stats`)
+///
+/// Words and characters count prose only: text and inline code, including
+/// JSX children. Code blocks, expressions, ESM and frontmatter are left out.
+/// Nodes added or removed by `transform_ast` plugins are counted as compiled.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentStats {
+    pub words: usize,
+    /// Characters of prose, whitespace included
+    pub characters: usize,
+    /// Estimated minutes to read the prose, rounded up
+    pub reading_time_minutes: u32,
+    pub headings: usize,
+    /// Images, including image references
+    pub images: usize,
+    /// Links, including link references
+    pub links: usize,
+    /// Languages of code fences, sorted
+    pub code_languages: BTreeSet<String>,
+    /// How often each JSX component is used, by name (HTML elements such as
+    /// `<div>` aren't components)
+    pub components: BTreeMap<String, usize>,
+}
+
+impl DocumentStats {
+    /// Count a node (not its children, which are recorded on their own)
+    pub(crate) fn record(&mut self, node: &Node) {
+        match node {
+            // Words are counted over whole blocks, so formatting inside a
+            // word (`un*believ*able`) doesn't split it
+            Node::Paragraph(_) | Node::TableCell(_) => self.record_words(node),
+            Node::Heading(_) => {
+                self.headings += 1;
+                self.record_words(node);
+            }
+            Node::Text(text) => self.characters += text.value.chars().count(),
+            Node::InlineCode(code) => self.characters += code.value.chars().count(),
+            Node::Image(_) | Node::ImageReference(_) => self.images += 1,
+            Node::Link(_) | Node::LinkReference(_) => self.links += 1,
+            Node::Code(code) => {
+                if let Some(lang) = &code.lang {
+                    self.code_languages.insert(lang.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn record_component(&mut self, name: &str) {
+        *self.components.entry(name.to_string()).or_default() += 1;
+    }
+
+    /// Set the reading time for a reading speed
+    pub(crate) fn set_reading_time(&mut self, words_per_minute: u32) {
+        let words_per_minute = words_per_minute.max(1) as usize;
+        self.reading_time_minutes = self.words.div_ceil(words_per_minute) as u32;
+    }
+
+    /// Count the words of a block's inline text
+    fn record_words(&mut self, block: &Node) {
+        let mut text = String::new();
+        prose(block, &mut text);
+        // Punctuation next to formatting (`*word*.`) is not a word
+        self.words += text
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+    }
+}
+
+/// Append the prose (text and inline code) under `node` to `text`
+fn prose(node: &Node, text: &mut String) {
+    match node {
+        Node::Text(t) => text.push_str(&t.value),
+        Node::InlineCode(code) => text.push_str(&code.value),
+        _ => {
+            for child in node.children().into_iter().flatten() {
+                prose(child, text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compile, MdxCompileOptions};
+
+    #[test]
+    fn test_stats() {
+        let mdx = "---\ntitle: Not counted\n---\n\nimport { Chart } from './chart'\n\n\
+                   # Hello *world*\n\n\
+                   Read [the docs](/docs) and [more][ref] with `npm i`.\n\n\
+                   ![img](/a.png)\n\n\
+                   ```rust\nfn main() {}\n```\n\n```js\nlet a;\n```\n\n```\nplain\n```\n\n\
+                   <Callout>\n  Some {props.value} text <Chart /> <Chart />\n</Callout>\n\n\
+                   <div>Café</div>\n\n## Bye\n\n[ref]: /ref";
+        let stats = compile(mdx, MdxCompileOptions::new()).unwrap().stats;
+
+        assert_eq!(stats.words, 14);
+        assert_eq!(stats.characters, 64);
+        assert_eq!(stats.reading_time_minutes, 1);
+        assert_eq!(stats.headings, 2);
+        assert_eq!(stats.images, 1);
+        assert_eq!(stats.links, 2);
+        assert_eq!(
+            stats.code_languages.into_iter().collect::<Vec<_>>(),
+            ["js", "rust"]
+        );
+        assert_eq!(
+            stats.components.into_iter().collect::<Vec<_>>(),
+            [("Callout".to_string(), 1), ("Chart".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_words_with_inline_formatting() {
+        let mdx = "foo**bar** un*believ*able `a`b *a* b\n\n# Head*ing*\n\n| x**y** |\n|---|\n| <b>c</b>d |";
+        let stats = compile(mdx, MdxCompileOptions::new().with_all_features())
+            .unwrap()
+            .stats;
+        let text = crate::extract_text(
+            mdx,
+            &MdxCompileOptions::new().with_all_features(),
+            &crate::TextOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(stats.words, 8);
+        assert_eq!(stats.words, text.split_whitespace().count());
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let mdx = "word ".repeat(450);
+        let stats = compile(&mdx, MdxCompileOptions::new()).unwrap().stats;
+        assert_eq!(stats.words, 450);
+        assert_eq!(stats.reading_time_minutes, 3);
+
+        let options = MdxCompileOptions::new().with_words_per_minute(450);
+        let stats = compile(&mdx, options).unwrap().stats;
+        assert_eq!(stats.reading_time_minutes, 1);
+
+        let stats = compile("", MdxCompileOptions::new()).unwrap().stats;
+        assert_eq!(stats.reading_time_minutes, 0);
+    }
+}
@@ -0,0 +1,275 @@
+//! Plain text from MDX, for search indexes and `<meta name="description">`
+//!
+//! JSX elements are unwrapped to their text; ESM, expressions, frontmatter
+//! and raw HTML are skipped. Blocks are separated by a blank line, list
+//! items and table rows by a newline.
+
+use markdown::mdast::Node;
+
+use crate::error::MdxError;
+use crate::{parse, MdxCompileOptions};
+
+/// Comment text of the excerpt marker, `{/* more */}`
+const MORE_MARKER: &str = "more";
+
+/// Options for [`extract_text`] and [`to_text`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextOptions {
+    /// Include the content of code blocks (left out by default)
+    pub code_blocks: bool,
+    /// Stop at the first `{/* more */}` marker
+    pub excerpt: bool,
+    /// Cut the text after this many words, ending it with `…`
+    pub max_words: Option<usize>,
+}
+
+impl TextOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_code_blocks(mut self) -> Self {
+        self.code_blocks = true;
+        self
+    }
+
+    /// Stop at the first `{/* more */}` marker
+    pub fn with_excerpt(mut self) -> Self {
+        self.excerpt = true;
+        self
+    }
+
+    pub fn with_max_words(mut self, max_words: usize) -> Self {
+        self.max_words = Some(max_words);
+        self
+    }
+}
+
+/// Extract the plain text of an MDX document
+///
+/// ```
+/// use bunny_mdx::text::{extract_text, TextOptions};
+/// use bunny_mdx::MdxCompileOptions;
+///
+/// let mdx = "import { Note } from './note'\n\n# Intro\n\n<Note>Read *this* first.</Note>\n\n{/* more */}\n\nThe rest";
+/// let options = MdxCompileOptions::new();
+///
+/// let text = extract_text(mdx, &options, &TextOptions::new())?;
+/// assert_eq!(text, "Intro\n\nRead this first.\n\nThe rest");
+///
+/// let excerpt = extract_text(mdx, &options, &TextOptions::new().with_excerpt())?;
+/// assert_eq!(excerpt, "Intro\n\nRead this first.");
+///
+/// let short = extract_text(mdx, &options, &TextOptions::new().with_max_words(2))?;
+/// assert_eq!(short, "Intro\n\nRead…");
+/// # Ok::<(), Box<bunny_mdx::MdxError>>(())
+/// ```
+pub fn extract_text(
+    source: &str,
+    options: &MdxCompileOptions,
+    text: &TextOptions,
+) -> Result<String, Box<MdxError>> {
+    let root = parse(source, options)?;
+    Ok(to_text(&Node::Root(root), text))
+}
+
+/// Plain text of an mdast tree (see [`extract_text`])
+pub fn to_text(node: &Node, options: &TextOptions) -> String {
+    let mut writer = TextWriter {
+        options,
+        stopped: false,
+    };
+    let text = writer.node(node);
+    match options.max_words {
+        Some(max_words) => truncate_words(&text, max_words),
+        None => text,
+    }
+}
+
+struct TextWriter<'a> {
+    options: &'a TextOptions,
+    /// Set at the excerpt marker; nothing after it is written
+    stopped: bool,
+}
+
+impl TextWriter<'_> {
+    fn node(&mut self, node: &Node) -> String {
+        match node {
+            Node::Root(root) => self.join(&root.children, "\n\n"),
+            Node::Blockquote(quote) => self.join(&quote.children, "\n\n"),
+            Node::FootnoteDefinition(footnote) => self.join(&footnote.children, "\n\n"),
+            Node::MdxJsxFlowElement(element) => self.join(&element.children, "\n\n"),
+            Node::List(list) => self.join(&list.children, "\n"),
+            Node::ListItem(item) => self.join(&item.children, "\n"),
+            Node::Table(table) => self.join(&table.children, "\n"),
+            Node::TableRow(row) => self.join(&row.children, " "),
+
+            Node::Paragraph(paragraph) => self.inline(&paragraph.children),
+            Node::Heading(heading) => self.inline(&heading.children),
+            Node::TableCell(cell) => self.inline(&cell.children),
+            Node::Emphasis(emphasis) => self.concat(&emphasis.children),
+            Node::Strong(strong) => self.concat(&strong.children),
+            Node::Delete(delete) => self.concat(&delete.children),
+            Node::Link(link) => self.concat(&link.children),
+            Node::LinkReference(link_ref) => self.concat(&link_ref.children),
+            Node::MdxJsxTextElement(element) => self.concat(&element.children),
+
+            Node::Text(text) => text.value.clone(),
+            Node::InlineCode(code) => code.value.clone(),
+            Node::InlineMath(math) => math.value.clone(),
+            Node::Math(math) => math.value.clone(),
+            Node::Image(image) => image.alt.clone(),
+            Node::ImageReference(image_ref) => image_ref.alt.clone(),
+            Node::Break(_) => "\n".to_string(),
+            Node::Code(code) if self.options.code_blocks => code.value.clone(),
+
+            Node::MdxFlowExpression(expression) => {
+                self.check_marker(&expression.value);
+                String::new()
+            }
+            Node::MdxTextExpression(expression) => {
+                self.check_marker(&expression.value);
+                String::new()
+            }
+
+            // ESM, frontmatter, raw HTML, definitions, footnote references, ...
+            _ => String::new(),
+        }
+    }
+
+    /// Block children, separated by `separator`
+    fn join(&mut self, children: &[Node], separator: &str) -> String {
+        let mut parts = Vec::new();
+        for child in children {
+            let text = self.node(child);
+            let text = text.trim();
+            if !text.is_empty() {
+                parts.push(text.to_string());
+            }
+            if self.stopped {
+                break;
+            }
+        }
+        parts.join(separator)
+    }
+
+    /// Text of a paragraph, heading or table cell
+    fn inline(&mut self, children: &[Node]) -> String {
+        collapse_spaces(&self.concat(children))
+    }
+
+    /// Inline children, as one run of text
+    fn concat(&mut self, children: &[Node]) -> String {
+        let mut text = String::new();
+        for child in children {
+            text.push_str(&self.node(child));
+            if self.stopped {
+                break;
+            }
+        }
+        text
+    }
+
+    fn check_marker(&mut self, expression: &str) {
+        if !self.options.excerpt || !crate::expression::is_comment_only(expression) {
+            return;
+        }
+        let comment = expression
+            .trim()
+            .trim_start_matches("/*")
+            .trim_end_matches("*/")
+            .trim_start_matches("//");
+        if comment.trim() == MORE_MARKER {
+            self.stopped = true;
+        }
+    }
+}
+
+/// Collapse the runs of spaces left where expressions and other skipped
+/// nodes were (the ends are trimmed by the enclosing block)
+fn collapse_spaces(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        let is_space = c == ' ' || c == '\t';
+        if is_space && (out.ends_with(' ') || out.ends_with('\n')) {
+            continue;
+        }
+        if c == '\n' && out.ends_with(' ') {
+            out.pop();
+        }
+        out.push(if is_space { ' ' } else { c });
+    }
+    out
+}
+
+/// Keep the first `max_words` words, ending with `…` when words were cut
+fn truncate_words(text: &str, max_words: usize) -> String {
+    let mut words = 0;
+    let mut in_word = false;
+    for (index, c) in text.char_indices() {
+        if c.is_whitespace() {
+            in_word = false;
+        } else if !in_word {
+            if words == max_words {
+                return format!("{}\u{2026}", text[..index].trim_end());
+            }
+            words += 1;
+            in_word = true;
+        }
+    }
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(mdx: &str, options: &TextOptions) -> String {
+        extract_text(mdx, &MdxCompileOptions::new().with_all_features(), options).unwrap()
+    }
+
+    #[test]
+    fn test_blocks_and_inline() {
+        let mdx = "---\ntitle: Hi\n---\n\nexport const a = 1\n\n# Title `code`\n\n\
+                   A [link](/a), ![an image](/i.png) and {a} $x$.\n\n\
+                   > Quote\n\n- one\n- two\n  - three\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n\
+                   ```js\nif (a) {\n  b();\n}\n```\n\n<Card title=\"skipped\">\n  Inside <b>bold</b>\n</Card>";
+        assert_eq!(
+            text(mdx, &TextOptions::new()),
+            "Title code\n\nA link, an image and x.\n\nQuote\n\none\ntwo\nthree\n\na b\n1 2\n\nInside bold"
+        );
+        assert!(text(mdx, &TextOptions::new().with_code_blocks())
+            .contains("1 2\n\nif (a) {\n  b();\n}\n\nInside"));
+    }
+
+    #[test]
+    fn test_excerpt_marker() {
+        let mdx = "First\n\n- item {/* more */} hidden\n\nSecond";
+        assert_eq!(
+            text(mdx, &TextOptions::new().with_excerpt()),
+            "First\n\nitem"
+        );
+
+        // Other comments aren't markers, and the marker is ignored by default
+        let mdx = "First\n\n{/* note */}\n\nSecond\n\n{/*more*/}\n\nThird";
+        assert_eq!(
+            text(mdx, &TextOptions::new().with_excerpt()),
+            "First\n\nSecond"
+        );
+        assert_eq!(text(mdx, &TextOptions::new()), "First\n\nSecond\n\nThird");
+    }
+
+    #[test]
+    fn test_max_words() {
+        assert_eq!(truncate_words("one two  three", 2), "one two\u{2026}");
+        assert_eq!(truncate_words("one two", 2), "one two");
+        assert_eq!(truncate_words("one\n\ntwo", 1), "one\u{2026}");
+        assert_eq!(truncate_words("", 0), "");
+
+        let options = TextOptions::new().with_excerpt().with_max_words(3);
+        assert_eq!(
+            text("Über *naïve* café text\n\n{/* more */}", &options),
+            "Über naïve café\u{2026}"
+        );
+    }
+}
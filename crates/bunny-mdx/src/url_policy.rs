@@ -0,0 +1,172 @@
+//! URL sanitization for links, images and JSX `href`/`src` attributes
+//!
+//! Markdown happily links to `javascript:alert(1)`. A [`UrlPolicy`] limits
+//! the schemes URLs may use, can rewrite URLs first, and decides what happens
+//! to a URL that is not allowed.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Rewrites a URL before it is checked (e.g. to proxy images)
+pub type UrlRewrite = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Policy for URLs in markdown links and images, definitions used by
+/// references, and literal `href`/`src` attributes on MDX JSX elements
+///
+/// Relative URLs (`/docs`, `./a.png`, `#section`, `?q=1`) are always
+/// allowed; absolute URLs must use one of `allowed_schemes`.
+///
+/// ```
+/// use bunny_mdx::{compile, MdxCompileOptions, UrlPolicy, UrlViolation};
+///
+/// let policy = UrlPolicy::new().on_violation(UrlViolation::Error);
+/// let options = MdxCompileOptions::new().with_url_policy(policy);
+///
+/// assert!(compile("[docs](https://example.com)", options.clone()).is_ok());
+/// assert!(compile("[click](javascript:alert(1))", options).is_err());
+/// ```
+#[derive(Clone)]
+pub struct UrlPolicy {
+    /// Schemes allowed in absolute URLs, lowercase and without the `:`
+    /// (default: `http`, `https`, `mailto`, `tel`)
+    pub allowed_schemes: Vec<String>,
+    /// Called with every URL before its scheme is checked
+    pub rewrite: Option<UrlRewrite>,
+    /// What to do with a URL whose scheme is not allowed
+    pub on_violation: UrlViolation,
+}
+
+/// Action taken on a URL that is not allowed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UrlViolation {
+    /// Leave the `href`/`src` attribute out
+    Strip,
+    /// Replace the URL with `#`
+    #[default]
+    Replace,
+    /// Fail compilation with the URL and its location
+    Error,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: ["http", "https", "mailto", "tel"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            rewrite: None,
+            on_violation: UrlViolation::default(),
+        }
+    }
+}
+
+impl fmt::Debug for UrlPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrlPolicy")
+            .field("allowed_schemes", &self.allowed_schemes)
+            .field("rewrite", &self.rewrite.as_ref().map(|_| "<fn>"))
+            .field("on_violation", &self.on_violation)
+            .finish()
+    }
+}
+
+impl UrlPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the allowed schemes
+    pub fn allow_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_schemes = schemes
+            .into_iter()
+            .map(|scheme| scheme.into().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    pub fn with_rewrite(
+        mut self,
+        rewrite: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.rewrite = Some(Arc::new(rewrite));
+        self
+    }
+
+    pub fn on_violation(mut self, action: UrlViolation) -> Self {
+        self.on_violation = action;
+        self
+    }
+
+    /// Rewrite a URL and check its scheme
+    ///
+    /// Returns the URL to use, or the disallowed scheme.
+    pub(crate) fn check(&self, url: &str) -> Result<String, String> {
+        let url = match &self.rewrite {
+            Some(rewrite) => rewrite(url),
+            None => url.to_string(),
+        };
+        match scheme(&url) {
+            Some(scheme) if !self.allowed_schemes.contains(&scheme) => Err(scheme),
+            _ => Ok(url),
+        }
+    }
+}
+
+/// Lowercased scheme of an absolute URL, `None` for relative URLs
+///
+/// Like browsers, ASCII whitespace and control characters are ignored, so
+/// `java\tscript:` is still `javascript`.
+fn scheme(url: &str) -> Option<String> {
+    let cleaned: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect();
+    let end = cleaned.find([':', '/', '?', '#'])?;
+    if !cleaned[end..].starts_with(':') {
+        return None;
+    }
+    Some(cleaned[..end].to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheme() {
+        assert_eq!(scheme("https://example.com"), Some("https".to_string()));
+        assert_eq!(
+            scheme(" JavaScript:alert(1)"),
+            Some("javascript".to_string())
+        );
+        assert_eq!(
+            scheme("java\tscript:alert(1)"),
+            Some("javascript".to_string())
+        );
+        assert_eq!(scheme("/docs/a:b"), None);
+        assert_eq!(scheme("./a.png"), None);
+        assert_eq!(scheme("#top"), None);
+        assert_eq!(scheme("?q=a:b"), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let policy = UrlPolicy::new();
+        assert_eq!(policy.check("mailto:a@b.c"), Ok("mailto:a@b.c".to_string()));
+        assert_eq!(policy.check("data:text/html,x"), Err("data".to_string()));
+
+        let policy = UrlPolicy::new()
+            .allow_schemes(["HTTPS"])
+            .with_rewrite(|url| url.replace("http:", "https:"));
+        assert_eq!(
+            policy.check("http://a.com"),
+            Ok("https://a.com".to_string())
+        );
+        assert_eq!(policy.check("mailto:a@b.c"), Err("mailto".to_string()));
+    }
+}
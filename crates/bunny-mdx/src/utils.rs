@@ -22,6 +22,20 @@ pub(crate) fn offset_to_line_col(source: &str, offset: usize) -> (usize, usize)
     (line, col)
 }
 
+/// Normalize a link reference/definition label for matching.
+///
+/// Follows CommonMark: collapse runs of markdown whitespace to a single
+/// space, trim, and case-fold (lowercase then uppercase, like markdown-rs).
+pub(crate) fn normalize_identifier(value: &str) -> String {
+    value
+        .split([' ', '\t', '\n', '\r'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .to_uppercase()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +56,14 @@ mod tests {
         // Start of "test"
         assert_eq!(offset_to_line_col(source, 12), (3, 1));
     }
+
+    #[test]
+    fn test_normalize_identifier() {
+        assert_eq!(normalize_identifier("Foo"), normalize_identifier("FOO"));
+        assert_eq!(
+            normalize_identifier("  foo \n\t bar "),
+            normalize_identifier("foo bar")
+        );
+        assert_eq!(normalize_identifier("ẞ"), normalize_identifier("ß"));
+    }
 }
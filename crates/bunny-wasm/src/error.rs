@@ -29,6 +29,9 @@ pub enum WasmError {
     #[serde(rename_all = "camelCase")]
     CompilationError {
         message: String,
+        /// Stable error code, such as `MDX0001`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         location: Option<ErrorLocation>,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -64,6 +67,11 @@ pub struct ErrorLocation {
     pub line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub column: Option<usize>,
+    /// Byte offsets of the error in the source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<usize>,
 }
 
 impl WasmError {
@@ -90,6 +98,8 @@ impl WasmError {
                 file: err.file,
                 line: err.line,
                 column: err.column,
+                offset: err.span.map(|span| span.start.offset),
+                end_offset: err.span.map(|span| span.end.offset),
             })
         } else {
             None
@@ -97,6 +107,7 @@ impl WasmError {
 
         Self::CompilationError {
             message: err.message,
+            code: err.code.map(|code| code.to_string()),
             location,
             context: err.context,
             suggestion: err.suggestion,
@@ -162,11 +173,15 @@ impl fmt::Display for WasmError {
             }
             Self::CompilationError {
                 message,
+                code,
                 location,
                 context,
                 suggestion,
             } => {
-                write!(f, "Compilation Error: {}", message)?;
+                match code {
+                    Some(code) => write!(f, "Compilation Error [{}]: {}", code, message)?,
+                    None => write!(f, "Compilation Error: {}", message)?,
+                }
                 if let Some(loc) = location {
                     if let Some(file) = &loc.file {
                         write!(f, "\n  in {}", file)?;
@@ -329,17 +344,20 @@ mod tests {
     fn test_error_display() {
         let err = WasmError::CompilationError {
             message: "Syntax error".to_string(),
+            code: Some("MDX0008".to_string()),
             location: Some(ErrorLocation {
                 file: Some("test.mdx".to_string()),
                 line: Some(5),
                 column: Some(10),
+                offset: None,
+                end_offset: None,
             }),
             context: Some("  5 | import { foo } fro './bar'".to_string()),
             suggestion: Some("Did you mean 'from'?".to_string()),
         };
 
         let display = format!("{}", err);
-        assert!(display.contains("Compilation Error"));
+        assert!(display.contains("Compilation Error [MDX0008]"));
         assert!(display.contains("test.mdx"));
         assert!(display.contains("line 5, column 10"));
         assert!(display.contains("Did you mean 'from'?"));
@@ -58,6 +58,12 @@ pub struct WasmMdxOptions {
     math: bool,
     jsx_runtime: String,
     output_format: String,
+    source_map: bool,
+    development: bool,
+    provider_import_source: Option<String>,
+    raw_html: String,
+    words_per_minute: u32,
+    frontmatter_schema: Option<bunny_mdx::FrontmatterSchema>,
 }
 
 #[wasm_bindgen]
@@ -72,6 +78,12 @@ impl WasmMdxOptions {
             math: false,
             jsx_runtime: "react/jsx-runtime".to_string(),
             output_format: "program".to_string(),
+            source_map: false,
+            development: false,
+            provider_import_source: None,
+            raw_html: "jsx".to_string(),
+            words_per_minute: bunny_mdx::stats::DEFAULT_WORDS_PER_MINUTE,
+            frontmatter_schema: None,
         }
     }
 
@@ -135,11 +147,12 @@ impl WasmMdxOptions {
         self.jsx_runtime.clone()
     }
 
-    /// Set output format ("program" or "function-body")
+    /// Set output format ("program", "function-body" or "jsx")
     #[wasm_bindgen]
     pub fn set_output_format(&mut self, format: &str) {
         self.output_format = match format {
             "function-body" => "function-body".to_string(),
+            "jsx" => "jsx".to_string(),
             _ => "program".to_string(),
         };
     }
@@ -149,6 +162,87 @@ impl WasmMdxOptions {
     pub fn output_format(&self) -> String {
         self.output_format.clone()
     }
+
+    /// Enable/disable source map generation
+    #[wasm_bindgen]
+    pub fn set_source_map(&mut self, enabled: bool) {
+        self.source_map = enabled;
+    }
+
+    /// Get source map setting
+    #[wasm_bindgen(getter)]
+    pub fn source_map(&self) -> bool {
+        self.source_map
+    }
+
+    /// Enable/disable development mode (jsxDEV with source locations)
+    #[wasm_bindgen]
+    pub fn set_development(&mut self, enabled: bool) {
+        self.development = enabled;
+    }
+
+    /// Get development mode setting
+    #[wasm_bindgen(getter)]
+    pub fn development(&self) -> bool {
+        self.development
+    }
+
+    /// Set the module providing `useMDXComponents` (e.g. "@mdx-js/react")
+    #[wasm_bindgen]
+    pub fn set_provider_import_source(&mut self, source: String) {
+        self.provider_import_source = Some(source);
+    }
+
+    /// Get the provider import source
+    #[wasm_bindgen(getter)]
+    pub fn provider_import_source(&self) -> Option<String> {
+        self.provider_import_source.clone()
+    }
+
+    /// Set how raw HTML is compiled ("jsx", "drop" or "dangerously-set-inner-html")
+    #[wasm_bindgen]
+    pub fn set_raw_html(&mut self, policy: &str) {
+        self.raw_html = match policy {
+            "drop" => "drop".to_string(),
+            "dangerously-set-inner-html" => "dangerously-set-inner-html".to_string(),
+            _ => "jsx".to_string(),
+        };
+    }
+
+    /// Get the raw HTML policy
+    #[wasm_bindgen(getter)]
+    pub fn raw_html(&self) -> String {
+        self.raw_html.clone()
+    }
+
+    /// Set the reading speed used for `stats.readingTimeMinutes`
+    #[wasm_bindgen]
+    pub fn set_words_per_minute(&mut self, words_per_minute: u32) {
+        self.words_per_minute = words_per_minute;
+    }
+
+    /// Get the reading speed
+    #[wasm_bindgen(getter)]
+    pub fn words_per_minute(&self) -> u32 {
+        self.words_per_minute
+    }
+
+    /// Set a JSON Schema the frontmatter must match (compilation fails with
+    /// `MDX0105` otherwise)
+    #[wasm_bindgen]
+    pub fn set_frontmatter_schema(&mut self, schema: JsValue) -> Result<(), JsValue> {
+        let invalid = |details: String| {
+            JsValue::from(WasmError::validation_with_details(
+                "Invalid frontmatter schema",
+                details,
+            ))
+        };
+        let schema = serde_wasm_bindgen::from_value(schema).map_err(|e| invalid(e.to_string()))?;
+        let schema =
+            bunny_mdx::FrontmatterSchema::json(schema).map_err(|e| invalid(e.to_string()))?;
+        self.frontmatter_schema = Some(schema);
+        Ok(())
+    }
 }
 
 impl Default for WasmMdxOptions {
@@ -176,9 +270,32 @@ impl From<&WasmMdxOptions> for MdxCompileOptions {
         // Set output format
         rust_opts.output_format = match opts.output_format.as_str() {
             "function-body" => bunny_mdx::OutputFormat::FunctionBody,
+            "jsx" => bunny_mdx::OutputFormat::Jsx,
             _ => bunny_mdx::OutputFormat::Program,
         };
 
+        // Set source map generation
+        rust_opts.source_map = opts.source_map;
+
+        // Set development mode
+        rust_opts.development = opts.development;
+
+        // Set provider import source
+        rust_opts.provider_import_source = opts.provider_import_source.clone();
+
+        // Set raw HTML policy
+        rust_opts.raw_html = match opts.raw_html.as_str() {
+            "drop" => bunny_mdx::RawHtml::Drop,
+            "dangerously-set-inner-html" => bunny_mdx::RawHtml::DangerouslySetInnerHtml,
+            _ => bunny_mdx::RawHtml::Jsx,
+        };
+
+        // Set reading speed
+        rust_opts.words_per_minute = opts.words_per_minute;
+
+        // Set frontmatter schema
+        rust_opts.frontmatter_schema = opts.frontmatter_schema.clone();
+
         rust_opts
     }
 }
@@ -190,6 +307,9 @@ pub struct WasmMdxResult {
     /// Compiled JSX code
     pub code: String,
 
+    /// Source map (v3 JSON string) if enabled
+    pub map: Option<String>,
+
     /// Extracted frontmatter (if present)
     pub frontmatter: Option<WasmFrontmatter>,
 
@@ -207,6 +327,53 @@ pub struct WasmMdxResult {
 
     /// Default export name (if present)
     pub default_export: Option<String>,
+
+    /// Word count, reading time and the constructs used
+    pub stats: WasmDocumentStats,
+}
+
+/// Document statistics (serializable for JS)
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WasmDocumentStats {
+    /// Words of prose (not code, expressions, ESM or frontmatter)
+    pub words: usize,
+
+    /// Characters of prose, whitespace included
+    pub characters: usize,
+
+    /// Estimated reading time in minutes, rounded up
+    pub reading_time_minutes: u32,
+
+    /// Number of headings
+    pub headings: usize,
+
+    /// Number of images
+    pub images: usize,
+
+    /// Number of links
+    pub links: usize,
+
+    /// Code fence languages, sorted
+    pub code_languages: Vec<String>,
+
+    /// Uses of each JSX component, by name
+    pub components: std::collections::BTreeMap<String, usize>,
+}
+
+impl From<bunny_mdx::DocumentStats> for WasmDocumentStats {
+    fn from(stats: bunny_mdx::DocumentStats) -> Self {
+        Self {
+            words: stats.words,
+            characters: stats.characters,
+            reading_time_minutes: stats.reading_time_minutes,
+            headings: stats.headings,
+            images: stats.images,
+            links: stats.links,
+            code_languages: stats.code_languages.into_iter().collect(),
+            components: stats.components,
+        }
+    }
 }
 
 /// Frontmatter data (serializable for JS)
@@ -299,12 +466,14 @@ pub fn compile_mdx(source: &str, options: Option<WasmMdxOptions>) -> Result<JsVa
     // Build WASM result
     let wasm_result = WasmMdxResult {
         code: result.code,
+        map: result.map,
         frontmatter,
         images: result.images,
         named_exports: result.named_exports,
         reexports: result.reexports,
         imports: result.imports,
         default_export: result.default_export,
+        stats: result.stats.into(),
     };
 
     // Serialize to JS value
@@ -419,6 +588,8 @@ mod tests {
         assert!(result_obj.named_exports.is_empty() || !result_obj.named_exports.is_empty());
         assert!(result_obj.reexports.is_empty() || !result_obj.reexports.is_empty());
         assert!(result_obj.imports.is_empty() || !result_obj.imports.is_empty());
+        assert_eq!(result_obj.stats.headings, 1);
+        assert_eq!(result_obj.stats.words, 1);
     }
 
     // ============================================================================
@@ -499,6 +670,7 @@ mod tests {
         wasm_options.set_math(true);
         wasm_options.set_filepath("test.mdx".to_string());
         wasm_options.set_jsx_runtime("custom/jsx-runtime".to_string());
+        wasm_options.set_words_per_minute(300);
 
         let rust_options: MdxCompileOptions = (&wasm_options).into();
         assert!(rust_options.gfm);
@@ -506,6 +678,7 @@ mod tests {
         assert!(rust_options.math);
         assert_eq!(rust_options.filepath, Some("test.mdx".to_string()));
         assert_eq!(rust_options.jsx_runtime, "custom/jsx-runtime");
+        assert_eq!(rust_options.words_per_minute, 300);
     }
 
     #[test]
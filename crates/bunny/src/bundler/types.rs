@@ -1,6 +1,7 @@
 //! Types for the bunny runtime bundling API
 
 use bunny_mdx::{FrontmatterData, MdxCompileOptions};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Options for runtime MDX bundling
@@ -141,7 +142,7 @@ impl BundleMdxOptions {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundleMdxResult {
     /// Executable JavaScript bundle
     ///
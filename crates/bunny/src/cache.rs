@@ -0,0 +1,223 @@
+//! Storage backends for the [`Compiler`](crate::Compiler) cache
+//!
+//! Entries are opaque bytes under a hex content hash, so a store never needs
+//! to know what was compiled. Caching is best effort: a store that fails to
+//! read or write just reports a miss the next time.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Where the [`Compiler`](crate::Compiler) keeps compiled results
+///
+/// Implement this to share a cache between processes (Redis, S3, ...).
+pub trait CacheStore: Send + Sync {
+    /// Look up an entry
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+
+    /// Store an entry, replacing any previous value
+    fn put(&self, key: &str, value: Vec<u8>);
+}
+
+/// In-memory cache that evicts the least recently used entry when full
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<LruEntries>,
+}
+
+#[derive(Debug, Default)]
+struct LruEntries {
+    /// Values with the tick they were last used at
+    values: HashMap<String, (u64, Vec<u8>)>,
+    /// Keys by the tick they were last used at, least recent first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl LruEntries {
+    fn get(&mut self, key: &str) -> Option<Vec<u8>> {
+        let tick = self.next_tick();
+        let (used, value) = self.values.get_mut(key)?;
+        let key = self.order.remove(used).expect("order has every key");
+        *used = tick;
+        let value = value.clone();
+        self.order.insert(tick, key);
+        Some(value)
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) {
+        let tick = self.next_tick();
+        if let Some((used, _)) = self.values.insert(key.to_string(), (tick, value)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(tick, key.to_string());
+    }
+
+    /// Remove the least recently used entry
+    fn evict(&mut self) {
+        if let Some((_, key)) = self.order.pop_first() {
+            self.values.remove(&key);
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+impl MemoryCache {
+    /// Create a cache holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(LruEntries::default()),
+        }
+    }
+
+    /// Number of cached entries
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("Cache mutex poisoned")
+            .values
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryCache {
+    /// Holds up to 1024 entries
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl CacheStore for MemoryCache {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.entries.lock().expect("Cache mutex poisoned").get(key)
+    }
+
+    fn put(&self, key: &str, value: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("Cache mutex poisoned");
+        entries.put(key, value);
+        while entries.values.len() > self.capacity {
+            entries.evict();
+        }
+    }
+}
+
+/// On-disk cache with one file per entry in a directory
+///
+/// Entries survive restarts. Nothing is evicted; delete the directory to
+/// clear it.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Use `dir` for cache files (created on first write)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+impl CacheStore for DiskCache {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        fs::read(self.path(key)).ok()
+    }
+
+    fn put(&self, key: &str, value: Vec<u8>) {
+        // Write to a temporary file first so concurrent readers never see a
+        // partial entry. Each write gets its own file, as threads may store
+        // the same key at once.
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let write = WRITES.fetch_add(1, Ordering::Relaxed);
+        let temp = self
+            .dir
+            .join(format!("{}.{}.{}.tmp", key, std::process::id(), write));
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&temp, value))
+            .and_then(|_| fs::rename(&temp, self.path(key)));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", b"1".to_vec());
+        cache.put("b", b"2".to_vec());
+        assert_eq!(cache.get("a"), Some(b"1".to_vec()));
+
+        // `b` is now the least recently used entry
+        cache.put("c", b"3".to_vec());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(b"1".to_vec()));
+        assert_eq!(cache.get("c"), Some(b"3".to_vec()));
+
+        cache.put("c", b"4".to_vec());
+        assert_eq!(cache.get("c"), Some(b"4".to_vec()));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_disk_cache_concurrent_puts() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path());
+        let values: Vec<Vec<u8>> = (0..8u8).map(|n| vec![n; 1024 * 1024]).collect();
+
+        std::thread::scope(|scope| {
+            for value in &values {
+                let (cache, values) = (&cache, &values);
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        cache.put("key", value.clone());
+                        // Whichever write won, the entry is complete
+                        let entry = cache.get("key").unwrap();
+                        assert!(values.contains(&entry));
+                    }
+                });
+            }
+        });
+
+        // No temporary files are left behind
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn test_disk_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path().join("cache"));
+        assert_eq!(cache.get("key"), None);
+
+        cache.put("key", b"value".to_vec());
+        assert_eq!(cache.get("key"), Some(b"value".to_vec()));
+
+        // A new store over the same directory sees the entry
+        let cache = DiskCache::new(dir.path().join("cache"));
+        assert_eq!(cache.get("key"), Some(b"value".to_vec()));
+    }
+}
@@ -0,0 +1,275 @@
+//! Reusable compiler with a content-hash cache
+
+use std::sync::Arc;
+
+use bunny_mdx::{compile, MdxCompileOptions, MdxCompileResult, MdxError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::cache::{CacheStore, MemoryCache};
+
+#[cfg(feature = "bundler")]
+use crate::bundler::{bundle_mdx, BundleMdxOptions, BundleMdxResult};
+#[cfg(feature = "bundler")]
+use std::collections::HashMap;
+
+/// Compiles MDX with fixed options and caches the results
+///
+/// Results are cached under a hash of the source and
+/// [`MdxCompileOptions::cache_key`], which covers the options and the
+/// configuration of each plugin, so unchanged documents are only compiled
+/// once. The cache is in memory by default; use [`Compiler::with_cache`] for
+/// a [`DiskCache`](crate::DiskCache) or your own [`CacheStore`]. Errors are
+/// never cached.
+///
+/// Options without a cache key (a URL rewrite closure, or a plugin that
+/// doesn't implement [`MdxPlugin::cache_key`](bunny_mdx::MdxPlugin::cache_key))
+/// disable caching: every call compiles.
+///
+/// ```
+/// use bunny::{Compiler, MdxCompileOptions};
+///
+/// let compiler = Compiler::new(MdxCompileOptions::new().with_default_plugins());
+///
+/// let first = compiler.compile("# Hello")?;
+/// let second = compiler.compile("# Hello")?; // from the cache
+/// assert_eq!(first.code, second.code);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Compiler {
+    options: MdxCompileOptions,
+    cache: Arc<dyn CacheStore>,
+    /// Options and plugin configuration, hashed into every key (`None`
+    /// when results can't be cached)
+    fingerprint: Option<String>,
+}
+
+impl Compiler {
+    /// Create a compiler with an in-memory LRU cache
+    pub fn new(options: MdxCompileOptions) -> Self {
+        let fingerprint = options
+            .cache_key()
+            .map(|key| format!("bunny {}\n{}", env!("CARGO_PKG_VERSION"), key));
+        Self {
+            options,
+            cache: Arc::new(MemoryCache::default()),
+            fingerprint,
+        }
+    }
+
+    /// Use `cache` to store results
+    pub fn with_cache(mut self, cache: impl CacheStore + 'static) -> Self {
+        self.cache = Arc::new(cache);
+        self
+    }
+
+    pub fn options(&self) -> &MdxCompileOptions {
+        &self.options
+    }
+
+    /// Compile MDX to JSX, or return the cached result
+    pub fn compile(&self, source: &str) -> Result<MdxCompileResult, Box<MdxError>> {
+        let key = self.key("compile", &[source]);
+        if let Some(result) = self.cached(key.as_deref()) {
+            return Ok(result);
+        }
+        let result = compile(source, self.options.clone())?;
+        self.store(key.as_deref(), &result);
+        Ok(result)
+    }
+
+    /// Compile and bundle MDX with `files` as its virtual filesystem, or
+    /// return the cached bundle
+    #[cfg(feature = "bundler")]
+    pub async fn bundle(
+        &self,
+        source: &str,
+        files: HashMap<String, String>,
+    ) -> anyhow::Result<BundleMdxResult> {
+        let mut paths: Vec<&String> = files.keys().collect();
+        paths.sort();
+        let mut parts = vec![source];
+        for path in paths {
+            parts.push(path);
+            parts.push(&files[path]);
+        }
+        let key = self.key("bundle", &parts);
+        if let Some(result) = self.cached(key.as_deref()) {
+            return Ok(result);
+        }
+
+        let result = bundle_mdx(BundleMdxOptions {
+            source: source.to_string(),
+            files,
+            mdx_options: Some(self.options.clone()),
+        })
+        .await?;
+        self.store(key.as_deref(), &result);
+        Ok(result)
+    }
+
+    /// Hex hash of the operation, the fingerprint and `parts` (`None` when
+    /// results aren't cached)
+    fn key(&self, kind: &str, parts: &[&str]) -> Option<String> {
+        let fingerprint = self.fingerprint.as_deref()?;
+        let mut hasher = blake3::Hasher::new();
+        for part in [kind, fingerprint].iter().chain(parts) {
+            // Length prefixes keep ("ab", "c") and ("a", "bc") apart
+            hasher.update(&(part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        Some(hasher.finalize().to_hex().to_string())
+    }
+
+    fn cached<T: DeserializeOwned>(&self, key: Option<&str>) -> Option<T> {
+        let bytes = self.cache.get(key?)?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn store<T: Serialize>(&self, key: Option<&str>, value: &T) {
+        let Some(key) = key else {
+            return;
+        };
+        if let Ok(bytes) = serde_json::to_vec(value) {
+            self.cache.put(key, bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::DiskCache;
+    use bunny_mdx::plugins::{GithubSlugger, HeadingIdPlugin};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts writes to another store
+    struct CountingCache<S> {
+        inner: S,
+        puts: Arc<AtomicUsize>,
+    }
+
+    impl<S: CacheStore> CountingCache<S> {
+        fn new(inner: S) -> Self {
+            Self {
+                inner,
+                puts: Arc::default(),
+            }
+        }
+    }
+
+    impl<S: CacheStore> CacheStore for CountingCache<S> {
+        fn get(&self, key: &str) -> Option<Vec<u8>> {
+            self.inner.get(key)
+        }
+
+        fn put(&self, key: &str, value: Vec<u8>) {
+            self.puts.fetch_add(1, Ordering::SeqCst);
+            self.inner.put(key, value);
+        }
+    }
+
+    #[test]
+    fn test_results_are_cached_by_source() {
+        let cache = CountingCache::new(MemoryCache::default());
+        let puts = cache.puts.clone();
+        let compiler =
+            Compiler::new(MdxCompileOptions::new().with_default_plugins()).with_cache(cache);
+
+        let first = compiler.compile("# Hi\n\n![a](/a.png)").unwrap();
+        let second = compiler.compile("# Hi\n\n![a](/a.png)").unwrap();
+        assert_eq!(first.code, second.code);
+        assert_eq!(second.images, ["/a.png"]);
+        assert_eq!(puts.load(Ordering::SeqCst), 1);
+
+        compiler.compile("# Bye").unwrap();
+        assert_eq!(puts.load(Ordering::SeqCst), 2);
+
+        // Errors are not cached
+        assert!(compiler.compile("{foo(}").is_err());
+        assert_eq!(puts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_keys_depend_on_options_and_plugins() {
+        let plain = Compiler::new(MdxCompileOptions::new());
+        let gfm = Compiler::new(MdxCompileOptions::new().with_all_features());
+        let plugins = Compiler::new(MdxCompileOptions::new().with_default_plugins());
+
+        let key = plain.key("compile", &["# Hi"]).unwrap();
+        assert_eq!(
+            Some(&key),
+            Compiler::new(MdxCompileOptions::new())
+                .key("compile", &["# Hi"])
+                .as_ref()
+        );
+        assert_ne!(Some(&key), plain.key("compile", &["# Hi!"]).as_ref());
+        assert_ne!(Some(&key), plain.key("bundle", &["# Hi"]).as_ref());
+        assert_ne!(Some(&key), gfm.key("compile", &["# Hi"]).as_ref());
+        assert_ne!(Some(&key), plugins.key("compile", &["# Hi"]).as_ref());
+    }
+
+    #[test]
+    fn test_keys_depend_on_plugin_configuration() {
+        let mdx = "# Hello World\n\n## Rust & TypeScript";
+        let dir = tempfile::tempdir().unwrap();
+        let plain = Compiler::new(MdxCompileOptions::new().with_plugin(HeadingIdPlugin::new()))
+            .with_cache(DiskCache::new(dir.path()));
+        plain.compile(mdx).unwrap();
+
+        let options = MdxCompileOptions::new().with_plugin(
+            HeadingIdPlugin::new()
+                .with_toc_export()
+                .with_slugger(GithubSlugger),
+        );
+        let configured = Compiler::new(options.clone()).with_cache(DiskCache::new(dir.path()));
+        let result = configured.compile(mdx).unwrap();
+        assert_eq!(result.code, bunny_mdx::compile(mdx, options).unwrap().code);
+        assert!(result.code.contains("export const toc"));
+        assert!(result.code.contains("rust--typescript"));
+    }
+
+    /// A plugin without a cache key
+    struct Opaque;
+
+    impl bunny_mdx::MdxPlugin for Opaque {
+        fn name(&self) -> &'static str {
+            "opaque"
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_uncacheable_options_skip_the_cache() {
+        let cache = CountingCache::new(MemoryCache::default());
+        let puts = cache.puts.clone();
+        let compiler =
+            Compiler::new(MdxCompileOptions::new().with_plugin(Opaque)).with_cache(cache);
+        compiler.compile("# Hi").unwrap();
+        compiler.compile("# Hi").unwrap();
+        assert_eq!(puts.load(Ordering::SeqCst), 0);
+
+        let policy = bunny_mdx::UrlPolicy::new().with_rewrite(|url| url.to_string());
+        let compiler = Compiler::new(MdxCompileOptions::new().with_url_policy(policy));
+        assert_eq!(compiler.key("compile", &["# Hi"]), None);
+    }
+
+    #[test]
+    fn test_disk_cache_is_shared_between_compilers() {
+        let mdx = "---\ntitle: Hi\n---\n\n# Hi";
+        let dir = tempfile::tempdir().unwrap();
+        let first = Compiler::new(MdxCompileOptions::new()).with_cache(DiskCache::new(dir.path()));
+        let result = first.compile(mdx).unwrap();
+
+        let cache = CountingCache::new(DiskCache::new(dir.path()));
+        let puts = cache.puts.clone();
+        let second = Compiler::new(MdxCompileOptions::new()).with_cache(cache);
+        let cached = second.compile(mdx).unwrap();
+        assert_eq!(cached.code, result.code);
+        assert_eq!(cached.frontmatter, result.frontmatter);
+        assert_eq!(puts.load(Ordering::SeqCst), 0);
+    }
+}
@@ -94,9 +94,11 @@
 //!
 //! ## Performance Considerations
 //!
-//! - **Runtime overhead**: Bundling happens at request time, add caching!
+//! - **Runtime overhead**: Bundling happens at request time; a [`Compiler`]
+//!   caches compiled and bundled results by content hash
 //! - **Memory usage**: Bundler runs in-memory
-//! - **Scaling**: Use caching layer (Redis, in-memory) for production
+//! - **Scaling**: Use [`DiskCache`] or your own [`CacheStore`] (Redis, S3)
+//!   to share the cache between processes
 //!
 //! ## Architecture
 //!
@@ -112,9 +114,18 @@
 
 // Re-export bunny-mdx types and functions (always available)
 pub use bunny_mdx::{
-    compile, FrontmatterData, FrontmatterFormat, MdxCompileOptions, MdxCompileResult, MdxError,
+    compile, compile_from_mdast, compile_many, compile_to_html, extract_text, mdast, parse,
+    FrontmatterData, FrontmatterFormat, FrontmatterSchema, HtmlOptions, MdxCompileOptions,
+    MdxCompileResult, MdxError, MdxHtmlResult, MdxInput, SafeExpressions, SafeMode, TextOptions,
+    UnknownComponents, UrlPolicy, UrlViolation,
 };
 
+pub mod cache;
+mod compiler;
+
+pub use cache::{CacheStore, DiskCache, MemoryCache};
+pub use compiler::Compiler;
+
 // Convenience wrapper for compile function
 use anyhow::Result;
 use bunny_mdx::MdxCompileOptions as Options;
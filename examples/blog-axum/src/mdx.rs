@@ -17,7 +17,8 @@ impl MdxCompiler {
     pub fn new() -> Self {
         Self {
             options: MdxCompileOptions::new()
-                .with_jsx_runtime("react/jsx-runtime"),
+                .with_jsx_runtime("react/jsx-runtime")
+                .with_default_plugins(),
         }
     }
 